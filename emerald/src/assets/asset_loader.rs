@@ -5,10 +5,14 @@ use crate::ent::load_ent_from_toml;
 use crate::font::Font;
 use crate::font::FontImage;
 use crate::font::FontKey;
-use crate::rendering::components::Sprite;
+use crate::material::get_material_key;
+use crate::material::MaterialDescriptor;
+use crate::material::MaterialKey;
+use crate::rendering::components::{NineSlice, NineSliceBorder, ParticleEmitter, Sprite};
 use crate::rendering_engine::RenderingEngine;
 use crate::texture::get_texture_key;
 use crate::texture::TextureKey;
+use crate::texture::TextureOptions;
 use crate::*;
 
 use std::collections::HashMap;
@@ -176,14 +180,21 @@ impl<'c> AssetLoader<'c> {
     pub fn aseprite<T: AsRef<str>>(&mut self, path: T) -> Result<Aseprite, EmeraldError> {
         let path = path.as_ref();
         let data = self.asset_bytes(path)?;
-        Aseprite::new(
+        let aseprite = Aseprite::new(
             &self.rendering_engine.bind_group_layouts,
             &self.rendering_engine.device,
             &self.rendering_engine.queue,
             self.asset_engine,
             path,
             data,
-        )
+        )?;
+
+        if self.rendering_engine.settings.texture_atlas.pack_on_load {
+            self.rendering_engine
+                .pack_textures(&aseprite.textures(), self.asset_engine)?;
+        }
+
+        Ok(aseprite)
     }
 
     /// Loads an exported Aseprite sprite sheet. The animations json file should
@@ -211,9 +222,38 @@ impl<'c> AssetLoader<'c> {
             return Ok(key);
         }
 
+        let data = self.asset_bytes(path)?;
+        let texture_key = self
+            .rendering_engine
+            .load_texture(path, self.asset_engine, &data)?;
+
+        if self.rendering_engine.settings.texture_atlas.pack_on_load {
+            self.rendering_engine
+                .pack_textures(std::slice::from_ref(&texture_key), self.asset_engine)?;
+        }
+
+        Ok(texture_key)
+    }
+
+    /// Loads a texture with its own filtering, wrapping and mipmaps.
+    /// If the texture was already loaded, only its sampler is replaced.
+    /// Textures loaded with options are never packed into the texture atlas.
+    pub fn texture_with_options<T: AsRef<str>>(
+        &mut self,
+        path: T,
+        options: TextureOptions,
+    ) -> Result<TextureKey, EmeraldError> {
+        let path: &str = path.as_ref();
+
+        if let Some(key) = get_texture_key(self.asset_engine, path) {
+            self.rendering_engine
+                .set_texture_sampler(&key, options.sampler, self.asset_engine)?;
+            return Ok(key);
+        }
+
         let data = self.asset_bytes(path)?;
         self.rendering_engine
-            .load_texture(path, &mut self.asset_engine, &data)
+            .load_texture_with_options(path, self.asset_engine, &data, options)
     }
 
     /// Creating render textures is slightly expensive and should be used conservatively.
@@ -224,12 +264,110 @@ impl<'c> AssetLoader<'c> {
             .create_render_texture(w as _, h as _, &mut self.asset_engine)
     }
 
+    /// Loads a material from the WGSL fragment shader at the given path.
+    /// See `MaterialDescriptor` for the uniforms and textures given to the shader.
+    pub fn material<T: AsRef<str>>(
+        &mut self,
+        path: T,
+        descriptor: MaterialDescriptor,
+    ) -> Result<MaterialKey, EmeraldError> {
+        let path: &str = path.as_ref();
+
+        if let Some(key) = get_material_key(self.asset_engine, path) {
+            return Ok(key);
+        }
+
+        let source = self.string(path)?;
+        self.material_from_source(path, &source, descriptor)
+    }
+
+    /// Creates a material from the source of a WGSL fragment shader.
+    /// The shader must define `fs_main(in: VertexOutput) -> @location(0) vec4<f32>`,
+    /// and can sample the drawn texture through `t_diffuse` and `s_diffuse`.
+    pub fn material_from_source(
+        &mut self,
+        label: &str,
+        source: &str,
+        descriptor: MaterialDescriptor,
+    ) -> Result<MaterialKey, EmeraldError> {
+        self.rendering_engine
+            .create_material(label, source, descriptor, self.asset_engine)
+    }
+
     pub fn sprite<T: AsRef<str>>(&mut self, path: T) -> Result<Sprite, EmeraldError> {
         let path: &str = path.as_ref();
         let texture_key = self.texture(path)?;
         Ok(Sprite::from_texture(texture_key))
     }
 
+    /// Loads a texture as a nine-slice with the given border, drawn at the given size.
+    pub fn nine_slice<T: AsRef<str>>(
+        &mut self,
+        path: T,
+        border: NineSliceBorder,
+        size: Vector2<f32>,
+    ) -> Result<NineSlice, EmeraldError> {
+        let texture_key = self.texture(path)?;
+        Ok(NineSlice::new(texture_key, border, size))
+    }
+
+    /// Loads a nine-slice from a slice of an Aseprite file that has a 9-slice center.
+    /// The nine-slice is drawn from the frame the slice starts on, at the size of the slice.
+    #[cfg(feature = "aseprite")]
+    pub fn nine_slice_from_aseprite<T: AsRef<str>, S: AsRef<str>>(
+        &mut self,
+        path: T,
+        slice_name: S,
+    ) -> Result<NineSlice, EmeraldError> {
+        let path: &str = path.as_ref();
+        let data = self.asset_bytes(path)?;
+        let aseprite = asefile::AsepriteFile::read(std::io::Cursor::new(data))?;
+        let (frame_index, target, border) =
+            crate::rendering::components::get_aseprite_slice(&aseprite, slice_name.as_ref())?;
+        if frame_index >= aseprite.num_frames() {
+            return Err(EmeraldError::new(format!(
+                "Aseprite slice {:?} starts on frame {}, which does not exist",
+                slice_name.as_ref(),
+                frame_index
+            )));
+        }
+
+        // Shares the label of the frame textures loaded for an `Aseprite`.
+        let label = format!("{}#{}", path, frame_index);
+        let texture_key = match get_texture_key(self.asset_engine, &label) {
+            Some(key) => key,
+            None => {
+                let image = image::DynamicImage::ImageRgba8(aseprite.frame(frame_index).image());
+                crate::texture::Texture::from_image(
+                    &label,
+                    &self.rendering_engine.bind_group_layouts,
+                    self.asset_engine,
+                    &self.rendering_engine.device,
+                    &self.rendering_engine.queue,
+                    &image,
+                )?
+            }
+        };
+
+        let mut nine_slice = NineSlice::new(
+            texture_key,
+            border,
+            Vector2::new(target.width, target.height),
+        );
+        nine_slice.target = target;
+
+        Ok(nine_slice)
+    }
+
+    /// Loads a particle emitter preset, a toml file with the same settings as an ent's `particle_emitter`.
+    pub fn particle_emitter<T: AsRef<str>>(
+        &mut self,
+        path: T,
+    ) -> Result<ParticleEmitter, EmeraldError> {
+        let toml = self.string(path)?.parse::<toml::Value>()?;
+        crate::ent::ent_particle_emitter_loader::load_particle_emitter(self, &toml)
+    }
+
     /// Load the sound at the given path into the given mixer.
     /// Returns the sound handle to play the sound with.
     pub fn sound<T: AsRef<str>>(&mut self, path: T) -> Result<SoundKey, EmeraldError> {
@@ -1,4 +1,4 @@
-use crate::EmeraldError;
+use crate::{readback::CapturedImage, EmeraldError};
 use std::fs::File;
 use std::io::prelude::Write;
 
@@ -21,4 +21,13 @@ impl Writer {
         file.write_all(bytes)?;
         Ok(())
     }
+
+    /// Encodes the image as a png and writes it to the user file.
+    pub fn write_png<T: Into<String>>(
+        &mut self,
+        image: &CapturedImage,
+        relative_path: T,
+    ) -> Result<(), EmeraldError> {
+        self.write_to_user_file(&image.to_png()?, relative_path)
+    }
 }
@@ -105,10 +105,29 @@ impl<'c> Emerald<'c> {
 
     #[inline]
     pub fn screen_size(&self) -> (u32, u32) {
-        (
-            self.rendering_engine.size.width,
-            self.rendering_engine.size.height,
-        )
+        let size = self.rendering_engine.screen_size();
+        (size.width, size.height)
+    }
+
+    /// Converts a position on the screen, in pixels from its top left corner,
+    /// into the world translation drawn there by the world's active camera.
+    #[inline]
+    pub fn screen_to_world(
+        &self,
+        world: &crate::World,
+        screen_translation: &Translation,
+    ) -> Translation {
+        world.screen_to_world(self.screen_size(), screen_translation)
+    }
+
+    /// Converts a world translation into the position on the screen it's drawn at by the world's active camera.
+    #[inline]
+    pub fn world_to_screen(
+        &self,
+        world: &crate::World,
+        world_translation: &Translation,
+    ) -> Translation {
+        world.world_to_screen(self.screen_size(), world_translation)
     }
 
     #[inline]
@@ -4,7 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     texture::TextureKey,
-    tilemap::{get_tilemap_index, TileId, Tilemap, TilesetResource},
+    tilemap::{
+        get_tilemap_index, load_tile_collisions, TileCollision, TileId, Tilemap, TilesetResource,
+    },
     AssetLoader, Emerald, EmeraldError, World,
 };
 
@@ -293,7 +295,7 @@ impl AutoTileMapSchema {
             .into_iter()
             .map(|ruleset_schema| ruleset_schema.to_ruleset())
             .collect::<Result<Vec<AutoTileRuleset>, EmeraldError>>()?;
-        let texture = loader.texture(tileset_resource.texture)?;
+        let texture = loader.texture(tileset_resource.texture.clone())?;
         let tile_size = Vector2::new(
             texture.size().0 as usize / tileset_resource.width,
             texture.size().1 as usize / tileset_resource.height,
@@ -307,6 +309,7 @@ impl AutoTileMapSchema {
             self.height,
             rulesets,
         );
+        autotilemap.tilemap.tile_collisions = load_tile_collisions(&tileset_resource.collisions)?;
 
         for tile in self.tiles {
             autotilemap.set_tile(tile.x, tile.y)?;
@@ -359,8 +362,8 @@ impl AutoTilemap {
         }
     }
 
-    /// Bakes the inner tileset in accordance to the Autotilemap
-    /// TODO: feature(physics): Additionally bakes the colliders
+    /// Bakes the inner tileset in accordance to the Autotilemap.
+    /// Colliders built with a `TilemapColliderBuilder` are rebuilt by the `tilemap_collider_system`.
     pub fn bake(&mut self) -> Result<(), EmeraldError> {
         for x in 0..self.width() {
             for y in 0..self.height() {
@@ -487,6 +490,15 @@ impl AutoTilemap {
     pub fn get_tile_id(&self, x: usize, y: usize) -> Result<Option<TileId>, EmeraldError> {
         self.tilemap.get_tile(x, y)
     }
+
+    /// Sets how the given tile of the tileset collides, `None` makes the tile passable.
+    pub fn set_tile_collision(&mut self, tile_id: TileId, tile_collision: Option<TileCollision>) {
+        self.tilemap.set_tile_collision(tile_id, tile_collision)
+    }
+
+    pub fn get_tile_collision(&self, tile_id: TileId) -> Option<&TileCollision> {
+        self.tilemap.get_tile_collision(tile_id)
+    }
 }
 
 pub(crate) fn load_ent_autotilemap<'a>(
@@ -1,9 +1,84 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize, Serializer};
 
-use crate::{texture::TextureKey, *};
+use crate::{ent::Vec2f32Schema, texture::TextureKey, *};
 
 pub type TileId = usize;
 
+/// Describes how a tile of the tileset collides with physics bodies.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TileCollision {
+    /// The whole tile is solid.
+    Solid,
+    /// The whole tile is solid, but only when landed on from above.
+    OneWay,
+    /// A convex shape, with points given in pixels relative to the center of the tile.
+    Custom(Vec<Vector2<f32>>),
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TileCollisionSchema {
+    pub id: TileId,
+    /// options: "solid", "one_way", "custom"
+    pub shape: String,
+    /// Points of the custom convex shape, in pixels relative to the center of the tile.
+    #[serde(default)]
+    pub points: Option<Vec<Vec2f32Schema>>,
+}
+impl TileCollisionSchema {
+    fn to_tile_collision(&self) -> Result<TileCollision, EmeraldError> {
+        match self.shape.as_str() {
+            "solid" => Ok(TileCollision::Solid),
+            "one_way" => Ok(TileCollision::OneWay),
+            "custom" => match &self.points {
+                Some(points) if points.len() >= 3 => Ok(TileCollision::Custom(
+                    points.iter().map(|p| Vector2::new(p.x, p.y)).collect(),
+                )),
+                _ => Err(EmeraldError::new(format!(
+                    "Custom collision for tile {} requires at least 3 points.",
+                    self.id
+                ))),
+            },
+            _ => Err(EmeraldError::new(format!(
+                "{:?} is not a valid tile collision shape.",
+                self.shape
+            ))),
+        }
+    }
+
+    fn from_tile_collision(id: TileId, tile_collision: &TileCollision) -> Self {
+        let (shape, points) = match tile_collision {
+            TileCollision::Solid => ("solid", None),
+            TileCollision::OneWay => ("one_way", None),
+            TileCollision::Custom(points) => (
+                "custom",
+                Some(
+                    points
+                        .iter()
+                        .map(|p| Vec2f32Schema { x: p.x, y: p.y })
+                        .collect(),
+                ),
+            ),
+        };
+
+        TileCollisionSchema {
+            id,
+            shape: shape.to_string(),
+            points,
+        }
+    }
+}
+
+pub(crate) fn load_tile_collisions(
+    schemas: &[TileCollisionSchema],
+) -> Result<HashMap<TileId, TileCollision>, EmeraldError> {
+    schemas
+        .iter()
+        .map(|schema| Ok((schema.id, schema.to_tile_collision()?)))
+        .collect()
+}
+
 #[derive(Deserialize, Serialize)]
 struct TileSchema {
     x: usize,
@@ -18,6 +93,9 @@ pub struct TilesetResource {
     pub height: usize,
     /// Width in tiles
     pub width: usize,
+    /// Collision metadata for the tiles of this tileset.
+    #[serde(default)]
+    pub collisions: Vec<TileCollisionSchema>,
 }
 
 fn load_tileset_resource<T: Into<String>>(
@@ -75,6 +153,7 @@ impl TilemapSchema {
             self.width,
             self.height,
         );
+        tilemap.tile_collisions = load_tile_collisions(&resource.collisions)?;
         for tile in self.tiles {
             tilemap.set_tile(tile.x, tile.y, Some(tile.id))?;
         }
@@ -98,6 +177,9 @@ pub struct Tilemap {
     pub(crate) tilesheet_width: usize,
     // Height of tilesheet in tiles
     pub(crate) tilesheet_height: usize,
+    pub(crate) tile_collisions: HashMap<TileId, TileCollision>,
+    /// Incremented whenever the tiles or their collisions change.
+    pub(crate) generation: usize,
     pub z_index: f32,
     pub visible: bool,
 }
@@ -129,6 +211,8 @@ impl Tilemap {
             visible: true,
             tilesheet_height,
             tilesheet_width,
+            tile_collisions: HashMap::new(),
+            generation: 0,
         }
     }
 
@@ -159,7 +243,10 @@ impl Tilemap {
         let tile_index = get_tilemap_index(x, y, self.width, self.height)?;
 
         if let Some(tile_id) = self.tiles.get_mut(tile_index) {
-            *tile_id = new_tile;
+            if *tile_id != new_tile {
+                *tile_id = new_tile;
+                self.generation += 1;
+            }
 
             return Ok(());
         }
@@ -193,9 +280,36 @@ impl Tilemap {
         self.tile_size.y
     }
 
+    /// Returns the area covered by the tilemap when placed at the given translation.
+    /// Tiles are centered on their position, so the first tile extends half a tile past the translation.
+    pub fn bounds(&self, translation: &Translation) -> Rectangle {
+        let tile_width = self.tile_size.x as f32;
+        let tile_height = self.tile_size.y as f32;
+
+        Rectangle::new(
+            translation.x - tile_width / 2.0,
+            translation.y - tile_height / 2.0,
+            self.width as f32 * tile_width,
+            self.height as f32 * tile_height,
+        )
+    }
+
     pub fn set_tilesheet(&mut self, tilesheet: TextureKey) {
         self.tilesheet = tilesheet
     }
+
+    /// Sets how the given tile of the tileset collides, `None` makes the tile passable.
+    pub fn set_tile_collision(&mut self, tile_id: TileId, tile_collision: Option<TileCollision>) {
+        match tile_collision {
+            Some(tile_collision) => self.tile_collisions.insert(tile_id, tile_collision),
+            None => self.tile_collisions.remove(&tile_id),
+        };
+        self.generation += 1;
+    }
+
+    pub fn get_tile_collision(&self, tile_id: TileId) -> Option<&TileCollision> {
+        self.tile_collisions.get(&tile_id)
+    }
 }
 
 impl Serialize for Tilemap {
@@ -216,10 +330,20 @@ impl Serialize for Tilemap {
             });
         }
 
+        let mut collisions = self
+            .tile_collisions
+            .iter()
+            .map(|(id, tile_collision)| {
+                TileCollisionSchema::from_tile_collision(*id, tile_collision)
+            })
+            .collect::<Vec<TileCollisionSchema>>();
+        collisions.sort_by_key(|schema| schema.id);
+
         let resource = TilesetResource {
             height: self.tilesheet_height,
             width: self.tilesheet_width,
             texture: String::from(self.tilesheet.label()),
+            collisions,
         };
 
         let tilemap_schema = TilemapSchema {
@@ -274,7 +398,7 @@ pub(crate) fn load_ent_tilemap<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tilemap::{TileSchema, TilemapSchema};
+    use crate::tilemap::{load_tile_collisions, TileCollision, TileSchema, TilemapSchema};
 
     #[test]
     fn deser_tile() {
@@ -311,4 +435,39 @@ mod tests {
         assert_eq!(schema.height, 10);
         assert_eq!(&schema.tileset.as_ref().unwrap().texture, "tileset.png");
     }
+
+    #[test]
+    fn deser_tileset_collisions() {
+        let toml = r#"
+            width = 10
+            height = 10
+
+            [tileset]
+            texture = "tileset.png"
+            width = 2
+            height = 2
+
+            [[tileset.collisions]]
+            id = 0
+            shape = "solid"
+
+            [[tileset.collisions]]
+            id = 1
+            shape = "one_way"
+
+            [[tileset.collisions]]
+            id = 2
+            shape = "custom"
+            points = [{ x = -8.0, y = -8.0 }, { x = 8.0, y = -8.0 }, { x = 8.0, y = 8.0 }]
+        "#;
+        let schema: TilemapSchema = crate::toml::from_str(toml).unwrap();
+        let collisions = load_tile_collisions(&schema.tileset.unwrap().collisions).unwrap();
+
+        assert_eq!(collisions[&0], TileCollision::Solid);
+        assert_eq!(collisions[&1], TileCollision::OneWay);
+        match &collisions[&2] {
+            TileCollision::Custom(points) => assert_eq!(points.len(), 3),
+            _ => panic!("Expected a custom tile collision"),
+        }
+    }
 }
@@ -1,14 +1,14 @@
 use std::collections::VecDeque;
 
 use winit::{
-    dpi::PhysicalSize,
+    dpi::{PhysicalPosition, PhysicalSize},
     event::{ElementState, VirtualKeyCode, WindowEvent},
 };
 
 use crate::{
     profiling::profile_cache::ProfileCache, rendering_engine::RenderingEngine,
     resources::Resources, AssetEngine, AudioEngine, Emerald, EmeraldError, Game, GameSettings,
-    InputEngine, LoggingEngine,
+    InputEngine, LoggingEngine, Translation,
 };
 
 pub(crate) struct GameEngineContext {
@@ -105,7 +105,14 @@ impl GameEngine {
     }
 
     pub fn handle_cursor_move(&mut self, position: &winit::dpi::PhysicalPosition<f64>) {
-        self.input_engine.handle_cursor_move(position)
+        // Mouse positions are given on the screen the game draws to, which may have a virtual resolution.
+        let translation = self
+            .rendering_engine
+            .window_to_screen(&Translation::new(position.x as f32, position.y as f32));
+        self.input_engine.handle_cursor_move(&PhysicalPosition::new(
+            translation.x as f64,
+            translation.y as f64,
+        ))
     }
 
     pub fn handle_mouse_input(&mut self, button: &winit::event::MouseButton, state: &ElementState) {
@@ -22,20 +22,7 @@ pub fn screen_translation_to_world_translation(
     screen_translation: &Translation,
     world: &World,
 ) -> Translation {
-    let camera_pos = world
-        .get_active_camera()
-        .and_then(|id| world.get::<&Translation>(id.clone()).ok())
-        .map(|pos| *pos)
-        .unwrap_or_default();
-
-    // TODO(bombfuse): take the camera zoom level into account when translating
-    let screen_size = Translation::new(screen_size.0 as f32, screen_size.1 as f32);
-    let normalized_screen_translation = Translation::new(
-        screen_translation.x - screen_size.x / 2.0,
-        screen_size.y - screen_translation.y - screen_size.y / 2.0,
-    );
-
-    camera_pos + normalized_screen_translation
+    world.screen_to_world(screen_size, screen_translation)
 }
 
 /// Describes touch-screen input state.
@@ -1,4 +1,4 @@
-use crate::{texture::TextureKey, Rectangle};
+use crate::{rendering::components::NineSlice, texture::TextureKey, Rectangle};
 
 pub struct UIButton {
     pub pressed_texture: TextureKey,
@@ -13,6 +13,11 @@ pub struct UIButton {
     pub(crate) is_pressed: bool,
     pub(crate) was_pressed: bool,
 
+    /// Drawn instead of the pressed texture, and used for the pressed area of the button.
+    pub pressed_nine_slice: Option<NineSlice>,
+    /// Drawn instead of the unpressed texture, and used for the unpressed area of the button.
+    pub unpressed_nine_slice: Option<NineSlice>,
+
     pub z_index: f32,
     pub visible: bool,
 }
@@ -25,11 +30,22 @@ impl UIButton {
             custom_unpressed_bounding_box: None,
             is_pressed: false,
             was_pressed: false,
+            pressed_nine_slice: None,
+            unpressed_nine_slice: None,
             z_index: 0.0,
             visible: true,
         }
     }
 
+    /// A button drawn with nine-slices, so it can be any size without distorting its borders.
+    pub fn from_nine_slices(pressed: NineSlice, unpressed: NineSlice) -> Self {
+        let mut button = UIButton::new(pressed.texture().clone(), unpressed.texture().clone());
+        button.pressed_nine_slice = Some(pressed);
+        button.unpressed_nine_slice = Some(unpressed);
+
+        button
+    }
+
     pub fn is_pressed(&self) -> bool {
         self.is_pressed
     }
@@ -62,6 +78,14 @@ impl UIButton {
         self.was_pressed = false;
     }
 
+    pub(crate) fn current_nine_slice(&self) -> Option<&NineSlice> {
+        if self.is_pressed() {
+            self.pressed_nine_slice.as_ref()
+        } else {
+            self.unpressed_nine_slice.as_ref()
+        }
+    }
+
     pub(crate) fn current_texture(&self) -> &TextureKey {
         if self.is_pressed() {
             &self.pressed_texture
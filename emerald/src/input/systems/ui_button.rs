@@ -1,45 +1,59 @@
 use std::collections::HashMap;
 
 use crate::{
-    screen_translation_to_world_translation,
+    render_layers::{LayerProjection, RenderLayer, RenderLayerDef, DEFAULT_RENDER_LAYER},
     texture::Texture,
     transform::{Transform, Translation},
     Emerald, TouchState, UIButton, World,
 };
 
+/// Positions of the mouse and of each touch on a render layer.
+type LayerPositions = (Translation, HashMap<u64, Translation>);
+
 /// Updates the status of UI Buttons.
 /// Presses the button if the user has pressed it, etc...
+/// Buttons are hit-tested on their render layer, so buttons on screen-space layers ignore the camera.
 pub fn ui_button_system(emd: &mut Emerald<'_>, world: &mut World) {
     let mouse = emd.input().mouse();
     let touches = emd.input().touches().clone();
     let screen_size = emd.screen_size();
-    let mouse_position = screen_translation_to_world_translation(
-        (screen_size.0 as u32, screen_size.1 as u32),
-        &mouse.translation,
+
+    let layers = world.render_layers().clone();
+    let layer_positions: Vec<Option<LayerPositions>> = layers
+        .iter()
+        .map(|layer| get_layer_positions(world, layer, screen_size, &mouse.translation, &touches))
+        .collect();
+    // Used for buttons on the default layer, when the world doesn't have one.
+    let default_positions = get_layer_positions(
         world,
+        &RenderLayerDef::new(DEFAULT_RENDER_LAYER, LayerProjection::ActiveCamera),
+        screen_size,
+        &mouse.translation,
+        &touches,
     );
 
-    let touch_world_positions: HashMap<u64, Translation> = touches
+    for (_, (ui_button, transform, render_layer)) in world
+        .query::<(&mut UIButton, &Transform, Option<&RenderLayer>)>()
         .iter()
-        .map(|(id, touch_state)| {
-            let sc = (screen_size.0 as u32, screen_size.1 as u32);
-            (
-                *id,
-                screen_translation_to_world_translation(sc, &touch_state.translation, world),
-            )
-        })
-        .collect();
+    {
+        let positions = match layers.resolve(render_layer) {
+            Some(index) => &layer_positions[index],
+            None => &default_positions,
+        };
 
-    for (_, (ui_button, transform)) in world.query::<(&mut UIButton, &Transform)>().iter() {
-        let button_check =
-            is_translation_inside_button(emd, &ui_button, &transform, &mouse_position)
-                || check_touches_overlap_button(
-                    emd,
-                    &touches,
-                    &touch_world_positions,
-                    &ui_button,
-                    &transform,
-                );
+        let button_check = match positions {
+            Some((mouse_position, touch_positions)) => {
+                is_translation_inside_button(emd, ui_button, transform, mouse_position)
+                    || check_touches_overlap_button(
+                        emd,
+                        &touches,
+                        touch_positions,
+                        ui_button,
+                        transform,
+                    )
+            }
+            None => false,
+        };
 
         if button_check {
             let press = mouse.left.is_pressed
@@ -59,6 +73,27 @@ pub fn ui_button_system(emd: &mut Emerald<'_>, world: &mut World) {
     }
 }
 
+/// Returns `None` if the camera of the layer is missing.
+fn get_layer_positions(
+    world: &World,
+    layer: &RenderLayerDef,
+    screen_size: (u32, u32),
+    mouse_translation: &Translation,
+    touches: &HashMap<u64, TouchState>,
+) -> Option<LayerPositions> {
+    let mouse_position = layer.screen_to_layer(world, screen_size, mouse_translation)?;
+    let touch_positions = touches
+        .iter()
+        .filter_map(|(id, touch_state)| {
+            layer
+                .screen_to_layer(world, screen_size, &touch_state.translation)
+                .map(|position| (*id, position))
+        })
+        .collect();
+
+    Some((mouse_position, touch_positions))
+}
+
 fn check_touches_overlap_button(
     emd: &mut Emerald<'_>,
     touches: &HashMap<u64, TouchState>,
@@ -85,6 +120,14 @@ fn is_translation_inside_button(
     ui_button_transform: &Transform,
     translation: &Translation,
 ) -> bool {
+    if let Some(nine_slice) = ui_button.current_nine_slice() {
+        let bounds = nine_slice.bounds(&ui_button_transform.translation);
+        return translation.x >= bounds.left()
+            && translation.x <= bounds.right()
+            && translation.y >= bounds.bottom()
+            && translation.y <= bounds.top();
+    }
+
     let mut is_inside = false;
 
     let texture_key = ui_button.current_texture();
@@ -1,7 +1,18 @@
+pub mod blend_mode;
 pub mod components;
 pub mod font;
+pub(crate) mod instancing;
+pub mod lighting;
+pub mod material;
+pub mod physics_debug;
+pub mod post_process;
+pub mod readback;
+pub mod render_layers;
 pub mod render_settings;
 pub(crate) mod rendering_engine;
 pub(crate) mod rendering_handler;
 pub(crate) mod shaders;
 pub mod texture;
+pub mod texture_atlas;
+pub mod viewport;
+pub(crate) mod virtual_screen;
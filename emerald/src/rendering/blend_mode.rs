@@ -0,0 +1,69 @@
+/// How a drawable is combined with what was drawn before it.
+/// Drawables using a material are blended the way the material was created with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    #[default]
+    Alpha,
+    /// Adds the color to the frame, for glows and fire.
+    Additive,
+    /// Darkens the frame by the color, for shadows.
+    Multiply,
+    /// Lightens the frame by the inverse of the color.
+    Screen,
+    /// Alpha blending for textures whose colors are already multiplied by their alpha.
+    PremultipliedAlpha,
+    /// Replaces the frame, ignoring alpha.
+    Opaque,
+}
+impl BlendMode {
+    pub(crate) fn blend_state(&self) -> wgpu::BlendState {
+        match self {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => blend_state(wgpu::BlendFactor::SrcAlpha, wgpu::BlendFactor::One),
+            BlendMode::Multiply => {
+                blend_state(wgpu::BlendFactor::Dst, wgpu::BlendFactor::OneMinusSrcAlpha)
+            }
+            BlendMode::Screen => {
+                blend_state(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrc)
+            }
+            BlendMode::PremultipliedAlpha => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Opaque => wgpu::BlendState::REPLACE,
+        }
+    }
+}
+
+/// Blends color with the given factors, keeping the alpha of the frame as alpha blending does.
+fn blend_state(src_factor: wgpu::BlendFactor, dst_factor: wgpu::BlendFactor) -> wgpu::BlendState {
+    wgpu::BlendState {
+        color: wgpu::BlendComponent {
+            src_factor,
+            dst_factor,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha: wgpu::BlendState::ALPHA_BLENDING.alpha,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlendMode;
+
+    #[test]
+    fn blend_modes_have_distinct_blend_states() {
+        let modes = [
+            BlendMode::Alpha,
+            BlendMode::Additive,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::PremultipliedAlpha,
+            BlendMode::Opaque,
+        ];
+
+        assert_eq!(BlendMode::default(), BlendMode::Alpha);
+        for (i, a) in modes.iter().enumerate() {
+            for b in &modes[i + 1..] {
+                assert_ne!(a.blend_state(), b.blend_state(), "{:?} and {:?}", a, b);
+            }
+        }
+    }
+}
@@ -2,16 +2,28 @@
 pub mod aseprite;
 
 mod camera;
+mod camera_controller;
 mod color_rect;
 mod color_tri;
 mod label;
+mod light;
+mod nine_slice;
+mod particle_emitter;
+mod rich_text;
 mod sprite;
+mod vector_shape;
 
 #[cfg(feature = "aseprite")]
 pub use aseprite::*;
 
 pub use camera::*;
+pub use camera_controller::*;
 pub use color_rect::*;
 pub use color_tri::*;
 pub use label::*;
+pub use light::*;
+pub use nine_slice::*;
+pub use particle_emitter::*;
+pub use rich_text::*;
 pub use sprite::*;
+pub use vector_shape::*;
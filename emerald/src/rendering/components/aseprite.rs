@@ -1,14 +1,16 @@
 use std::sync::Arc;
 
-use crate::rendering_engine::BindGroupLayouts;
-use crate::texture::{get_texture_key, Texture};
+use crate::blend_mode::BlendMode;
+use crate::material::MaterialKey;
+use crate::rendering_engine::{BindGroupLayouts, QuadPlacement};
+use crate::texture::{get_texture_key, Texture, TextureKey};
 use crate::*;
 use crate::{Color, EmeraldError, Rectangle, Vector2, WHITE};
 
 use asefile::AnimationDirection;
 use image::DynamicImage;
 
-use super::Sprite;
+use super::{ParticleFrame, Sprite};
 
 #[derive(Clone, Debug)]
 pub struct Aseprite {
@@ -25,13 +27,77 @@ pub struct Aseprite {
     pub visible: bool,
     pub color: Color,
     pub centered: bool,
+    /// See `Sprite::flip_x`.
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// See `Sprite::pivot`.
+    pub pivot: Option<Vector2<f32>>,
+    /// See `Sprite::skew`.
+    pub skew: Vector2<f32>,
     pub z_index: f32,
+    /// Custom shader to draw with, instead of the default textured shader.
+    pub material: Option<MaterialKey>,
+    /// How the drawable is combined with what was drawn before it.
+    pub blend_mode: BlendMode,
+    /// Frames of normals, drawn alongside the frame with the same index, see `set_normal_map`.
+    normal_map: Option<Arc<AsepriteData>>,
 }
 impl Aseprite {
     pub(crate) fn get_sprite(&self) -> &Sprite {
         &self.get_frame().sprite
     }
 
+    pub(crate) fn placement(&self) -> QuadPlacement {
+        QuadPlacement {
+            centered: self.centered,
+            pivot: self.pivot,
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            skew: self.skew,
+            snap: true,
+        }
+    }
+
+    /// Shades the aseprite per pixel in lit worlds, using the frames of another aseprite as normal maps.
+    /// Each frame is shaded by the normal map frame with the same index.
+    pub fn set_normal_map(&mut self, normal_map: &Aseprite) {
+        self.normal_map = Some(normal_map.data.clone());
+    }
+
+    pub fn clear_normal_map(&mut self) {
+        self.normal_map = None;
+    }
+
+    /// The normal map of the current frame, if there is one.
+    pub(crate) fn get_normal_map_sprite(&self) -> Option<&Sprite> {
+        let normal_map = self.normal_map.as_ref()?;
+        normal_map
+            .frames
+            .get(self.get_frame_index())
+            .map(|frame| &frame.sprite)
+    }
+
+    /// The textures of every frame.
+    pub(crate) fn textures(&self) -> Vec<TextureKey> {
+        self.data
+            .frames
+            .iter()
+            .map(|frame| frame.sprite.texture_key.clone())
+            .collect()
+    }
+
+    /// The area of every frame, for drawing particles with.
+    pub(crate) fn particle_frames(&self) -> Vec<ParticleFrame> {
+        self.data
+            .frames
+            .iter()
+            .map(|frame| ParticleFrame {
+                texture_key: frame.sprite.texture_key.clone(),
+                target: frame.sprite.target,
+            })
+            .collect()
+    }
+
     pub(crate) fn new(
         bind_group_layouts: &BindGroupLayouts,
         device: &wgpu::Device,
@@ -77,8 +143,15 @@ impl Aseprite {
             offset: Vector2::new(0.0, 0.0),
             color: WHITE,
             centered: true,
+            flip_x: false,
+            flip_y: false,
+            pivot: None,
+            skew: Vector2::new(0.0, 0.0),
             z_index: 0.0,
             visible: true,
+            material: None,
+            blend_mode: BlendMode::Alpha,
+            normal_map: None,
         }
     }
 
@@ -86,13 +159,14 @@ impl Aseprite {
         self.current_tag_index.map(|idx| &self.data.tags[idx])
     }
 
-    fn get_frame(&self) -> &Frame {
-        let index = self
-            .get_current_tag()
+    fn get_frame_index(&self) -> usize {
+        self.get_current_tag()
             .map(|tag| tag.get_frame(self.frame_counter))
-            .unwrap_or_default();
+            .unwrap_or_default()
+    }
 
-        &self.data.frames[index]
+    fn get_frame(&self) -> &Frame {
+        &self.data.frames[self.get_frame_index()]
     }
 
     pub fn get_animation_name(&self) -> &str {
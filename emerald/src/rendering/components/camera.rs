@@ -1,8 +1,12 @@
 use crate::*;
 
+/// The camera the world is drawn through.
+/// The camera is positioned and rotated by the `Transform` of its entity.
 #[derive(Clone, Copy, Debug)]
 pub struct Camera {
+    /// Offset of the view on the screen, in pixels. Not affected by zoom or rotation.
     pub offset: Vector2<f32>,
+    /// Magnification of the world, ex. a zoom of 2.0 draws everything twice as large.
     pub zoom: f32,
     pub(crate) is_active: bool,
 }
@@ -15,3 +19,168 @@ impl Default for Camera {
         }
     }
 }
+
+/// Maps translations relative to the camera onto the screen,
+/// in pixels from the center of the screen with y pointing up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ViewTransform {
+    pub zoom: f32,
+    pub rotation: f32,
+    pub offset: Vector2<f32>,
+}
+impl Default for ViewTransform {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            rotation: 0.0,
+            offset: Vector2::new(0.0, 0.0),
+        }
+    }
+}
+impl ViewTransform {
+    pub fn new(camera: &Camera, camera_transform: &Transform) -> Self {
+        Self {
+            zoom: camera.zoom,
+            rotation: camera_transform.rotation,
+            offset: camera.offset,
+        }
+    }
+
+    pub fn apply(&self, point: Vector2<f32>) -> Vector2<f32> {
+        rotate(point, -self.rotation) * self.zoom + self.offset
+    }
+
+    pub fn inverse(&self, point: Vector2<f32>) -> Vector2<f32> {
+        rotate((point - self.offset) / self.zoom, self.rotation)
+    }
+}
+
+fn rotate(point: Vector2<f32>, rotation: f32) -> Vector2<f32> {
+    let (sin, cos) = rotation.sin_cos();
+    Vector2::new(cos * point.x - sin * point.y, sin * point.x + cos * point.y)
+}
+
+/// Returns the translation of the active camera, along with the view it draws the world through.
+pub(crate) fn get_active_camera_view(world: &World) -> (Translation, ViewTransform) {
+    world
+        .get_active_camera()
+        .and_then(|entity| get_camera_view(world, entity))
+        .unwrap_or_default()
+}
+
+/// Returns the translation of the given camera entity, along with the view it draws the world through.
+pub(crate) fn get_camera_view(
+    world: &World,
+    entity: Entity,
+) -> Option<(Translation, ViewTransform)> {
+    match (
+        world.get::<&Camera>(entity),
+        world.get::<&Transform>(entity),
+    ) {
+        (Ok(camera), Ok(transform)) => Some((
+            transform.translation,
+            ViewTransform::new(&camera, &transform),
+        )),
+        _ => None,
+    }
+}
+
+/// Converts a position on a screen of the given size, in pixels from the top left corner,
+/// into a world translation as seen through the active camera.
+pub(crate) fn screen_to_world(
+    world: &World,
+    screen_size: (u32, u32),
+    screen_translation: &Translation,
+) -> Translation {
+    let (camera_translation, view) = get_active_camera_view(world);
+    screen_to_world_through_view(&camera_translation, &view, screen_size, screen_translation)
+}
+
+/// Converts a world translation into a position on a screen of the given size,
+/// in pixels from the top left corner, as seen through the active camera.
+pub(crate) fn world_to_screen(
+    world: &World,
+    screen_size: (u32, u32),
+    world_translation: &Translation,
+) -> Translation {
+    let (camera_translation, view) = get_active_camera_view(world);
+    world_to_screen_through_view(&camera_translation, &view, screen_size, world_translation)
+}
+
+pub(crate) fn screen_to_world_through_view(
+    camera_translation: &Translation,
+    view: &ViewTransform,
+    screen_size: (u32, u32),
+    screen_translation: &Translation,
+) -> Translation {
+    let centered = Vector2::new(
+        screen_translation.x - screen_size.0 as f32 / 2.0,
+        screen_size.1 as f32 / 2.0 - screen_translation.y,
+    );
+    let relative = view.inverse(centered);
+
+    Translation::new(
+        camera_translation.x + relative.x,
+        camera_translation.y + relative.y,
+    )
+}
+
+pub(crate) fn world_to_screen_through_view(
+    camera_translation: &Translation,
+    view: &ViewTransform,
+    screen_size: (u32, u32),
+    world_translation: &Translation,
+) -> Translation {
+    let centered = view.apply(Vector2::new(
+        world_translation.x - camera_translation.x,
+        world_translation.y - camera_translation.y,
+    ));
+
+    Translation::new(
+        centered.x + screen_size.0 as f32 / 2.0,
+        screen_size.1 as f32 / 2.0 - centered.y,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{screen_to_world, world_to_screen, Camera};
+    use crate::{Transform, Translation, Vector2, World};
+
+    #[test]
+    fn screen_to_world_round_trips_through_zoom_rotation_and_offset() {
+        let mut world = World::new();
+        let mut transform = Transform::from_translation((100.0, -50.0));
+        transform.rotation = 0.7;
+        let camera = Camera {
+            offset: Vector2::new(12.0, -4.0),
+            zoom: 2.5,
+            ..Default::default()
+        };
+        let entity = world.spawn((transform, camera));
+        world.make_active_camera(entity).unwrap();
+
+        let screen_size = (640, 480);
+        let world_translation = Translation::new(130.0, -20.0);
+        let screen = world_to_screen(&world, screen_size, &world_translation);
+        let back = screen_to_world(&world, screen_size, &screen);
+
+        assert!((back.x - world_translation.x).abs() < 1e-3);
+        assert!((back.y - world_translation.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn zoom_scales_distance_from_camera() {
+        let mut world = World::new();
+        let camera = Camera {
+            zoom: 2.0,
+            ..Default::default()
+        };
+        let entity = world.spawn((Transform::default(), camera));
+        world.make_active_camera(entity).unwrap();
+
+        let screen = world_to_screen(&world, (200, 100), &Translation::new(10.0, 10.0));
+        assert_eq!(screen.x, 120.0);
+        assert_eq!(screen.y, 30.0);
+    }
+}
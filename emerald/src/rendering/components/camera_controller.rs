@@ -0,0 +1,311 @@
+use super::Camera;
+
+use crate::{tilemap::Tilemap, *};
+
+/// Drives the `Transform` and `Camera` of its entity, see `camera_controller_system`.
+/// Follows a target entity, keeps the view inside of level bounds, shakes and zooms smoothly.
+#[derive(Clone, Debug)]
+pub struct CameraController {
+    /// The entity to follow. The camera stays in place while there is no target.
+    pub target: Option<Entity>,
+    /// How quickly the camera catches up to the target, per second.
+    /// A value of 0.0 or less snaps to the target immediately.
+    pub follow_speed: f32,
+    /// Size of the rectangle around the center of the view that the target can move
+    /// inside of without moving the camera, in world units.
+    pub deadzone: Vector2<f32>,
+    /// Area of the world the view is kept inside of, in world units.
+    /// If the view is larger than the bounds, it is centered on them instead.
+    pub bounds: Option<Rectangle>,
+    /// How far ahead of the target to look, in seconds of the target's velocity.
+    pub look_ahead: f32,
+    /// The furthest the camera looks ahead of the target, in world units.
+    pub max_look_ahead: f32,
+    /// The zoom the camera transitions towards.
+    pub target_zoom: f32,
+    /// How quickly the zoom approaches the target zoom, per second.
+    /// A value of 0.0 or less changes the zoom immediately.
+    pub zoom_speed: f32,
+    /// Amount of shake from 0.0 to 1.0. The shake grows with the square of the trauma.
+    pub trauma: f32,
+    /// Amount of trauma lost per second.
+    pub trauma_decay: f32,
+    /// Offset of the view at full trauma, in world units.
+    pub max_shake_offset: Vector2<f32>,
+    /// Rotation of the view at full trauma, in radians.
+    pub max_shake_rotation: f32,
+    /// How quickly the shake changes direction.
+    pub shake_frequency: f32,
+
+    look_ahead_offset: Vector2<f32>,
+    last_target_translation: Option<Translation>,
+    shake_offset: Vector2<f32>,
+    shake_rotation: f32,
+    elapsed_time: f32,
+}
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            target: None,
+            follow_speed: 8.0,
+            deadzone: Vector2::new(0.0, 0.0),
+            bounds: None,
+            look_ahead: 0.0,
+            max_look_ahead: 64.0,
+            target_zoom: 1.0,
+            zoom_speed: 4.0,
+            trauma: 0.0,
+            trauma_decay: 1.0,
+            max_shake_offset: Vector2::new(16.0, 16.0),
+            max_shake_rotation: 0.05,
+            shake_frequency: 20.0,
+            look_ahead_offset: Vector2::new(0.0, 0.0),
+            last_target_translation: None,
+            shake_offset: Vector2::new(0.0, 0.0),
+            shake_rotation: 0.0,
+            elapsed_time: 0.0,
+        }
+    }
+}
+impl CameraController {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+
+    /// Adds trauma to shake the camera, the total trauma is capped at 1.0.
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    /// Keeps the view inside of the area covered by the given tilemap.
+    pub fn set_bounds_from_tilemap(&mut self, tilemap: &Tilemap, transform: &Transform) {
+        self.bounds = Some(tilemap.bounds(&transform.translation));
+    }
+
+    fn follow(
+        &mut self,
+        focus: Vector2<f32>,
+        target: Option<Translation>,
+        delta: f32,
+    ) -> Vector2<f32> {
+        let target = match target {
+            Some(target) => target,
+            None => {
+                self.last_target_translation = None;
+                return focus;
+            }
+        };
+
+        let mut desired_look_ahead = Vector2::new(0.0, 0.0);
+        if let Some(last) = self.last_target_translation {
+            if delta > 0.0 {
+                let velocity = Vector2::new(target.x - last.x, target.y - last.y) / delta;
+                desired_look_ahead = velocity * self.look_ahead;
+                let length = desired_look_ahead.norm();
+                if length > self.max_look_ahead {
+                    desired_look_ahead *= self.max_look_ahead / length;
+                }
+            }
+        }
+        self.last_target_translation = Some(target);
+        self.look_ahead_offset = approach(
+            self.look_ahead_offset,
+            desired_look_ahead,
+            self.follow_speed,
+            delta,
+        );
+
+        let goal = Vector2::new(target.x, target.y) + self.look_ahead_offset;
+        let desired = Vector2::new(
+            apply_deadzone(focus.x, goal.x, self.deadzone.x / 2.0),
+            apply_deadzone(focus.y, goal.y, self.deadzone.y / 2.0),
+        );
+
+        approach(focus, desired, self.follow_speed, delta)
+    }
+
+    fn clamp_to_bounds(&self, focus: Vector2<f32>, view_half_size: Vector2<f32>) -> Vector2<f32> {
+        match self.bounds {
+            Some(bounds) => Vector2::new(
+                clamp_axis(focus.x, bounds.left(), bounds.right(), view_half_size.x),
+                clamp_axis(focus.y, bounds.bottom(), bounds.top(), view_half_size.y),
+            ),
+            None => focus,
+        }
+    }
+
+    fn update_shake(&mut self, delta: f32) {
+        self.elapsed_time += delta;
+        self.trauma = (self.trauma - self.trauma_decay * delta).max(0.0);
+
+        let shake = self.trauma * self.trauma;
+        let time = self.elapsed_time * self.shake_frequency;
+        self.shake_offset = Vector2::new(
+            self.max_shake_offset.x * shake * shake_noise(time, 0.0),
+            self.max_shake_offset.y * shake * shake_noise(time, 17.0),
+        );
+        self.shake_rotation = self.max_shake_rotation * shake * shake_noise(time, 43.0);
+    }
+}
+
+/// Updates every entity with a `CameraController`, `Camera` and `Transform`.
+/// Should be called once per frame, after the targets have moved.
+pub fn camera_controller_system(emd: &mut Emerald<'_>, world: &mut World) {
+    let screen_size = emd.screen_size();
+    update_camera_controllers(world, emd.delta(), screen_size);
+}
+
+pub(crate) fn update_camera_controllers(world: &mut World, delta: f32, screen_size: (u32, u32)) {
+    let targets: Vec<(Entity, Option<Translation>)> = world
+        .query::<&CameraController>()
+        .iter()
+        .map(|(entity, controller)| {
+            let target = controller
+                .target
+                .and_then(|target| world.get::<&Transform>(target).ok())
+                .map(|transform| transform.translation);
+            (entity, target)
+        })
+        .collect();
+
+    for (entity, target) in targets {
+        if let Ok((controller, camera, transform)) =
+            world.query_one_mut::<(&mut CameraController, &mut Camera, &mut Transform)>(entity)
+        {
+            camera.zoom = approach_scalar(
+                camera.zoom,
+                controller.target_zoom,
+                controller.zoom_speed,
+                delta,
+            );
+
+            // Work from the unshaken position, so the shake never accumulates.
+            let focus = Vector2::new(transform.translation.x, transform.translation.y)
+                - controller.shake_offset;
+            let focus = controller.follow(focus, target, delta);
+            let view_half_size = if camera.zoom > 0.0 {
+                Vector2::new(screen_size.0 as f32, screen_size.1 as f32) / (2.0 * camera.zoom)
+            } else {
+                Vector2::new(0.0, 0.0)
+            };
+            let focus = controller.clamp_to_bounds(focus, view_half_size);
+
+            let base_rotation = transform.rotation - controller.shake_rotation;
+            controller.update_shake(delta);
+
+            let translation = focus + controller.shake_offset;
+            transform.translation = Translation::new(translation.x, translation.y);
+            transform.rotation = base_rotation + controller.shake_rotation;
+        }
+    }
+}
+
+/// Frame rate independent exponential approach of `current` towards `target`.
+fn approach(current: Vector2<f32>, target: Vector2<f32>, speed: f32, delta: f32) -> Vector2<f32> {
+    if speed <= 0.0 {
+        return target;
+    }
+
+    current + (target - current) * (1.0 - (-speed * delta).exp())
+}
+
+fn approach_scalar(current: f32, target: f32, speed: f32, delta: f32) -> f32 {
+    if speed <= 0.0 {
+        return target;
+    }
+
+    current + (target - current) * (1.0 - (-speed * delta).exp())
+}
+
+fn apply_deadzone(focus: f32, goal: f32, half_size: f32) -> f32 {
+    if goal > focus + half_size {
+        goal - half_size
+    } else if goal < focus - half_size {
+        goal + half_size
+    } else {
+        focus
+    }
+}
+
+fn clamp_axis(value: f32, min: f32, max: f32, half_view: f32) -> f32 {
+    if max - min <= half_view * 2.0 {
+        (min + max) / 2.0
+    } else {
+        value.clamp(min + half_view, max - half_view)
+    }
+}
+
+/// Smooth noise in the range -1.0..=1.0, made from a few out of phase sine waves.
+fn shake_noise(time: f32, seed: f32) -> f32 {
+    (time + seed).sin() * 0.5
+        + (time * 2.3 + seed * 1.7).sin() * 0.3
+        + (time * 5.1 + seed * 3.1).sin() * 0.2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{update_camera_controllers, CameraController};
+    use crate::{rendering::components::Camera, Rectangle, Transform, Vector2, World};
+
+    #[test]
+    fn follows_target_outside_of_deadzone() {
+        let mut world = World::new();
+        let target = world.spawn((Transform::from_translation((50.0, 5.0)),));
+        let controller = CameraController {
+            follow_speed: 0.0,
+            deadzone: Vector2::new(20.0, 20.0),
+            ..CameraController::new(target)
+        };
+        let camera = world.spawn((Transform::default(), Camera::default(), controller));
+
+        update_camera_controllers(&mut world, 1.0 / 60.0, (200, 200));
+
+        let transform = world.get::<&Transform>(camera).unwrap();
+        assert_eq!(transform.translation.x, 40.0);
+        assert_eq!(transform.translation.y, 0.0);
+    }
+
+    #[test]
+    fn view_is_clamped_to_bounds() {
+        let mut world = World::new();
+        let target = world.spawn((Transform::from_translation((-500.0, 0.0)),));
+        let controller = CameraController {
+            follow_speed: 0.0,
+            zoom_speed: 0.0,
+            target_zoom: 2.0,
+            bounds: Some(Rectangle::new(-100.0, -100.0, 400.0, 20.0)),
+            ..CameraController::new(target)
+        };
+        let camera = world.spawn((Transform::default(), Camera::default(), controller));
+
+        update_camera_controllers(&mut world, 1.0 / 60.0, (200, 100));
+
+        // The view is 100 by 50 world units at a zoom of 2.0.
+        let transform = world.get::<&Transform>(camera).unwrap();
+        assert_eq!(transform.translation.x, -50.0);
+        assert_eq!(transform.translation.y, -90.0);
+        assert_eq!(world.get::<&Camera>(camera).unwrap().zoom, 2.0);
+    }
+
+    #[test]
+    fn shake_decays_without_drifting() {
+        let mut world = World::new();
+        let mut controller = CameraController::default();
+        controller.add_trauma(2.0);
+        assert_eq!(controller.trauma, 1.0);
+        let camera = world.spawn((Transform::default(), Camera::default(), controller));
+
+        for _ in 0..120 {
+            update_camera_controllers(&mut world, 1.0 / 60.0, (200, 200));
+        }
+
+        let transform = world.get::<&Transform>(camera).unwrap();
+        assert!(transform.translation.x.abs() < 1e-3);
+        assert!(transform.translation.y.abs() < 1e-3);
+        assert!(transform.rotation.abs() < 1e-5);
+        assert_eq!(world.get::<&CameraController>(camera).unwrap().trauma, 0.0);
+    }
+}
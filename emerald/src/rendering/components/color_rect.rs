@@ -1,4 +1,4 @@
-use crate::*;
+use crate::{blend_mode::BlendMode, material::MaterialKey, *};
 
 #[derive(Debug, Copy, Clone)]
 pub struct ColorRect {
@@ -10,6 +10,8 @@ pub struct ColorRect {
     pub centered: bool,
     pub rotation: f32,
     pub z_index: f32,
+    /// How the drawable is combined with what was drawn before it.
+    pub blend_mode: BlendMode,
 }
 impl ColorRect {
     pub fn new(color: Color, width: u32, height: u32) -> Self {
@@ -32,6 +34,11 @@ impl Default for ColorRect {
             centered: true,
             rotation: 0.0,
             z_index: 0.0,
+            blend_mode: BlendMode::Alpha,
         }
     }
 }
+
+/// Draws the entity's `ColorRect` with a custom shader, instead of the default textured shader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRectMaterial(pub MaterialKey);
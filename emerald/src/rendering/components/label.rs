@@ -1,5 +1,11 @@
+use std::collections::HashMap;
+
 use crate::*;
-use crate::{font::FontKey, rendering::*};
+use crate::{
+    blend_mode::BlendMode, font::FontKey, material::MaterialKey, rendering::*, texture::TextureKey,
+};
+
+use super::{parse_markup, TextSpan};
 
 pub use fontdue::layout::{HorizontalAlign, VerticalAlign, WrapStyle};
 
@@ -21,6 +27,21 @@ pub struct Label {
     pub wrap_style: WrapStyle,
     pub max_height: Option<f32>,
     pub max_width: Option<f32>,
+    /// Custom shader to draw with, instead of the default textured shader.
+    pub material: Option<MaterialKey>,
+    /// How the drawable is combined with what was drawn before it.
+    pub blend_mode: BlendMode,
+
+    /// Parses the text as markup, see `parse_markup`.
+    pub rich_text: bool,
+    /// The font of bold spans, which use the label's font if `None`.
+    pub bold_font: Option<FontKey>,
+    /// The font of italic spans, which use the label's font if `None`. Bold takes precedence.
+    pub italic_font: Option<FontKey>,
+    /// Textures drawn in place of `[icon=name]` tags, scaled to the size of the text.
+    pub icons: HashMap<String, TextureKey>,
+    /// Drives the wave and shake effects, advanced by `label_effect_system`.
+    pub effect_time: f32,
 }
 impl Label {
     pub fn new<T: Into<String>>(text: T, font_key: FontKey, font_size: u16) -> Self {
@@ -40,6 +61,69 @@ impl Label {
             wrap_style: WrapStyle::Word,
             max_height: None,
             max_width: Some(300.0),
+            material: None,
+            blend_mode: BlendMode::Alpha,
+            rich_text: false,
+            bold_font: None,
+            italic_font: None,
+            icons: HashMap::new(),
+            effect_time: 0.0,
         }
     }
+
+    /// The spans of the text, a single span unless the text is rich text.
+    pub fn spans(&self) -> Vec<TextSpan> {
+        if self.rich_text {
+            parse_markup(&self.text)
+        } else {
+            vec![TextSpan {
+                text: self.text.clone(),
+                ..Default::default()
+            }]
+        }
+    }
+
+    /// The number of characters shown once every character is visible, see `visible_characters`.
+    /// Whitespace and markup aren't counted, icons count as a single character.
+    pub fn character_count(&self) -> usize {
+        self.spans()
+            .iter()
+            .map(|span| match span.icon {
+                Some(_) => 1,
+                None => span.text.chars().filter(|c| !c.is_whitespace()).count(),
+            })
+            .sum()
+    }
+}
+
+pub fn label_effect_system(world: &mut World, delta: f32) {
+    for (_, label) in world.query::<&mut Label>().iter() {
+        label.effect_time += delta;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::Label;
+    use crate::{
+        asset_key::{AssetKey, RefChangeChannel},
+        font::{Font, FontKey},
+    };
+
+    #[test]
+    fn character_count_skips_markup_and_whitespace() {
+        let channel = RefChangeChannel::default();
+        let asset_key = AssetKey::new(0, TypeId::of::<Font>(), channel.sender.clone());
+        let text = "[b]Hi[/b] there,\n[icon=coin] x[wave]2[/wave]";
+        let mut label = Label::new(text, FontKey::new(asset_key, "font.ttf", 16), 16);
+
+        // Without rich text the tags are drawn, and counted, as text.
+        assert_eq!(label.character_count(), 41);
+
+        // "Hi", "there," "x" and "2", with the icon counting as a single character.
+        label.rich_text = true;
+        assert_eq!(label.character_count(), 11);
+    }
 }
@@ -0,0 +1,109 @@
+use crate::*;
+
+/// Lights up a circle around its entity, in worlds with lighting enabled.
+#[derive(Clone, Debug)]
+pub struct PointLight {
+    pub color: Color,
+    /// Brightness of the light at its center.
+    pub intensity: f32,
+    /// Distance at which the light fades out entirely, in pixels.
+    pub radius: f32,
+    /// How quickly the light fades with distance, 1.0 fades linearly and higher values fade faster.
+    pub falloff: f32,
+    /// Height of the light above the world, in pixels. Lower lights shade normal maps at steeper angles.
+    pub height: f32,
+    pub offset: Vector2<f32>,
+    pub casts_shadows: bool,
+    pub enabled: bool,
+}
+impl PointLight {
+    pub fn new(color: Color, radius: f32) -> Self {
+        Self {
+            color,
+            intensity: 1.0,
+            radius,
+            falloff: 1.0,
+            height: 32.0,
+            offset: Vector2::new(0.0, 0.0),
+            casts_shadows: true,
+            enabled: true,
+        }
+    }
+}
+
+/// Lights up a cone in front of its entity, in worlds with lighting enabled.
+#[derive(Clone, Debug)]
+pub struct SpotLight {
+    pub color: Color,
+    pub intensity: f32,
+    pub radius: f32,
+    pub falloff: f32,
+    pub height: f32,
+    /// Direction the cone points in, in radians counter-clockwise from the x axis.
+    /// Rotated along with the entity's transform.
+    pub direction: f32,
+    /// Width of the cone in radians, its edges soften over the outer quarter.
+    pub angle: f32,
+    pub offset: Vector2<f32>,
+    pub casts_shadows: bool,
+    pub enabled: bool,
+}
+impl SpotLight {
+    pub fn new(color: Color, radius: f32, direction: f32, angle: f32) -> Self {
+        Self {
+            color,
+            intensity: 1.0,
+            radius,
+            falloff: 1.0,
+            height: 32.0,
+            direction,
+            angle,
+            offset: Vector2::new(0.0, 0.0),
+            casts_shadows: true,
+            enabled: true,
+        }
+    }
+}
+
+/// The outline that casts a shadow for a `LightOccluder`.
+#[derive(Clone, Debug)]
+pub enum OccluderShape {
+    /// A closed outline, in pixels relative to the entity's translation and rotated with it.
+    Polygon(Vec<Vector2<f32>>),
+    /// The outlines of the colliders of the entity's rigid body.
+    Colliders,
+}
+
+/// Blocks the light of lights that cast shadows.
+#[derive(Clone, Debug)]
+pub struct LightOccluder {
+    pub shape: OccluderShape,
+    pub enabled: bool,
+}
+impl LightOccluder {
+    pub fn polygon(points: Vec<Vector2<f32>>) -> Self {
+        Self {
+            shape: OccluderShape::Polygon(points),
+            enabled: true,
+        }
+    }
+
+    /// A rectangle of the given size, centered on the entity.
+    pub fn rectangle(width: f32, height: f32) -> Self {
+        let (x, y) = (width / 2.0, height / 2.0);
+        Self::polygon(vec![
+            Vector2::new(-x, -y),
+            Vector2::new(x, -y),
+            Vector2::new(x, y),
+            Vector2::new(-x, y),
+        ])
+    }
+
+    /// Casts shadows in the shape of the entity's colliders.
+    pub fn from_colliders() -> Self {
+        Self {
+            shape: OccluderShape::Colliders,
+            enabled: true,
+        }
+    }
+}
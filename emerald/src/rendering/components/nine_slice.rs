@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{material::MaterialKey, texture::TextureKey, *};
+
+/// Widths of the borders of a nine-slice, in pixels of its texture.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct NineSliceBorder {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+impl NineSliceBorder {
+    pub fn new(left: f32, right: f32, top: f32, bottom: f32) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// A border of the same width on every side.
+    pub fn uniform(width: f32) -> Self {
+        Self::new(width, width, width, width)
+    }
+}
+
+/// How the edges or center of a nine-slice fill the space between its corners.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NineSliceMode {
+    Stretch,
+    /// Repeated at the size of the texture, cutting off the last repetition.
+    Tile,
+}
+
+/// A texture drawn at any size without distorting its corners, ex. for UI panels and buttons.
+/// The corners are drawn as they are, the edges and center are stretched or tiled to fill the rest.
+#[derive(Clone, Debug)]
+pub struct NineSlice {
+    pub(crate) texture_key: TextureKey,
+    /// Area of the texture to slice, in pixels from its top left corner.
+    /// A zero sized target uses the entire texture.
+    pub target: Rectangle,
+    pub border: NineSliceBorder,
+    /// Size the nine-slice is drawn at, in pixels. Borders shrink if they don't fit.
+    pub size: Vector2<f32>,
+    pub edge_mode: NineSliceMode,
+    pub center_mode: NineSliceMode,
+    pub rotation: f32,
+    pub offset: Vector2<f32>,
+    pub visible: bool,
+    pub color: Color,
+    pub centered: bool,
+    pub z_index: f32,
+    /// Custom shader to draw with, instead of the default textured shader.
+    pub material: Option<MaterialKey>,
+}
+impl NineSlice {
+    pub fn new(texture_key: TextureKey, border: NineSliceBorder, size: Vector2<f32>) -> Self {
+        NineSlice {
+            texture_key,
+            target: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            border,
+            size,
+            edge_mode: NineSliceMode::Stretch,
+            center_mode: NineSliceMode::Stretch,
+            rotation: 0.0,
+            offset: Vector2::new(0.0, 0.0),
+            visible: true,
+            color: WHITE,
+            centered: true,
+            z_index: 0.0,
+            material: None,
+        }
+    }
+
+    pub fn texture(&self) -> &TextureKey {
+        &self.texture_key
+    }
+
+    /// The area covered by the nine-slice at the given translation, ignoring rotation.
+    pub fn bounds(&self, translation: &Translation) -> Rectangle {
+        let mut bounds = Rectangle::new(
+            translation.x + self.offset.x,
+            translation.y + self.offset.y,
+            self.size.x,
+            self.size.y,
+        );
+        if self.centered {
+            bounds.x -= self.size.x / 2.0;
+            bounds.y -= self.size.y / 2.0;
+        }
+
+        bounds
+    }
+
+    /// Splits the nine-slice into the parts of the texture it's drawn with.
+    pub(crate) fn patches(&self, texture_size: (f32, f32)) -> Vec<NineSlicePatch> {
+        let mut target = self.target;
+        if target.is_zero_sized() {
+            target.width = texture_size.0;
+            target.height = texture_size.1;
+        }
+
+        // Borders are clamped to the texture, then shrunk evenly if they don't fit the size drawn.
+        let left = self.border.left.clamp(0.0, target.width);
+        let right = self.border.right.clamp(0.0, target.width - left);
+        let top = self.border.top.clamp(0.0, target.height);
+        let bottom = self.border.bottom.clamp(0.0, target.height - top);
+        let shrink_x = fit_borders(left + right, self.size.x);
+        let shrink_y = fit_borders(top + bottom, self.size.y);
+
+        // Columns from left to right and rows from top to bottom, as (source start, source length, destination start, destination length).
+        let columns = [
+            (target.x, left, 0.0, left * shrink_x),
+            (
+                target.x + left,
+                target.width - left - right,
+                left * shrink_x,
+                self.size.x - (left + right) * shrink_x,
+            ),
+            (
+                target.x + target.width - right,
+                right,
+                self.size.x - right * shrink_x,
+                right * shrink_x,
+            ),
+        ];
+        let rows = [
+            (target.y, top, 0.0, top * shrink_y),
+            (
+                target.y + top,
+                target.height - top - bottom,
+                top * shrink_y,
+                self.size.y - (top + bottom) * shrink_y,
+            ),
+            (
+                target.y + target.height - bottom,
+                bottom,
+                self.size.y - bottom * shrink_y,
+                bottom * shrink_y,
+            ),
+        ];
+
+        let mut patches = Vec::new();
+        for (row_index, row) in rows.iter().enumerate() {
+            for (column_index, column) in columns.iter().enumerate() {
+                let mode = match (column_index == 1, row_index == 1) {
+                    (true, true) => self.center_mode,
+                    (true, false) | (false, true) => self.edge_mode,
+                    (false, false) => NineSliceMode::Stretch,
+                };
+                let tile_x = mode == NineSliceMode::Tile && column_index == 1;
+                let tile_y = mode == NineSliceMode::Tile && row_index == 1;
+
+                for x in split_axis(*column, tile_x) {
+                    for y in split_axis(*row, tile_y) {
+                        patches.push(NineSlicePatch {
+                            source: Rectangle::new(x.0, y.0, x.1, y.1),
+                            // Rows are measured from the top, while destinations have y pointing up.
+                            destination: Rectangle::new(x.2, self.size.y - y.2 - y.3, x.3, y.3),
+                        });
+                    }
+                }
+            }
+        }
+
+        patches
+    }
+}
+
+/// A part of a nine-slice, drawn from an area of the texture into an area of the nine-slice.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct NineSlicePatch {
+    /// In pixels from the top left corner of the texture.
+    pub source: Rectangle,
+    /// In pixels from the bottom left corner of the nine-slice, with y pointing up.
+    pub destination: Rectangle,
+}
+
+/// Scale applied to the borders along an axis, so they fit inside of the size drawn.
+fn fit_borders(borders: f32, size: f32) -> f32 {
+    if borders > size && borders > 0.0 {
+        (size / borders).max(0.0)
+    } else {
+        1.0
+    }
+}
+
+/// Splits a column or row into the pieces drawn for it, skipping empty ones.
+/// Tiled pieces keep the size of the source, the last one is cut short.
+fn split_axis(
+    (source_start, source_length, destination_start, destination_length): (f32, f32, f32, f32),
+    tile: bool,
+) -> Vec<(f32, f32, f32, f32)> {
+    if source_length <= 0.0 || destination_length <= 0.0 {
+        return Vec::new();
+    }
+
+    if !tile {
+        return vec![(
+            source_start,
+            source_length,
+            destination_start,
+            destination_length,
+        )];
+    }
+
+    let mut pieces = Vec::new();
+    let mut offset = 0.0;
+    while offset < destination_length {
+        let length = source_length.min(destination_length - offset);
+        pieces.push((source_start, length, destination_start + offset, length));
+        offset += source_length;
+    }
+
+    pieces
+}
+
+/// Reads the target and border of a 9-slice from the slice with the given name.
+/// Returns the index of the frame the slice starts on, along with the target and border.
+#[cfg(feature = "aseprite")]
+pub(crate) fn get_aseprite_slice(
+    aseprite: &asefile::AsepriteFile,
+    name: &str,
+) -> Result<(u32, Rectangle, NineSliceBorder), EmeraldError> {
+    let slice = aseprite
+        .slices()
+        .iter()
+        .find(|slice| slice.name == name)
+        .ok_or_else(|| EmeraldError::new(format!("Aseprite slice {:?} does not exist", name)))?;
+    let key = slice.keys.first().ok_or_else(|| {
+        EmeraldError::new(format!("Aseprite slice {:?} does not have any keys", name))
+    })?;
+    let slice9 = key.slice9.as_ref().ok_or_else(|| {
+        EmeraldError::new(format!(
+            "Aseprite slice {:?} does not have a 9-slice center",
+            name
+        ))
+    })?;
+
+    let (width, height) = (key.size.0 as f32, key.size.1 as f32);
+    let target = Rectangle::new(key.origin.0 as f32, key.origin.1 as f32, width, height);
+    let border = NineSliceBorder::new(
+        slice9.center_x as f32,
+        width - slice9.center_x as f32 - slice9.center_width as f32,
+        slice9.center_y as f32,
+        height - slice9.center_y as f32 - slice9.center_height as f32,
+    );
+
+    Ok((key.from_frame, target, border))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fit_borders, split_axis};
+
+    #[test]
+    fn tiles_are_cut_short_to_fit() {
+        let pieces = split_axis((4.0, 8.0, 4.0, 20.0), true);
+        assert_eq!(
+            pieces,
+            vec![
+                (4.0, 8.0, 4.0, 8.0),
+                (4.0, 8.0, 12.0, 8.0),
+                (4.0, 4.0, 20.0, 4.0)
+            ]
+        );
+        assert_eq!(split_axis((0.0, 8.0, 0.0, 20.0), false).len(), 1);
+        assert!(split_axis((0.0, 0.0, 0.0, 20.0), true).is_empty());
+    }
+
+    #[test]
+    fn borders_shrink_to_fit_small_sizes() {
+        assert_eq!(fit_borders(16.0, 32.0), 1.0);
+        assert_eq!(fit_borders(16.0, 8.0), 0.5);
+        assert_eq!(fit_borders(0.0, 0.0), 1.0);
+    }
+}
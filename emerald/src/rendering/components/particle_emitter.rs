@@ -0,0 +1,428 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{blend_mode::BlendMode, material::MaterialKey, texture::TextureKey, *};
+
+/// Seeds emitters differently from each other, so they don't emit in lockstep.
+static NEXT_SEED: AtomicU32 = AtomicU32::new(0x9E37_79B9);
+
+/// A number of particles emitted at once, at a time within the emitter's cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ParticleBurst {
+    /// In seconds from the start of the cycle.
+    pub time: f32,
+    pub count: usize,
+}
+impl ParticleBurst {
+    pub fn new(time: f32, count: usize) -> Self {
+        Self { time, count }
+    }
+}
+
+/// Where particles live once emitted.
+/// Neither space applies the rotation or scale of the entity's transform to its particles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticleSpace {
+    /// Particles move along with the emitter's entity, keeping their offsets from it.
+    Local,
+    /// Particles stay where they were emitted, leaving a trail behind a moving emitter.
+    World,
+}
+
+/// How each particle picks the frame it's drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticleFrameMode {
+    /// A random frame, chosen when the particle is emitted.
+    Random,
+    /// Steps through the frames in order over the particle's lifetime.
+    OverLifetime,
+    Fixed(usize),
+}
+
+/// An area of a texture a particle can be drawn with.
+#[derive(Clone, Debug)]
+pub struct ParticleFrame {
+    pub texture_key: TextureKey,
+    /// In pixels from the top left corner of the texture, a zero sized target uses the entire texture.
+    pub target: Rectangle,
+}
+
+/// A value that can be blended between the keys of a `ParticleCurve`.
+pub trait CurveValue: Copy {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+impl CurveValue for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+impl CurveValue for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
+}
+
+/// A value over the lifetime of a particle, blended linearly between keys.
+/// Keys are times from 0.0 at emission to 1.0 at expiry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleCurve<T> {
+    keys: Vec<(f32, T)>,
+}
+impl<T: CurveValue> ParticleCurve<T> {
+    pub fn new(mut keys: Vec<(f32, T)>) -> Self {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keys }
+    }
+
+    pub fn constant(value: T) -> Self {
+        Self::new(vec![(0.0, value)])
+    }
+
+    /// Blends from one value at emission to another at expiry.
+    pub fn linear(start: T, end: T) -> Self {
+        Self::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    pub fn keys(&self) -> &[(f32, T)] {
+        &self.keys
+    }
+
+    /// Returns `None` if the curve has no keys.
+    pub fn sample(&self, t: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        if t <= first.0 {
+            return Some(first.1);
+        }
+
+        for pair in self.keys.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            if t <= end.0 {
+                let span = end.0 - start.0;
+                let t = if span > 0.0 {
+                    (t - start.0) / span
+                } else {
+                    1.0
+                };
+                return Some(start.1.lerp(&end.1, t));
+            }
+        }
+
+        self.keys.last().map(|key| key.1)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Particle {
+    /// Relative to the emitter's entity in local space, or the world in world space.
+    pub position: Vector2<f32>,
+    pub velocity: Vector2<f32>,
+    pub age: f32,
+    pub lifetime: f32,
+    pub frame: usize,
+}
+impl Particle {
+    /// How far the particle is through its lifetime, from 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Emits and simulates particles, drawn in a single batch when they share a texture.
+/// Particles are advanced by `particle_emitter_system`.
+#[derive(Clone, Debug)]
+pub struct ParticleEmitter {
+    pub emitting: bool,
+    /// Particles emitted per second.
+    pub rate: f32,
+    pub bursts: Vec<ParticleBurst>,
+    /// Length of a cycle in seconds, bursts are timed from its start.
+    pub duration: f32,
+    /// Whether the emitter starts another cycle at the end of one, or stops emitting.
+    pub looping: bool,
+    /// Particles aren't emitted while this many are alive.
+    pub max_particles: usize,
+    /// Ranges that each particle picks a random value from, as (min, max).
+    /// Lifetime is in seconds and speed in pixels per second.
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    /// In radians counter-clockwise from the x axis.
+    pub angle: (f32, f32),
+    /// Acceleration applied to every particle, in pixels per second squared.
+    pub gravity: Vector2<f32>,
+    pub color: ParticleCurve<Color>,
+    pub scale: ParticleCurve<f32>,
+    pub frames: Vec<ParticleFrame>,
+    pub frame_mode: ParticleFrameMode,
+    pub space: ParticleSpace,
+    /// Where particles are emitted, relative to the entity.
+    pub offset: Vector2<f32>,
+    pub visible: bool,
+    pub z_index: f32,
+    /// Custom shader to draw with, instead of the default textured shader.
+    pub material: Option<MaterialKey>,
+    /// How the drawable is combined with what was drawn before it.
+    pub blend_mode: BlendMode,
+
+    particles: Vec<Particle>,
+    elapsed: f32,
+    /// Fraction of a particle left over from rate based emission.
+    emit_accumulator: f32,
+    rng: u32,
+    /// Translation of the entity at the last update, world space particles are drawn relative to it.
+    origin: Vector2<f32>,
+}
+impl ParticleEmitter {
+    pub fn new(texture_key: TextureKey) -> Self {
+        Self::from_frames(
+            vec![ParticleFrame {
+                texture_key,
+                target: Rectangle::zeroed(),
+            }],
+            ParticleFrameMode::Fixed(0),
+        )
+    }
+
+    pub fn from_frames(frames: Vec<ParticleFrame>, frame_mode: ParticleFrameMode) -> Self {
+        Self {
+            emitting: true,
+            rate: 10.0,
+            bursts: Vec::new(),
+            duration: 1.0,
+            looping: true,
+            max_particles: 1000,
+            lifetime: (1.0, 1.0),
+            speed: (50.0, 50.0),
+            angle: (0.0, std::f32::consts::TAU),
+            gravity: Vector2::new(0.0, 0.0),
+            color: ParticleCurve::constant(WHITE),
+            scale: ParticleCurve::constant(1.0),
+            frames,
+            frame_mode,
+            space: ParticleSpace::Local,
+            offset: Vector2::new(0.0, 0.0),
+            visible: true,
+            z_index: 0.0,
+            material: None,
+            blend_mode: BlendMode::Alpha,
+            particles: Vec::new(),
+            elapsed: 0.0,
+            emit_accumulator: 0.0,
+            rng: NEXT_SEED.fetch_add(0x6D2B_79F5, Ordering::Relaxed) | 1,
+            origin: Vector2::new(0.0, 0.0),
+        }
+    }
+
+    /// Draws particles with the frames of an aseprite, stepping through them over each particle's lifetime.
+    #[cfg(feature = "aseprite")]
+    pub fn from_aseprite(aseprite: &super::Aseprite) -> Self {
+        Self::from_frames(aseprite.particle_frames(), ParticleFrameMode::OverLifetime)
+    }
+
+    /// Makes the random values picked by particles repeatable.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.rng = seed | 1;
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub(crate) fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Removes every particle and starts the cycle over.
+    pub fn restart(&mut self) {
+        self.particles.clear();
+        self.elapsed = 0.0;
+        self.emit_accumulator = 0.0;
+        self.emitting = true;
+    }
+
+    /// Emits particles immediately, regardless of the rate and bursts.
+    pub fn emit(&mut self, count: usize) {
+        let count = count.min(self.max_particles.saturating_sub(self.particles.len()));
+        let emit_position = match self.space {
+            ParticleSpace::Local => self.offset,
+            ParticleSpace::World => self.origin + self.offset,
+        };
+
+        for _ in 0..count {
+            let angle = self.random_range(self.angle);
+            let speed = self.random_range(self.speed);
+            let lifetime = self.random_range(self.lifetime);
+            let frame = match self.frame_mode {
+                ParticleFrameMode::Random if !self.frames.is_empty() => {
+                    (self.next_random() * self.frames.len() as f32) as usize
+                }
+                ParticleFrameMode::Fixed(index) => index,
+                _ => 0,
+            };
+
+            self.particles.push(Particle {
+                position: emit_position,
+                velocity: Vector2::new(angle.cos() * speed, angle.sin() * speed),
+                age: 0.0,
+                lifetime,
+                frame,
+            });
+        }
+    }
+
+    /// Advances the particles, then emits new ones from the given translation of the entity.
+    pub fn update(&mut self, delta: f32, translation: &Translation) {
+        self.origin = Vector2::new(translation.x, translation.y);
+
+        let gravity = self.gravity * delta;
+        let mut index = 0;
+        while index < self.particles.len() {
+            let particle = &mut self.particles[index];
+            particle.age += delta;
+            if particle.age >= particle.lifetime {
+                self.particles.swap_remove(index);
+                continue;
+            }
+
+            particle.velocity += gravity;
+            particle.position += particle.velocity * delta;
+            index += 1;
+        }
+
+        if !self.emitting {
+            return;
+        }
+
+        self.emit_accumulator += self.rate.max(0.0) * delta;
+        let count = self.emit_accumulator.floor();
+        self.emit_accumulator -= count;
+        self.emit(count as usize);
+
+        let start = self.elapsed;
+        self.elapsed += delta;
+        let bursts: usize = self
+            .bursts
+            .iter()
+            .filter(|burst| burst.time >= start && burst.time < self.elapsed)
+            .map(|burst| burst.count)
+            .sum();
+        self.emit(bursts);
+
+        if self.duration > 0.0 && self.elapsed >= self.duration {
+            if self.looping {
+                self.elapsed %= self.duration;
+                let bursts: usize = self
+                    .bursts
+                    .iter()
+                    .filter(|burst| burst.time < self.elapsed)
+                    .map(|burst| burst.count)
+                    .sum();
+                self.emit(bursts);
+            } else {
+                self.emitting = false;
+            }
+        }
+    }
+
+    /// The frame a particle is drawn with, if the emitter has one for it.
+    pub(crate) fn frame_of(&self, particle: &Particle) -> Option<&ParticleFrame> {
+        match self.frame_mode {
+            ParticleFrameMode::OverLifetime => {
+                let index = (particle.progress() * self.frames.len() as f32) as usize;
+                self.frames
+                    .get(index.min(self.frames.len().saturating_sub(1)))
+            }
+            _ => self.frames.get(particle.frame),
+        }
+    }
+
+    /// Where a particle is drawn, relative to the entity.
+    pub(crate) fn draw_offset(&self, particle: &Particle) -> Vector2<f32> {
+        match self.space {
+            ParticleSpace::Local => particle.position,
+            ParticleSpace::World => particle.position - self.origin,
+        }
+    }
+
+    /// Steps the xorshift generator, returning a value from 0.0 up to 1.0.
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        (self.rng >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    fn random_range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next_random()
+    }
+}
+
+pub fn particle_emitter_system(world: &mut World, delta: f32) {
+    for (_, (emitter, transform)) in world.query::<(&mut ParticleEmitter, &Transform)>().iter() {
+        emitter.update(delta, &transform.translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParticleBurst, ParticleCurve, ParticleEmitter, ParticleFrameMode};
+    use crate::{Color, Translation};
+
+    #[test]
+    fn curves_blend_between_keys() {
+        let curve = ParticleCurve::new(vec![(1.0, 0.0), (0.0, 2.0), (0.5, 4.0)]);
+        assert_eq!(curve.sample(-1.0), Some(2.0));
+        assert_eq!(curve.sample(0.25), Some(3.0));
+        assert_eq!(curve.sample(0.75), Some(2.0));
+        assert_eq!(curve.sample(2.0), Some(0.0));
+
+        let fade = ParticleCurve::linear(Color::new(255, 0, 0, 255), Color::new(255, 0, 0, 0));
+        assert_eq!(fade.sample(0.5), Some(Color::new(255, 0, 0, 128)));
+        assert_eq!(ParticleCurve::<f32>::new(Vec::new()).sample(0.5), None);
+    }
+
+    #[test]
+    fn rate_and_bursts_emit_until_particles_expire() {
+        let mut emitter = ParticleEmitter::from_frames(Vec::new(), ParticleFrameMode::Random);
+        emitter.set_seed(7);
+        emitter.rate = 4.0;
+        emitter.bursts = vec![ParticleBurst::new(0.0, 10)];
+        emitter.lifetime = (0.6, 0.6);
+        emitter.looping = false;
+        let origin = Translation::new(0.0, 0.0);
+
+        emitter.update(0.25, &origin);
+        assert_eq!(emitter.particle_count(), 11);
+        emitter.update(0.25, &origin);
+        assert_eq!(emitter.particle_count(), 12);
+        emitter.update(0.25, &origin);
+        assert_eq!(emitter.particle_count(), 13);
+        emitter.update(0.25, &origin);
+        assert_eq!(emitter.particle_count(), 3);
+
+        emitter.update(1.0, &origin);
+        assert!(!emitter.emitting);
+        assert_eq!(emitter.particle_count(), 0);
+    }
+
+    #[test]
+    fn max_particles_caps_emission() {
+        let mut emitter = ParticleEmitter::from_frames(Vec::new(), ParticleFrameMode::Random);
+        emitter.max_particles = 5;
+        emitter.emit(3);
+        emitter.emit(3);
+        assert_eq!(emitter.particle_count(), 5);
+    }
+}
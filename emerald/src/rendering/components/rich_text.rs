@@ -0,0 +1,274 @@
+use crate::{Color, BLACK, WHITE};
+
+const DEFAULT_WAVE_AMPLITUDE: f32 = 4.0;
+const DEFAULT_SHAKE_AMPLITUDE: f32 = 2.0;
+/// Radians per second each character moves through the wave.
+const WAVE_SPEED: f32 = 6.0;
+/// Radians between neighbouring characters of a wave.
+const WAVE_PHASE: f32 = 0.6;
+/// Times per second shaking characters jump to a new offset.
+const SHAKE_RATE: f32 = 20.0;
+
+/// An animated offset applied to each character of a span, in pixels before the label's scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextEffect {
+    Wave { amplitude: f32 },
+    Shake { amplitude: f32 },
+}
+impl TextEffect {
+    /// The offset of the character at the given index of the label, at the given time.
+    pub fn offset(&self, index: usize, time: f32) -> (f32, f32) {
+        match *self {
+            TextEffect::Wave { amplitude } => (
+                0.0,
+                amplitude * (time * WAVE_SPEED - index as f32 * WAVE_PHASE).sin(),
+            ),
+            TextEffect::Shake { amplitude } => {
+                let step = (time * SHAKE_RATE) as u32;
+                (
+                    amplitude * noise(index as u32, step, 0),
+                    amplitude * noise(index as u32, step, 1),
+                )
+            }
+        }
+    }
+}
+
+/// A run of text sharing a style, parsed from the markup of a `Label`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    /// Overrides the color of the label.
+    pub color: Option<Color>,
+    /// Overrides the font size of the label.
+    pub size: Option<f32>,
+    pub bold: bool,
+    pub italic: bool,
+    pub effect: Option<TextEffect>,
+    /// The name of one of the label's icons, drawn in place of text.
+    pub icon: Option<String>,
+}
+impl TextSpan {
+    fn same_style(&self, other: &TextSpan) -> bool {
+        self.color == other.color
+            && self.size == other.size
+            && self.bold == other.bold
+            && self.italic == other.italic
+            && self.effect == other.effect
+    }
+}
+
+/// Splits marked up text into spans of the same style.
+///
+/// Supported tags are `[color=red]` or `[color=#ff0000]`, `[size=24]`, `[b]`, `[i]`,
+/// `[wave]` or `[wave=8]`, `[shake]` or `[shake=4]`, each closed by `[/name]`,
+/// and `[icon=name]` which isn't closed. Tags that can't be parsed are kept as text.
+pub fn parse_markup(text: &str) -> Vec<TextSpan> {
+    let mut spans: Vec<TextSpan> = Vec::new();
+    // The name of each open tag, along with the tag itself.
+    let mut open_tags: Vec<(&str, &str)> = Vec::new();
+    let mut current = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('[') {
+        current.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find(']') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[1..end];
+        let style = current_style(&open_tags);
+
+        if let Some(name) = tag.strip_prefix('/') {
+            if let Some(index) = open_tags.iter().rposition(|(open, _)| *open == name) {
+                push_span(&mut spans, &mut current, &style);
+                open_tags.remove(index);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        } else if let Some(icon) = tag.strip_prefix("icon=") {
+            push_span(&mut spans, &mut current, &style);
+            spans.push(TextSpan {
+                icon: Some(icon.to_string()),
+                ..style
+            });
+            rest = &rest[end + 1..];
+            continue;
+        } else if apply_tag(tag, &style).is_some() {
+            push_span(&mut spans, &mut current, &style);
+            open_tags.push((tag.split('=').next().unwrap_or(tag), tag));
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        // Not a tag, the bracket is kept as text.
+        current.push('[');
+        rest = &rest[1..];
+    }
+    current.push_str(rest);
+
+    push_span(&mut spans, &mut current, &current_style(&open_tags));
+
+    spans
+}
+
+/// The style inside of every open tag, later tags overriding earlier ones.
+fn current_style(open_tags: &[(&str, &str)]) -> TextSpan {
+    open_tags
+        .iter()
+        .fold(TextSpan::default(), |style, (_, tag)| {
+            apply_tag(tag, &style).unwrap_or(style)
+        })
+}
+
+/// The style inside of an opening tag, or `None` if the tag isn't recognized.
+fn apply_tag(tag: &str, style: &TextSpan) -> Option<TextSpan> {
+    let mut style = style.clone();
+    let (name, value) = match tag.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (tag, None),
+    };
+
+    match (name, value) {
+        ("color", Some(value)) => style.color = Some(parse_color(value)?),
+        ("size", Some(value)) => style.size = Some(value.parse().ok()?),
+        ("b", None) => style.bold = true,
+        ("i", None) => style.italic = true,
+        ("wave", value) => {
+            style.effect = Some(TextEffect::Wave {
+                amplitude: parse_amplitude(value, DEFAULT_WAVE_AMPLITUDE)?,
+            })
+        }
+        ("shake", value) => {
+            style.effect = Some(TextEffect::Shake {
+                amplitude: parse_amplitude(value, DEFAULT_SHAKE_AMPLITUDE)?,
+            })
+        }
+        _ => return None,
+    }
+
+    Some(style)
+}
+
+fn parse_amplitude(value: Option<&str>, default: f32) -> Option<f32> {
+    match value {
+        Some(value) => value.parse().ok(),
+        None => Some(default),
+    }
+}
+
+/// Parses a color name, or a hex color as `#rrggbb` or `#rrggbbaa`.
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::new(channel(0)?, channel(2)?, channel(4)?, 255)),
+            8 => Some(Color::new(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            )),
+            _ => None,
+        };
+    }
+
+    let color = match value {
+        "white" => WHITE,
+        "black" => BLACK,
+        "red" => Color::new(255, 0, 0, 255),
+        "green" => Color::new(0, 255, 0, 255),
+        "blue" => Color::new(0, 0, 255, 255),
+        "yellow" => Color::new(255, 255, 0, 255),
+        "cyan" => Color::new(0, 255, 255, 255),
+        "magenta" => Color::new(255, 0, 255, 255),
+        "orange" => Color::new(255, 165, 0, 255),
+        "gray" | "grey" => Color::new(128, 128, 128, 255),
+        _ => return None,
+    };
+
+    Some(color)
+}
+
+/// Adds the text collected so far as a span, merged into the previous span if they share a style.
+fn push_span(spans: &mut Vec<TextSpan>, text: &mut String, style: &TextSpan) {
+    if text.is_empty() {
+        return;
+    }
+
+    match spans.last_mut() {
+        Some(last) if last.icon.is_none() && last.same_style(style) => last.text.push_str(text),
+        _ => spans.push(TextSpan {
+            text: text.clone(),
+            icon: None,
+            ..style.clone()
+        }),
+    }
+    text.clear();
+}
+
+/// A repeatable pseudo random value from -1 to 1.
+fn noise(index: u32, step: u32, axis: u32) -> f32 {
+    let mut x = index
+        .wrapping_mul(0x9E37_79B9)
+        .wrapping_add(step.wrapping_mul(0x85EB_CA6B))
+        .wrapping_add(axis.wrapping_mul(0xC2B2_AE35));
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+
+    (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_markup, TextEffect, TextSpan};
+    use crate::Color;
+
+    #[test]
+    fn nested_tags_split_into_spans() {
+        let spans = parse_markup("Hot [color=red]fire [b]now[/color]![/b] [wave]ok[/wave]");
+        let red = Some(Color::new(255, 0, 0, 255));
+
+        assert_eq!(spans.len(), 6);
+        assert_eq!(spans[0].text, "Hot ");
+        assert_eq!((spans[1].text.as_str(), spans[1].color), ("fire ", red));
+        assert!(spans[2].bold && spans[2].color == red);
+        // Closing the color keeps the bold tag opened inside of it.
+        assert!(spans[3].bold && spans[3].color.is_none());
+        assert_eq!(spans[4].text, " ");
+        assert_eq!(spans[5].effect, Some(TextEffect::Wave { amplitude: 4.0 }));
+    }
+
+    #[test]
+    fn unknown_tags_and_icons() {
+        let spans = parse_markup("[size=24]a[icon=coin][x] b[/size] [/i]");
+
+        assert_eq!(
+            spans,
+            vec![
+                TextSpan {
+                    text: "a".into(),
+                    size: Some(24.0),
+                    ..Default::default()
+                },
+                TextSpan {
+                    icon: Some("coin".into()),
+                    size: Some(24.0),
+                    ..Default::default()
+                },
+                TextSpan {
+                    text: "[x] b".into(),
+                    size: Some(24.0),
+                    ..Default::default()
+                },
+                TextSpan {
+                    text: " [/i]".into(),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+}
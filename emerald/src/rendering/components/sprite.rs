@@ -1,4 +1,7 @@
-use crate::{texture::TextureKey, *};
+use crate::{
+    blend_mode::BlendMode, material::MaterialKey, rendering_engine::QuadPlacement,
+    texture::TextureKey, *,
+};
 
 #[derive(Clone, Debug)]
 pub struct Sprite {
@@ -10,8 +13,23 @@ pub struct Sprite {
     pub visible: bool,
     pub color: Color,
     pub centered: bool,
+    /// Mirrors the texture horizontally, without changing the scale.
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// The point of the sprite placed at its position and rotated around, overriding `centered`.
+    /// From (0, 0) at the bottom left of the sprite to (1, 1) at its top right,
+    /// mirrored along with the sprite when it's flipped.
+    pub pivot: Option<Vector2<f32>>,
+    /// The angle in radians the sides of the sprite lean to the right, and its top and bottom lean up.
+    pub skew: Vector2<f32>,
     pub(crate) texture_key: TextureKey,
     pub z_index: f32,
+    /// Custom shader to draw with, instead of the default textured shader.
+    pub material: Option<MaterialKey>,
+    /// How the drawable is combined with what was drawn before it.
+    pub blend_mode: BlendMode,
+    /// Texture of the same size holding the sprite's normals, shading it per pixel in lit worlds.
+    pub normal_map: Option<TextureKey>,
 }
 impl Sprite {
     pub fn from_texture(texture_key: TextureKey) -> Self {
@@ -23,8 +41,26 @@ impl Sprite {
             offset: Vector2::new(0.0, 0.0),
             color: WHITE,
             centered: true,
+            flip_x: false,
+            flip_y: false,
+            pivot: None,
+            skew: Vector2::new(0.0, 0.0),
             z_index: 0.0,
             visible: true,
+            material: None,
+            blend_mode: BlendMode::Alpha,
+            normal_map: None,
+        }
+    }
+
+    pub(crate) fn placement(&self) -> QuadPlacement {
+        QuadPlacement {
+            centered: self.centered,
+            pivot: self.pivot,
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            skew: self.skew,
+            snap: true,
         }
     }
 }
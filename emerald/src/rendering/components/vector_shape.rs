@@ -0,0 +1,432 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::{rendering::physics_debug::line_triangles, *};
+
+/// How the open ends of a stroked line are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end points.
+    Butt,
+    /// Extends past the end points by half the thickness.
+    Square,
+    /// Rounded past the end points. Corners of polylines are rounded as well.
+    Round,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VectorShapeKind {
+    Line {
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+    },
+    Polyline {
+        points: Vec<Vector2<f32>>,
+        /// Whether the last point connects back to the first.
+        closed: bool,
+    },
+    Circle {
+        radius: f32,
+        /// Filled circles ignore the thickness, outlines are stroked with it.
+        filled: bool,
+    },
+    /// Stroked along a circle from one angle to the other, in radians counter-clockwise from the x axis.
+    Arc {
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// Centered on the offset of the shape, stroked with square corners.
+    RectOutline { width: f32, height: f32 },
+    /// Filled, the outline may be concave but shouldn't cross itself.
+    Polygon { points: Vec<Vector2<f32>> },
+}
+
+/// A line or shape drawn in a solid color, stroked at a thickness or filled.
+/// Points are in pixels relative to the entity's translation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VectorShape {
+    pub kind: VectorShapeKind,
+    pub color: Color,
+    /// Width of stroked lines, in pixels.
+    pub thickness: f32,
+    pub cap: LineCap,
+    /// Number of segments a full circle is made of, arcs and rounded caps use a share of them.
+    pub segments: u32,
+    pub offset: Vector2<f32>,
+    pub z_index: f32,
+    pub visible: bool,
+}
+impl VectorShape {
+    pub fn new(kind: VectorShapeKind, color: Color) -> Self {
+        Self {
+            kind,
+            color,
+            thickness: 1.0,
+            cap: LineCap::Butt,
+            segments: 32,
+            offset: Vector2::new(0.0, 0.0),
+            z_index: 0.0,
+            visible: true,
+        }
+    }
+
+    pub fn line(start: Vector2<f32>, end: Vector2<f32>, thickness: f32, color: Color) -> Self {
+        Self::new(VectorShapeKind::Line { start, end }, color).with_thickness(thickness)
+    }
+
+    pub fn polyline(points: Vec<Vector2<f32>>, closed: bool, thickness: f32, color: Color) -> Self {
+        Self::new(VectorShapeKind::Polyline { points, closed }, color).with_thickness(thickness)
+    }
+
+    pub fn circle(radius: f32, color: Color) -> Self {
+        Self::new(
+            VectorShapeKind::Circle {
+                radius,
+                filled: true,
+            },
+            color,
+        )
+    }
+
+    pub fn circle_outline(radius: f32, thickness: f32, color: Color) -> Self {
+        Self::new(
+            VectorShapeKind::Circle {
+                radius,
+                filled: false,
+            },
+            color,
+        )
+        .with_thickness(thickness)
+    }
+
+    pub fn arc(
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        thickness: f32,
+        color: Color,
+    ) -> Self {
+        Self::new(
+            VectorShapeKind::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            },
+            color,
+        )
+        .with_thickness(thickness)
+    }
+
+    pub fn rect_outline(width: f32, height: f32, thickness: f32, color: Color) -> Self {
+        Self::new(VectorShapeKind::RectOutline { width, height }, color).with_thickness(thickness)
+    }
+
+    pub fn polygon(points: Vec<Vector2<f32>>, color: Color) -> Self {
+        Self::new(VectorShapeKind::Polygon { points }, color)
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Splits the shape into counter-clockwise triangles, offset by the shape's offset.
+    pub fn triangles(&self) -> Vec<[Vector2<f32>; 3]> {
+        let segments = self.segments.max(3);
+        let mut triangles = match &self.kind {
+            VectorShapeKind::Line { start, end } => {
+                stroke(&[*start, *end], false, self.thickness, self.cap, segments)
+            }
+            VectorShapeKind::Polyline { points, closed } => {
+                stroke(points, *closed, self.thickness, self.cap, segments)
+            }
+            VectorShapeKind::Circle { radius, filled } => {
+                if *filled {
+                    fan(Vector2::new(0.0, 0.0), *radius, 0.0, TAU, segments)
+                } else {
+                    let mut points = arc_points(*radius, 0.0, TAU, segments);
+                    points.pop();
+                    stroke(&points, true, self.thickness, self.cap, segments)
+                }
+            }
+            VectorShapeKind::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            } => stroke(
+                &arc_points(*radius, *start_angle, *end_angle, segments),
+                false,
+                self.thickness,
+                self.cap,
+                segments,
+            ),
+            VectorShapeKind::RectOutline { width, height } => {
+                let half = self.thickness / 2.0;
+                let (x, y) = (width / 2.0, height / 2.0);
+                // The top and bottom edges cover the corners, so they come out square without overlapping.
+                [
+                    (-x - half, y - half, x + half, y + half),
+                    (-x - half, -y - half, x + half, -y + half),
+                    (-x - half, -y + half, -x + half, y - half),
+                    (x - half, -y + half, x + half, y - half),
+                ]
+                .iter()
+                .flat_map(|&(left, bottom, right, top)| {
+                    let (a, b, c, d) = (
+                        Vector2::new(left, bottom),
+                        Vector2::new(right, bottom),
+                        Vector2::new(right, top),
+                        Vector2::new(left, top),
+                    );
+                    [[a, b, c], [a, c, d]]
+                })
+                .collect()
+            }
+            VectorShapeKind::Polygon { points } => fill_polygon(points),
+        };
+
+        for triangle in &mut triangles {
+            for point in triangle.iter_mut() {
+                *point += self.offset;
+            }
+            *triangle = counter_clockwise(*triangle);
+        }
+
+        triangles
+    }
+}
+
+/// Triangulates a polygon that may be concave, returning no triangles for fewer than 3 points.
+pub(crate) fn fill_polygon(points: &[Vector2<f32>]) -> Vec<[Vector2<f32>; 3]> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    let vertices = points
+        .iter()
+        .flat_map(|point| [point.x as f64, point.y as f64])
+        .collect::<Vec<f64>>();
+
+    emd_earcutr::earcut(&vertices, &Vec::new(), 2)
+        .chunks_exact(3)
+        .map(|triangle| {
+            [
+                points[triangle[0]],
+                points[triangle[1]],
+                points[triangle[2]],
+            ]
+        })
+        .map(counter_clockwise)
+        .collect()
+}
+
+/// Points along a circle centered on the origin, from one angle to the other inclusive.
+fn arc_points(radius: f32, start_angle: f32, end_angle: f32, segments: u32) -> Vec<Vector2<f32>> {
+    let sweep = end_angle - start_angle;
+    let count = segments_for(sweep, segments);
+
+    (0..=count)
+        .map(|index| {
+            let angle = start_angle + sweep * index as f32 / count as f32;
+            Vector2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// A slice of a circle, as triangles sharing its center.
+fn fan(
+    center: Vector2<f32>,
+    radius: f32,
+    start_angle: f32,
+    sweep: f32,
+    segments: u32,
+) -> Vec<[Vector2<f32>; 3]> {
+    let points = arc_points(radius, start_angle, start_angle + sweep, segments);
+
+    points
+        .windows(2)
+        .map(|pair| [center, center + pair[0], center + pair[1]])
+        .collect()
+}
+
+/// The share of a full circle's segments used for a sweep, at least one.
+fn segments_for(sweep: f32, segments: u32) -> u32 {
+    ((segments as f32 * sweep.abs() / TAU).ceil() as u32).max(1)
+}
+
+/// Strokes the lines between the points, joining corners with bevels, or rounds for round caps.
+fn stroke(
+    points: &[Vector2<f32>],
+    closed: bool,
+    thickness: f32,
+    cap: LineCap,
+    segments: u32,
+) -> Vec<[Vector2<f32>; 3]> {
+    let half = thickness / 2.0;
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 2 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 2 || half <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut lines: Vec<(Vector2<f32>, Vector2<f32>)> =
+        points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if closed && points.len() > 2 {
+        lines.push((points[points.len() - 1], points[0]));
+    }
+    let directions: Vec<Vector2<f32>> = lines
+        .iter()
+        .map(|(start, end)| (end - start).normalize())
+        .collect();
+
+    let mut triangles = Vec::new();
+    let open = !closed || points.len() == 2;
+    for (index, (start, end)) in lines.iter().enumerate() {
+        let (mut start, mut end) = (*start, *end);
+        if open && cap == LineCap::Square {
+            if index == 0 {
+                start -= directions[index] * half;
+            }
+            if index == lines.len() - 1 {
+                end += directions[index] * half;
+            }
+        }
+
+        if let Some(quad) = line_triangles(start, end, thickness) {
+            triangles.extend(quad);
+        }
+    }
+
+    // Corners between consecutive lines, including the one at the first point of closed lines.
+    let corners = if open { 1..lines.len() } else { 0..lines.len() };
+    for index in corners {
+        let previous = (index + lines.len() - 1) % lines.len();
+        let (d1, d2) = (directions[previous], directions[index]);
+        let turn = d1.perp(&d2).atan2(d1.dot(&d2));
+        if turn.abs() <= f32::EPSILON {
+            continue;
+        }
+
+        // The gap between the lines opens on the outside of the turn.
+        let side = if turn > 0.0 { -half } else { half };
+        let outer = Vector2::new(-d1.y, d1.x) * side;
+        let corner = lines[index].0;
+        if cap == LineCap::Round {
+            triangles.extend(fan(corner, half, outer.y.atan2(outer.x), turn, segments));
+        } else {
+            let next_outer = Vector2::new(-d2.y, d2.x) * side;
+            triangles.push([corner, corner + outer, corner + next_outer]);
+        }
+    }
+
+    if open && cap == LineCap::Round {
+        let first = directions[0];
+        let last = directions[directions.len() - 1];
+        triangles.extend(fan(
+            points[0],
+            half,
+            first.y.atan2(first.x) + PI / 2.0,
+            PI,
+            segments,
+        ));
+        triangles.extend(fan(
+            points[points.len() - 1],
+            half,
+            last.y.atan2(last.x) - PI / 2.0,
+            PI,
+            segments,
+        ));
+    }
+
+    triangles
+}
+
+fn counter_clockwise([a, b, c]: [Vector2<f32>; 3]) -> [Vector2<f32>; 3] {
+    if (b - a).perp(&(c - a)) < 0.0 {
+        [a, c, b]
+    } else {
+        [a, b, c]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill_polygon, LineCap, VectorShape};
+    use crate::{Vector2, WHITE};
+
+    fn area(triangles: &[[Vector2<f32>; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| (b - a).perp(&(c - a)) / 2.0)
+            .sum()
+    }
+
+    #[test]
+    fn square_caps_extend_lines() {
+        let line = VectorShape::line(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), 2.0, WHITE);
+        assert!((area(&line.triangles()) - 20.0).abs() < 0.001);
+
+        let line = line.with_cap(LineCap::Square);
+        assert!((area(&line.triangles()) - 24.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn rect_outlines_have_square_corners() {
+        let outline = VectorShape::rect_outline(10.0, 10.0, 2.0, WHITE);
+        let triangles = outline.triangles();
+        let covered = |x: f32, y: f32| {
+            let point = Vector2::new(x, y);
+            triangles.iter().any(|[a, b, c]| {
+                (b - a).perp(&(point - a)) >= 0.0
+                    && (c - b).perp(&(point - b)) >= 0.0
+                    && (a - c).perp(&(point - c)) >= 0.0
+            })
+        };
+
+        for (x, y) in [(1.0, 1.0), (-1.0, 1.0), (1.0, -1.0), (-1.0, -1.0)] {
+            assert!(covered(5.9 * x, 5.9 * y));
+            assert!(covered(4.1 * x, 4.1 * y));
+            assert!(!covered(3.9 * x, 3.9 * y));
+        }
+        // The edges don't overlap, so translucent outlines are blended evenly.
+        assert!((area(&triangles) - (12.0 * 12.0 - 8.0 * 8.0)).abs() < 0.001);
+        assert!(triangles
+            .iter()
+            .all(|[a, b, c]| (b - a).perp(&(c - a)) >= 0.0));
+    }
+
+    #[test]
+    fn concave_polygons_are_filled() {
+        let points = vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(4.0, 0.0),
+            Vector2::new(4.0, 4.0),
+            Vector2::new(2.0, 1.0),
+            Vector2::new(0.0, 4.0),
+        ];
+        let triangles = fill_polygon(&points);
+        assert_eq!(triangles.len(), 3);
+        assert!((area(&triangles) - 10.0).abs() < 0.001);
+        assert!(fill_polygon(&points[..2]).is_empty());
+    }
+
+    #[test]
+    fn circles_use_their_segments() {
+        let mut circle = VectorShape::circle(10.0, WHITE);
+        circle.segments = 16;
+        assert_eq!(circle.triangles().len(), 16);
+
+        let mut arc = VectorShape::arc(10.0, 0.0, std::f32::consts::PI, 2.0, WHITE);
+        arc.segments = 16;
+        // Eight quads joined by seven bevels.
+        assert_eq!(arc.triangles().len(), 23);
+    }
+}
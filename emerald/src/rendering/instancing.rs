@@ -0,0 +1,417 @@
+use std::collections::VecDeque;
+
+use wgpu::{util::DeviceExt, BindGroupLayout, Device, RenderPipeline};
+
+use crate::{
+    rendering_engine::TexturedTriDraw, shaders::textured_quad::Vertex, Rectangle, Vector2,
+};
+
+/// Corners of the quad every instance is drawn with, from -0.5 to 0.5 with y pointing up.
+const UNIT_QUAD: [[f32; 2]; 4] = [[-0.5, 0.5], [-0.5, -0.5], [0.5, -0.5], [0.5, 0.5]];
+const UNIT_QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+const VERTEX_SIZE: u64 = std::mem::size_of::<Vertex>() as u64;
+const INDEX_SIZE: u64 = std::mem::size_of::<u32>() as u64;
+
+/// A textured quad, as the corner of the unit quad maps onto the screen.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct QuadInstance {
+    /// In clip space, as are the axes.
+    pub center: [f32; 2],
+    /// The edges of the quad, its width along `axis_x` and its height along `axis_y`.
+    pub axis_x: [f32; 2],
+    pub axis_y: [f32; 2],
+    /// The area of the texture, as (x, y, width, height) from its top left corner in texture coordinates.
+    pub uv_rect: [f32; 4],
+    pub color: [f32; 4],
+}
+impl QuadInstance {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+            1 => Float32x2,
+            2 => Float32x2,
+            3 => Float32x2,
+            4 => Float32x4,
+            5 => Float32x4,
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<QuadInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+
+    /// The corners of the quad, in the order the unit quad gives them.
+    pub fn vertices(&self) -> [Vertex; 4] {
+        let [x, y, width, height] = self.uv_rect;
+        let tex_coords = [
+            [x, y],
+            [x, y + height],
+            [x + width, y + height],
+            [x + width, y],
+        ];
+
+        let mut vertices = [Vertex {
+            position: [0.0, 0.0],
+            tex_coords: [0.0, 0.0],
+            color: self.color,
+        }; 4];
+        for (index, vertex) in vertices.iter_mut().enumerate() {
+            let [corner_x, corner_y] = UNIT_QUAD[index];
+            vertex.position = [
+                self.center[0] + self.axis_x[0] * corner_x + self.axis_y[0] * corner_y,
+                self.center[1] + self.axis_x[1] * corner_x + self.axis_y[1] * corner_y,
+            ];
+            vertex.tex_coords = tex_coords[index];
+        }
+
+        vertices
+    }
+
+    /// The area covered by the quad, in clip space.
+    pub fn bounds(&self) -> Rectangle {
+        let half_width = (self.axis_x[0].abs() + self.axis_y[0].abs()) / 2.0;
+        let half_height = (self.axis_x[1].abs() + self.axis_y[1].abs()) / 2.0;
+
+        Rectangle::new(
+            self.center[0] - half_width,
+            self.center[1] - half_height,
+            half_width * 2.0,
+            half_height * 2.0,
+        )
+    }
+}
+
+/// Turns quad batches back into vertices and indices, so they're drawn like any other tris.
+/// Batches of at least `min_instanced` quads without a material are left to be drawn instanced,
+/// every batch is expanded if it's `None`.
+pub(crate) fn expand_quad_draws(
+    draws: &mut VecDeque<TexturedTriDraw>,
+    instances: &[QuadInstance],
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+    min_instanced: Option<usize>,
+) {
+    for draw in draws.iter_mut() {
+        let range = match &draw.instances {
+            Some(range) => range.start as usize..range.end as usize,
+            None => continue,
+        };
+        if let Some(min_instanced) = min_instanced {
+            if draw.count() >= min_instanced && draw.state.material.is_none() {
+                continue;
+            }
+        }
+
+        let vertices_start = vertices.len() as u64 * VERTEX_SIZE;
+        let indices_start = indices.len() as u64 * INDEX_SIZE;
+        for (index, instance) in instances[range].iter().enumerate() {
+            let start = index as u32 * 4;
+            vertices.extend(instance.vertices());
+            indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+        }
+
+        draw.vertices_range = vertices_start..vertices.len() as u64 * VERTEX_SIZE;
+        draw.indices_range = indices_start..indices.len() as u64 * INDEX_SIZE;
+        draw.instances = None;
+    }
+}
+
+/// The pipeline and buffers that draw large batches of quads as instances of a single quad.
+pub(crate) struct InstancedQuads {
+    pub pipeline: RenderPipeline,
+    pub quad_vertices: wgpu::Buffer,
+    pub quad_indices: wgpu::Buffer,
+    pub instance_buffer: wgpu::Buffer,
+}
+impl InstancedQuads {
+    pub fn new(
+        device: &Device,
+        format: wgpu::TextureFormat,
+        texture_bind_group_layout: &BindGroupLayout,
+    ) -> Self {
+        let pipeline = Self::create_pipeline(
+            device,
+            format,
+            texture_bind_group_layout,
+            wgpu::BlendState::ALPHA_BLENDING,
+        );
+
+        let quad_vertices = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Unit Quad Vertex Buffer"),
+            contents: bytemuck::cast_slice(&UNIT_QUAD),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let quad_indices = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Unit Quad Index Buffer"),
+            contents: bytemuck::cast_slice(&UNIT_QUAD_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance Buffer"),
+            size: std::mem::size_of::<QuadInstance>() as u64 * 1024,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            pipeline,
+            quad_vertices,
+            quad_indices,
+            instance_buffer,
+        }
+    }
+
+    /// Creates the pipeline drawing quad instances with the given blending.
+    pub fn create_pipeline(
+        device: &Device,
+        format: wgpu::TextureFormat,
+        texture_bind_group_layout: &BindGroupLayout,
+        blend: wgpu::BlendState,
+    ) -> RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Instanced Quad Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/instanced_quad.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Instanced Quad Pipeline Layout"),
+            bind_group_layouts: &[texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let corner_layout = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+        };
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Instanced Quad Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[corner_layout, QuadInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(blend),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    /// Writes the instances to the instance buffer, growing it if they don't fit.
+    pub fn upload(&mut self, device: &Device, queue: &wgpu::Queue, instances: &[QuadInstance]) {
+        let bytes: &[u8] = bytemuck::cast_slice(instances);
+        if bytes.len() as u64 > self.instance_buffer.size() {
+            self.instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Instance Buffer"),
+                contents: bytes,
+                usage: self.instance_buffer.usage(),
+            });
+        } else if !bytes.is_empty() {
+            queue.write_buffer(&self.instance_buffer, 0, bytes);
+        }
+    }
+
+    pub fn index_count() -> u32 {
+        UNIT_QUAD_INDICES.len() as u32
+    }
+}
+
+/// The edges of a quad of the given size, skewed and then rotated.
+/// The skew is the angle each axis leans towards the other, in radians.
+pub(crate) fn quad_axes(
+    size: Vector2<f32>,
+    rotation: f32,
+    skew: Vector2<f32>,
+) -> (Vector2<f32>, Vector2<f32>) {
+    let (sin, cos) = rotation.sin_cos();
+    let rotate = |v: Vector2<f32>| Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+
+    (
+        rotate(Vector2::new(size.x, size.x * skew.y.tan())),
+        rotate(Vector2::new(size.y * skew.x.tan(), size.y)),
+    )
+}
+
+/// Maps a quad with the given edges onto the screen, see `quad_axes`.
+/// The quad's corners are given through `to_screen`, which maps a point in pixels to clip space.
+pub(crate) fn quad_instance(
+    center: Vector2<f32>,
+    (axis_x, axis_y): (Vector2<f32>, Vector2<f32>),
+    uv_rect: Rectangle,
+    color: [f32; 4],
+    to_screen: impl Fn(Vector2<f32>) -> Vector2<f32>,
+) -> QuadInstance {
+    let screen_center = to_screen(center);
+    let mut axis_x = to_screen(center + axis_x) - screen_center;
+    let axis_y = to_screen(center + axis_y) - screen_center;
+    let mut uv_rect = uv_rect;
+
+    // Skewing the axes past each other mirrors the quad, which would then be culled as a back face.
+    // Mirroring an axis along with the texture covers the same area, facing forwards.
+    if axis_x.perp(&axis_y) < 0.0 {
+        axis_x = -axis_x;
+        uv_rect.x += uv_rect.width;
+        uv_rect.width = -uv_rect.width;
+    }
+
+    QuadInstance {
+        center: [screen_center.x, screen_center.y],
+        axis_x: [axis_x.x, axis_x.y],
+        axis_y: [axis_y.x, axis_y.y],
+        uv_rect: [uv_rect.x, uv_rect.y, uv_rect.width, uv_rect.height],
+        color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::{expand_quad_draws, quad_axes, quad_instance};
+    use crate::{
+        asset_key::AssetId,
+        rendering_engine::{DrawState, QuadPlacement, TexturedTriDraw},
+        Rectangle, Vector2,
+    };
+
+    #[test]
+    fn instances_expand_to_the_corners_of_the_quad() {
+        let instance = quad_instance(
+            Vector2::new(10.0, 20.0),
+            quad_axes(
+                Vector2::new(4.0, 2.0),
+                std::f32::consts::FRAC_PI_2,
+                Vector2::new(0.0, 0.0),
+            ),
+            Rectangle::new(0.0, 0.0, 0.5, 1.0),
+            [1.0; 4],
+            |point| point,
+        );
+        let vertices = instance.vertices();
+
+        // Rotated a quarter turn counter-clockwise, the top left corner ends up at the bottom left.
+        let [x, y] = vertices[0].position;
+        assert!((x - 9.0).abs() < 0.001 && (y - 18.0).abs() < 0.001);
+        assert_eq!(vertices[0].tex_coords, [0.0, 0.0]);
+        assert_eq!(vertices[2].tex_coords, [0.5, 1.0]);
+
+        let bounds = instance.bounds();
+        assert!((bounds.width - 2.0).abs() < 0.001 && (bounds.height - 4.0).abs() < 0.001);
+
+        // Skewed along x, the top edge leans right by the height of the quad.
+        let (axis_x, axis_y) = quad_axes(
+            Vector2::new(4.0, 2.0),
+            0.0,
+            Vector2::new(std::f32::consts::FRAC_PI_4, 0.0),
+        );
+        assert!((axis_x - Vector2::new(4.0, 0.0)).norm() < 0.001);
+        assert!((axis_y - Vector2::new(2.0, 2.0)).norm() < 0.001);
+    }
+
+    #[test]
+    fn heavily_skewed_quads_keep_their_winding() {
+        let axes = quad_axes(Vector2::new(4.0, 2.0), 0.0, Vector2::new(1.0, 1.0));
+        assert!(axes.0.perp(&axes.1) < 0.0);
+
+        let instance = quad_instance(
+            Vector2::new(0.0, 0.0),
+            axes,
+            Rectangle::new(0.0, 0.0, 1.0, 1.0),
+            [1.0; 4],
+            |point| point,
+        );
+        let [x0, y0] = instance.axis_x;
+        let [x1, y1] = instance.axis_y;
+        assert!(x0 * y1 - y0 * x1 > 0.0);
+
+        // The same corners are covered, with the texture mirrored along with the axis.
+        let corner = -axes.0 / 2.0 + axes.1 / 2.0;
+        let vertices = instance.vertices();
+        let vertex = vertices
+            .iter()
+            .find(|vertex| (Vector2::from(vertex.position) - corner).norm() < 0.001)
+            .unwrap();
+        assert_eq!(vertex.tex_coords, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn flipped_quads_keep_their_pivot_in_place() {
+        let position = Vector2::new(10.0, 10.0);
+        let size = Vector2::new(4.0, 2.0);
+        let axes = quad_axes(size, 0.0, Vector2::new(0.0, 0.0));
+        let mut placement = QuadPlacement {
+            pivot: Some(Vector2::new(0.25, 0.0)),
+            ..QuadPlacement::centered(false)
+        };
+        assert_eq!(
+            placement.center(position, size, axes),
+            Vector2::new(11.0, 11.0)
+        );
+
+        // Mirrored along with the texture, the pivot is now a quarter from the right edge.
+        placement.flip_x = true;
+        assert_eq!(placement.pivot(), Some(Vector2::new(0.75, 0.0)));
+        assert_eq!(
+            placement.center(position, size, axes),
+            Vector2::new(9.0, 11.0)
+        );
+
+        placement.flip_y = true;
+        assert_eq!(placement.pivot(), Some(Vector2::new(0.75, 1.0)));
+        assert_eq!(
+            placement.center(position, size, axes),
+            Vector2::new(9.0, 9.0)
+        );
+    }
+
+    #[test]
+    fn small_batches_are_expanded() {
+        let instance = quad_instance(
+            Vector2::new(0.0, 0.0),
+            quad_axes(Vector2::new(1.0, 1.0), 0.0, Vector2::new(0.0, 0.0)),
+            Rectangle::new(0.0, 0.0, 1.0, 1.0),
+            [1.0; 4],
+            |point| point,
+        );
+        let instances = vec![instance; 5];
+        let texture = AssetId::default();
+        let mut small = TexturedTriDraw::quads(texture, texture, 0, DrawState::default());
+        small.add_instance();
+        let mut large = TexturedTriDraw::quads(texture, texture, 2, DrawState::default());
+        large.add_instance();
+        large.add_instance();
+        let mut draws = VecDeque::from([small, large]);
+
+        let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+        expand_quad_draws(&mut draws, &instances, &mut vertices, &mut indices, Some(3));
+        assert!(draws[0].instances.is_none());
+        assert_eq!(draws[1].instances, Some(2..5));
+        assert_eq!((vertices.len(), indices.len()), (8, 12));
+        assert_eq!(draws[0].indices_count(), 12);
+    }
+}
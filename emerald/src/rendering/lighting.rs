@@ -0,0 +1,756 @@
+use std::{collections::VecDeque, ops::Range};
+
+use rapier2d::na::Vector2;
+use wgpu::{util::DeviceExt, BindGroup, RenderPipeline};
+
+use crate::{
+    material::{Material, MaterialDescriptor, MaterialKey},
+    physics_debug::shape_edges,
+    rendering_engine::{create_textured_tri_pipeline, BindGroupLayoutId, TexturedTriDraw},
+    shaders::textured_quad::Vertex,
+    texture::TextureKey,
+    AssetEngine, Color, EmeraldError, Transform, Translation, World,
+};
+
+use super::{
+    components::{LightOccluder, OccluderShape, PointLight, SpotLight, ViewTransform},
+    instancing::{expand_quad_draws, QuadInstance},
+    post_process::{create_target, target_view, PostProcessContext},
+};
+
+const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+/// Lights drawn in a single pass, each marks its shadows with its own stencil value.
+const LIGHTS_PER_PASS: usize = 255;
+
+/// Shadows are extruded this many times the radius of the light, so they leave its circle
+/// even when the edge casting them passes close to the light.
+const SHADOW_REACH: f32 = 64.0;
+
+/// Multiplies the color of the frame by the color drawn, keeping its alpha.
+pub(crate) const MULTIPLY_BLEND: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Dst,
+        dst_factor: wgpu::BlendFactor::Zero,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// The lighting of a world, disabled by default.
+/// When enabled, everything drawn through a camera is multiplied by the light reaching it,
+/// being the ambient light plus the light of every `PointLight` and `SpotLight`.
+/// Screen space layers drawn after the last camera layer are left unlit.
+#[derive(Clone, Debug)]
+pub struct Lighting {
+    pub enabled: bool,
+    /// Light reaching everything, even where no light shines.
+    pub ambient: Color,
+    /// Number of segments used to approximate round colliders that cast shadows.
+    pub circle_subdivisions: u32,
+}
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ambient: Color::new(40, 40, 56, 255),
+            circle_subdivisions: 16,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightVertex {
+    pub position: [f32; 2],
+    /// From the vertex to the light in pixels, along with the height of the light.
+    pub light_offset: [f32; 3],
+    /// Linear color, with the intensity in alpha.
+    pub color: [f32; 4],
+    /// Radius, falloff, spot direction, and the cosine of the spot cone's edge.
+    pub params: [f32; 4],
+}
+impl LightVertex {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x3,
+            2 => Float32x4,
+            3 => Float32x4,
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LightVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// A light in pixels relative to the camera, ready to be drawn.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LightInstance {
+    pub position: Vector2<f32>,
+    pub color: [f32; 4],
+    pub radius: f32,
+    pub falloff: f32,
+    pub height: f32,
+    pub direction: f32,
+    /// Cosine of the edge of a spot light's cone, point lights use a value below -1.
+    pub cone_edge: f32,
+    pub casts_shadows: bool,
+}
+
+/// Collects the enabled lights of the world, relative to the camera.
+pub(crate) fn collect_lights(
+    world: &World,
+    camera_translation: &Translation,
+) -> Vec<LightInstance> {
+    let relative = |transform: &Transform, offset: Vector2<f32>| {
+        let translation = transform.translation - *camera_translation;
+        Vector2::new(translation.x, translation.y) + offset
+    };
+    let linear_color = |color: &Color, intensity: f32| {
+        let (r, g, b, _) = color.to_percentage_linear();
+        [r as f32, g as f32, b as f32, intensity]
+    };
+
+    let mut lights = Vec::new();
+    for (_, (light, transform)) in world.query::<(&PointLight, &Transform)>().iter() {
+        if light.enabled && light.radius > 0.0 {
+            lights.push(LightInstance {
+                position: relative(transform, light.offset),
+                color: linear_color(&light.color, light.intensity),
+                radius: light.radius,
+                falloff: light.falloff,
+                height: light.height,
+                direction: 0.0,
+                cone_edge: -2.0,
+                casts_shadows: light.casts_shadows,
+            });
+        }
+    }
+    for (_, (light, transform)) in world.query::<(&SpotLight, &Transform)>().iter() {
+        if light.enabled && light.radius > 0.0 {
+            lights.push(LightInstance {
+                position: relative(transform, light.offset),
+                color: linear_color(&light.color, light.intensity),
+                radius: light.radius,
+                falloff: light.falloff,
+                height: light.height,
+                direction: light.direction + transform.rotation,
+                cone_edge: (light.angle / 2.0).cos(),
+                casts_shadows: light.casts_shadows,
+            });
+        }
+    }
+
+    lights
+}
+
+/// Collects the edges of every enabled occluder in the world, relative to the camera.
+pub(crate) fn collect_occluder_edges(
+    world: &World,
+    camera_translation: &Translation,
+    circle_subdivisions: u32,
+) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+    let camera = Vector2::new(camera_translation.x, camera_translation.y);
+    let mut edges = Vec::new();
+
+    for (entity, (occluder, transform)) in world.query::<(&LightOccluder, &Transform)>().iter() {
+        if !occluder.enabled {
+            continue;
+        }
+
+        match &occluder.shape {
+            OccluderShape::Polygon(points) => {
+                let translation = Vector2::new(transform.translation.x, transform.translation.y);
+                edges.extend(polygon_edges(
+                    points,
+                    translation - camera,
+                    transform.rotation,
+                ));
+            }
+            OccluderShape::Colliders => {
+                if let Some(physics_engine) = world.physics_engine.as_ref() {
+                    for handle in physics_engine.get_colliders_handles(entity) {
+                        if let Some(collider) = physics_engine.colliders.get(handle) {
+                            edges.extend(
+                                shape_edges(
+                                    collider.shape(),
+                                    collider.position(),
+                                    circle_subdivisions,
+                                )
+                                .into_iter()
+                                .map(|(start, end)| (start - camera, end - camera)),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+/// The edges of a closed polygon, rotated and moved to the given translation.
+fn polygon_edges(
+    points: &[Vector2<f32>],
+    translation: Vector2<f32>,
+    rotation: f32,
+) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+    let (sin, cos) = rotation.sin_cos();
+    let place = |point: &Vector2<f32>| {
+        translation + Vector2::new(cos * point.x - sin * point.y, sin * point.x + cos * point.y)
+    };
+
+    (0..points.len())
+        .map(|i| (place(&points[i]), place(&points[(i + 1) % points.len()])))
+        .collect()
+}
+
+/// The corners of the shadow an edge casts from a light, extruded away from it.
+fn shadow_quad(
+    light: Vector2<f32>,
+    start: Vector2<f32>,
+    end: Vector2<f32>,
+    reach: f32,
+) -> [Vector2<f32>; 4] {
+    let extrude = |point: Vector2<f32>| {
+        let direction = point - light;
+        let length = direction.norm();
+        if length > 0.0 {
+            point + direction / length * reach
+        } else {
+            point
+        }
+    };
+
+    [start, end, extrude(end), extrude(start)]
+}
+
+/// Distance from a point to the closest point of a segment.
+fn distance_to_segment(point: Vector2<f32>, start: Vector2<f32>, end: Vector2<f32>) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.norm_squared();
+    let t = if length_squared > 0.0 {
+        ((point - start).dot(&segment) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (start + segment * t - point).norm()
+}
+
+/// The vertices of every light and its shadows, as lists of triangles.
+#[derive(Default)]
+pub(crate) struct LightGeometry {
+    pub vertices: Vec<LightVertex>,
+    /// The vertices of each light's shadows, followed by those of the light itself.
+    pub lights: Vec<(Range<u32>, Range<u32>)>,
+}
+impl LightGeometry {
+    pub fn build(
+        lights: &[LightInstance],
+        edges: &[(Vector2<f32>, Vector2<f32>)],
+        view: &ViewTransform,
+        target_size: (u32, u32),
+    ) -> Self {
+        let half_size = Vector2::new(target_size.0 as f32 / 2.0, target_size.1 as f32 / 2.0);
+        let to_clip = |point: Vector2<f32>| {
+            let position = view.apply(point);
+            [position.x / half_size.x, position.y / half_size.y]
+        };
+
+        let mut geometry = Self::default();
+        for light in lights {
+            let shadow_start = geometry.vertices.len() as u32;
+            if light.casts_shadows {
+                for (start, end) in edges {
+                    if distance_to_segment(light.position, *start, *end) >= light.radius {
+                        continue;
+                    }
+
+                    let quad =
+                        shadow_quad(light.position, *start, *end, light.radius * SHADOW_REACH);
+                    for index in [0, 1, 2, 0, 2, 3] {
+                        geometry.vertices.push(LightVertex {
+                            position: to_clip(quad[index]),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+
+            let light_start = geometry.vertices.len() as u32;
+            let r = light.radius;
+            let corners = [
+                Vector2::new(-r, -r),
+                Vector2::new(r, -r),
+                Vector2::new(r, r),
+                Vector2::new(-r, r),
+            ];
+            for index in [0, 1, 2, 0, 2, 3] {
+                let corner = corners[index];
+                geometry.vertices.push(LightVertex {
+                    position: to_clip(light.position + corner),
+                    light_offset: [-corner.x, -corner.y, light.height],
+                    color: light.color,
+                    params: [
+                        light.radius,
+                        light.falloff,
+                        light.direction,
+                        light.cone_edge,
+                    ],
+                });
+            }
+
+            geometry.lights.push((
+                shadow_start..light_start,
+                light_start..geometry.vertices.len() as u32,
+            ));
+        }
+
+        geometry
+    }
+}
+
+/// Normal maps drawn with the same quads as their sprites, collected while a lit world is drawn.
+#[derive(Default)]
+pub(crate) struct NormalMapDraws {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub instances: Vec<QuadInstance>,
+    pub draws: VecDeque<TexturedTriDraw>,
+}
+
+struct StencilTarget {
+    view: wgpu::TextureView,
+    size: (u32, u32),
+}
+
+/// Draws the light accumulation targets that lit worlds are multiplied by.
+#[derive(Default)]
+pub(crate) struct LightRenderer {
+    /// A world can be drawn with lighting several times a frame, each needs a target until the frame is rendered.
+    accumulation_targets: Vec<TextureKey>,
+    targets_used: usize,
+    /// Suffix shared by the labels of the accumulation and normal targets, bumped each time one is resized.
+    target_uid: usize,
+    normal_target: Option<TextureKey>,
+    stencil: Option<StencilTarget>,
+    normal_pipeline: Option<RenderPipeline>,
+    light_pipeline: Option<RenderPipeline>,
+    shadow_pipeline: Option<RenderPipeline>,
+    composite_material: Option<MaterialKey>,
+}
+impl LightRenderer {
+    /// Allows the accumulation targets to be reused, once the previous frame has been rendered.
+    pub fn begin_frame(&mut self) {
+        self.targets_used = 0;
+    }
+
+    /// Draws the normal maps and lights into a light accumulation target.
+    /// Returns the target along with the material that multiplies the frame by it.
+    pub fn render(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &mut AssetEngine,
+        size: (u32, u32),
+        ambient: Color,
+        geometry: &LightGeometry,
+        normal_map_draws: &mut NormalMapDraws,
+    ) -> Result<(TextureKey, MaterialKey), EmeraldError> {
+        self.prepare(ctx, asset_engine, size)?;
+        let accumulation = self.accumulation_targets[self.targets_used].clone();
+        self.targets_used += 1;
+
+        let (normal_target, stencil, normal_pipeline, light_pipeline, shadow_pipeline, composite) =
+            match (
+                &self.normal_target,
+                &self.stencil,
+                &self.normal_pipeline,
+                &self.light_pipeline,
+                &self.shadow_pipeline,
+                &self.composite_material,
+            ) {
+                (Some(a), Some(b), Some(c), Some(d), Some(e), Some(f)) => (a, b, c, d, e, f),
+                _ => return Err(EmeraldError::new("Lighting resources were not created")),
+            };
+        let normal_view = target_view(asset_engine, normal_target)?;
+        let accumulation_view = target_view(asset_engine, &accumulation)?;
+        let normal_bind_group = asset_engine
+            .get_asset::<BindGroup>(&normal_target.bind_group_key.asset_id)
+            .ok_or_else(|| EmeraldError::new("Unable to find the normal map target"))?;
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Lighting Encoder"),
+            });
+
+        // The normal pass has no instanced pipeline, so every quad is drawn from vertices.
+        expand_quad_draws(
+            &mut normal_map_draws.draws,
+            &normal_map_draws.instances,
+            &mut normal_map_draws.vertices,
+            &mut normal_map_draws.indices,
+            None,
+        );
+        let normal_buffers = if normal_map_draws.draws.is_empty() {
+            None
+        } else {
+            Some((
+                ctx.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Normal Map Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&normal_map_draws.vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    }),
+                ctx.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Normal Map Index Buffer"),
+                        contents: bytemuck::cast_slice(&normal_map_draws.indices),
+                        usage: wgpu::BufferUsages::INDEX,
+                    }),
+            ))
+        };
+
+        {
+            // Flat normals, with an alpha of zero marking pixels without a normal map.
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Normal Map Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &normal_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.5,
+                            g: 0.5,
+                            b: 1.0,
+                            a: 0.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            if let Some((vertex_buffer, index_buffer)) = &normal_buffers {
+                render_pass.set_pipeline(normal_pipeline);
+                while let Some(draw) = normal_map_draws.draws.pop_back() {
+                    let bind_group = asset_engine
+                        .get_asset::<BindGroup>(&draw.texture_bind_group_asset_id)
+                        .ok_or_else(|| {
+                            EmeraldError::new(format!(
+                                "Unable to find normal map bind group for AssetId {:?}",
+                                draw.texture_bind_group_asset_id
+                            ))
+                        })?;
+                    render_pass.set_bind_group(0, bind_group, &[]);
+                    render_pass
+                        .set_vertex_buffer(0, vertex_buffer.slice(draw.vertices_range.clone()));
+                    render_pass.set_index_buffer(
+                        index_buffer.slice(draw.indices_range.clone()),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    render_pass.draw_indexed(0..draw.indices_count(), 0, 0..1);
+                }
+            }
+        }
+
+        let light_buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Light Vertex Buffer"),
+                contents: bytemuck::cast_slice(&geometry.vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let (r, g, b, _) = ambient.to_percentage_linear();
+
+        // The stencil is cleared between passes, since each light in a pass marks its own shadows.
+        let mut passes: Vec<&[(Range<u32>, Range<u32>)]> =
+            geometry.lights.chunks(LIGHTS_PER_PASS).collect();
+        if passes.is_empty() {
+            passes.push(&[]);
+        }
+        for (pass_index, lights) in passes.into_iter().enumerate() {
+            let load = if pass_index == 0 {
+                wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a: 1.0 })
+            } else {
+                wgpu::LoadOp::Load
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Light Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &accumulation_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load, store: true },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &stencil.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: false,
+                    }),
+                }),
+            });
+
+            if lights.is_empty() {
+                continue;
+            }
+
+            render_pass.set_bind_group(0, normal_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, light_buffer.slice(..));
+            for (index, (shadows, light)) in lights.iter().enumerate() {
+                render_pass.set_stencil_reference(index as u32 + 1);
+                if !shadows.is_empty() {
+                    render_pass.set_pipeline(shadow_pipeline);
+                    render_pass.draw(shadows.clone(), 0..1);
+                }
+                render_pass.set_pipeline(light_pipeline);
+                render_pass.draw(light.clone(), 0..1);
+            }
+        }
+
+        ctx.queue.submit([encoder.finish()]);
+
+        Ok((accumulation, composite.clone()))
+    }
+
+    /// Creates the targets and pipelines that are missing, and recreates targets of the wrong size.
+    fn prepare(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &mut AssetEngine,
+        size: (u32, u32),
+    ) -> Result<(), EmeraldError> {
+        let index = self.targets_used;
+        let accumulation_ready = self
+            .accumulation_targets
+            .get(index)
+            .is_some_and(|target| target.size() == size);
+        if !accumulation_ready {
+            let label = format!("emd_light_accumulation_{}", self.target_uid);
+            self.target_uid += 1;
+            let target = create_target(ctx, asset_engine, &label, size)?;
+            if index < self.accumulation_targets.len() {
+                self.accumulation_targets[index] = target;
+            } else {
+                self.accumulation_targets.push(target);
+            }
+        }
+
+        if self.normal_target.as_ref().map(|target| target.size()) != Some(size) {
+            let label = format!("emd_light_normals_{}", self.target_uid);
+            self.target_uid += 1;
+            self.normal_target = Some(create_target(ctx, asset_engine, &label, size)?);
+        }
+
+        if self.stencil.as_ref().map(|stencil| stencil.size) != Some(size) {
+            let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("emd_light_stencil"),
+                size: wgpu::Extent3d {
+                    width: size.0,
+                    height: size.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: STENCIL_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            });
+            self.stencil = Some(StencilTarget {
+                view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                size,
+            });
+        }
+
+        let texture_bind_group_layout = ctx
+            .bind_group_layouts
+            .get(&BindGroupLayoutId::TextureQuad)
+            .ok_or_else(|| EmeraldError::new("Unable to get TextureQuad bind group layout"))?;
+
+        if self.normal_pipeline.is_none() {
+            self.normal_pipeline = Some(create_textured_tri_pipeline(
+                ctx.device,
+                ctx.format,
+                "Normal Map",
+                include_str!("./shaders/lighting/normals.wgsl"),
+                &[texture_bind_group_layout],
+                wgpu::BlendState::ALPHA_BLENDING,
+            ));
+        }
+
+        if self.light_pipeline.is_none() || self.shadow_pipeline.is_none() {
+            let shader = ctx
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Light Shader"),
+                    source: wgpu::ShaderSource::Wgsl(
+                        include_str!("./shaders/lighting/light.wgsl").into(),
+                    ),
+                });
+            let layout = ctx
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Light Pipeline Layout"),
+                    bind_group_layouts: &[texture_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+            // Shadows mark the stencil without drawing, lights are drawn where it isn't marked.
+            self.shadow_pipeline = Some(create_light_pipeline(
+                ctx,
+                &shader,
+                &layout,
+                "fs_shadow",
+                None,
+                wgpu::ColorWrites::empty(),
+                wgpu::CompareFunction::Always,
+                wgpu::StencilOperation::Replace,
+            ));
+            let additive = wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            };
+            self.light_pipeline = Some(create_light_pipeline(
+                ctx,
+                &shader,
+                &layout,
+                "fs_main",
+                Some(wgpu::BlendState {
+                    color: additive,
+                    alpha: additive,
+                }),
+                wgpu::ColorWrites::ALL,
+                wgpu::CompareFunction::NotEqual,
+                wgpu::StencilOperation::Keep,
+            ));
+        }
+
+        if self.composite_material.is_none() {
+            self.composite_material = Some(Material::create(
+                "emd_light_composite",
+                include_str!("./shaders/lighting/composite.wgsl"),
+                MaterialDescriptor::default(),
+                ctx.device,
+                ctx.format,
+                texture_bind_group_layout,
+                asset_engine,
+                MULTIPLY_BLEND,
+            )?);
+        }
+
+        Ok(())
+    }
+}
+
+fn create_light_pipeline(
+    ctx: &PostProcessContext<'_>,
+    shader: &wgpu::ShaderModule,
+    layout: &wgpu::PipelineLayout,
+    fragment_entry_point: &str,
+    blend: Option<wgpu::BlendState>,
+    write_mask: wgpu::ColorWrites,
+    stencil_compare: wgpu::CompareFunction,
+    stencil_pass_op: wgpu::StencilOperation,
+) -> RenderPipeline {
+    let stencil_face = wgpu::StencilFaceState {
+        compare: stencil_compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: stencil_pass_op,
+    };
+
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("Light Pipeline {}", fragment_entry_point)),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[LightVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: fragment_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.format,
+                    blend,
+                    write_mask,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: STENCIL_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState {
+                    front: stencil_face,
+                    back: stencil_face,
+                    read_mask: 0xff,
+                    write_mask: 0xff,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier2d::na::Vector2;
+
+    use super::{distance_to_segment, polygon_edges, shadow_quad};
+
+    #[test]
+    fn shadows_extend_away_from_the_light() {
+        let light = Vector2::new(0.0, 0.0);
+        let quad = shadow_quad(
+            light,
+            Vector2::new(10.0, -5.0),
+            Vector2::new(10.0, 5.0),
+            100.0,
+        );
+
+        assert_eq!(quad[0], Vector2::new(10.0, -5.0));
+        assert_eq!(quad[1], Vector2::new(10.0, 5.0));
+        assert!(quad[2].x > 90.0 && quad[2].y > 0.0);
+        assert!(quad[3].x > 90.0 && quad[3].y < 0.0);
+        assert_eq!(
+            distance_to_segment(light, Vector2::new(10.0, -5.0), Vector2::new(10.0, 5.0)),
+            10.0
+        );
+    }
+
+    #[test]
+    fn polygon_edges_are_closed_and_rotated() {
+        let points = [
+            Vector2::new(1.0, 0.0),
+            Vector2::new(0.0, 1.0),
+            Vector2::new(-1.0, 0.0),
+        ];
+        let edges = polygon_edges(&points, Vector2::new(10.0, 0.0), std::f32::consts::PI);
+
+        assert_eq!(edges.len(), 3);
+        assert!((edges[0].0 - Vector2::new(9.0, 0.0)).norm() < 0.001);
+        assert!((edges[2].1 - edges[0].0).norm() < 0.001);
+    }
+}
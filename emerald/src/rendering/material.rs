@@ -0,0 +1,240 @@
+use wgpu::{BindGroup, BindGroupLayout, RenderPipeline};
+
+use crate::{
+    asset_key::AssetKey,
+    rendering_engine::create_textured_tri_pipeline,
+    texture::{Texture, TextureKey},
+    AssetEngine, EmeraldError,
+};
+
+/// The resources a material's shader is given, in bind group 1.
+/// Binding 0 is the uniform block, followed by a texture and sampler binding for each texture.
+///
+/// ```wgsl
+/// struct Params { flash: vec4<f32> }
+/// @group(1) @binding(0) var<uniform> params: Params;
+/// @group(1) @binding(1) var t_palette: texture_2d<f32>;
+/// @group(1) @binding(2) var s_palette: sampler;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MaterialDescriptor {
+    /// Initial values of the uniform block, padded to a multiple of 16 bytes.
+    /// The size of the block can't change once the material is created.
+    pub uniforms: Vec<f32>,
+    /// Extra textures, the first one bound at bindings 1 and 2, the second at 3 and 4, etc.
+    pub textures: Vec<TextureKey>,
+}
+
+/// A custom fragment shader that sprites, aseprites, labels and color rects can be drawn with.
+pub(crate) struct Material {
+    pub pipeline: RenderPipeline,
+    pub bind_group: BindGroup,
+    pub uniform_buffer: wgpu::Buffer,
+    pub uniform_size: u64,
+
+    /// Held so the textures stay loaded while the material uses them.
+    _textures: Vec<TextureKey>,
+}
+impl Material {
+    /// Compiles the fragment shader of a material.
+    /// The source is appended to the shader prelude, and must define `fs_main(in: VertexOutput)`.
+    /// The prelude provides `VertexOutput { tex_coords, color_tint }`, along with the
+    /// drawn texture as `t_diffuse` and `s_diffuse`.
+    pub fn create(
+        label: &str,
+        source: &str,
+        descriptor: MaterialDescriptor,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        texture_bind_group_layout: &BindGroupLayout,
+        asset_engine: &mut AssetEngine,
+        blend: wgpu::BlendState,
+    ) -> Result<MaterialKey, EmeraldError> {
+        let mut textures = Vec::new();
+        for texture_key in &descriptor.textures {
+            match asset_engine.get_asset::<Texture>(&texture_key.asset_key.asset_id) {
+                Some(texture) => textures.push(texture),
+                None => {
+                    return Err(EmeraldError::new(format!(
+                        "Unable to create material {:?}, texture {:?} was not found.",
+                        label, texture_key
+                    )));
+                }
+            }
+        }
+
+        let uniform_bytes = uniform_bytes(&descriptor.uniforms);
+        let uniform_size = uniform_bytes.len() as u64;
+
+        // Invalid shaders are reported through the error scope, rather than panicking.
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let bind_group_layout =
+            create_uniform_layout(device, label, descriptor.textures.len() as u32);
+        let uniform_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{}_material_uniforms", label)),
+                contents: &uniform_bytes,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let bind_group = create_uniform_bind_group(
+            device,
+            label,
+            &bind_group_layout,
+            &uniform_buffer,
+            &textures,
+        );
+
+        let pipeline = create_textured_tri_pipeline(
+            device,
+            format,
+            label,
+            source,
+            &[texture_bind_group_layout, &bind_group_layout],
+            blend,
+        );
+
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(EmeraldError::new(format!(
+                "Unable to create material {:?}. {}",
+                label, error
+            )));
+        }
+
+        let material = Material {
+            pipeline,
+            bind_group,
+            uniform_buffer,
+            uniform_size,
+            _textures: descriptor.textures,
+        };
+        let asset_key = asset_engine.add_asset_with_label(Box::new(material), label)?;
+
+        Ok(MaterialKey::new(label, asset_key))
+    }
+}
+
+/// Creates the layout of a uniform block at binding 0,
+/// followed by a texture and sampler binding for each texture.
+pub(crate) fn create_uniform_layout(
+    device: &wgpu::Device,
+    label: &str,
+    texture_count: u32,
+) -> BindGroupLayout {
+    let mut entries = vec![wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }];
+    for i in 0..texture_count {
+        entries.push(wgpu::BindGroupLayoutEntry {
+            binding: 1 + i * 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        });
+        entries.push(wgpu::BindGroupLayoutEntry {
+            binding: 2 + i * 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        });
+    }
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some(&format!("{}_uniform_layout", label)),
+        entries: &entries,
+    })
+}
+
+/// Binds the uniform buffer and textures to a layout made by `create_uniform_layout`.
+pub(crate) fn create_uniform_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+    textures: &[&Texture],
+) -> BindGroup {
+    let mut entries = vec![wgpu::BindGroupEntry {
+        binding: 0,
+        resource: uniform_buffer.as_entire_binding(),
+    }];
+    for (i, texture) in textures.iter().enumerate() {
+        let i = i as u32;
+        entries.push(wgpu::BindGroupEntry {
+            binding: 1 + i * 2,
+            resource: wgpu::BindingResource::TextureView(&texture.view),
+        });
+        entries.push(wgpu::BindGroupEntry {
+            binding: 2 + i * 2,
+            resource: wgpu::BindingResource::Sampler(&texture.sampler),
+        });
+    }
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(&format!("{}_uniform_group", label)),
+        layout,
+        entries: &entries,
+    })
+}
+
+/// Converts uniform values into the bytes of a uniform block, padded to a multiple of 16 bytes.
+pub(crate) fn uniform_bytes(uniforms: &[f32]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = bytemuck::cast_slice(uniforms).to_vec();
+    let padded_len = bytes.len().max(16).next_multiple_of(16);
+    bytes.resize(padded_len, 0);
+
+    bytes
+}
+
+pub(crate) fn get_material_key(asset_engine: &AssetEngine, label: &str) -> Option<MaterialKey> {
+    asset_engine
+        .get_asset_key_by_label::<Material>(label)
+        .map(|asset_key| MaterialKey::new(label, asset_key))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialKey {
+    label: String,
+    pub(crate) asset_key: AssetKey,
+}
+impl MaterialKey {
+    pub(crate) fn new(label: &str, asset_key: AssetKey) -> Self {
+        MaterialKey {
+            label: label.to_string(),
+            asset_key,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::uniform_bytes;
+
+    #[test]
+    fn uniforms_are_padded_to_16_bytes() {
+        assert_eq!(uniform_bytes(&[]).len(), 16);
+        assert_eq!(uniform_bytes(&[1.0]).len(), 16);
+        assert_eq!(uniform_bytes(&[1.0; 5]).len(), 32);
+
+        let bytes = uniform_bytes(&[2.0]);
+        assert_eq!(&bytes[0..4], &2.0f32.to_ne_bytes());
+        assert!(bytes[4..].iter().all(|byte| *byte == 0));
+    }
+}
@@ -0,0 +1,420 @@
+use rapier2d::{
+    na::{Isometry2, Point2, Vector2},
+    parry::shape::{Shape, TypedShape},
+    prelude::{Collider, RigidBodyType},
+};
+
+use crate::{Color, PhysicsEngine};
+
+/// Settings for `RenderingHandler::draw_physics_debug`.
+/// Each category of debug information can be toggled on its own.
+#[derive(Clone, Debug)]
+pub struct PhysicsDebugSettings {
+    /// Draw the outlines of solid colliders.
+    pub shapes: bool,
+    /// Draw the outlines of sensor colliders.
+    pub sensors: bool,
+    /// Draw the anchors of joints and the links between them.
+    pub joints: bool,
+    /// Draw contact points and their normals.
+    pub contacts: bool,
+    /// Draw the axis-aligned bounding box of every collider.
+    pub aabbs: bool,
+    /// Color collider outlines based on whether their body is awake, sleeping or fixed.
+    pub body_states: bool,
+
+    /// Width of the drawn lines, in pixels.
+    pub line_width: f32,
+    /// Number of segments used to approximate round shapes.
+    pub circle_subdivisions: u32,
+    /// Length of the drawn contact normals, in pixels.
+    pub contact_normal_length: f32,
+
+    pub collider_color: Color,
+    pub sleeping_color: Color,
+    pub fixed_color: Color,
+    pub sensor_color: Color,
+    pub joint_color: Color,
+    pub contact_color: Color,
+    pub aabb_color: Color,
+}
+impl Default for PhysicsDebugSettings {
+    fn default() -> Self {
+        Self {
+            shapes: true,
+            sensors: true,
+            joints: true,
+            contacts: false,
+            aabbs: false,
+            body_states: true,
+            line_width: 1.0,
+            circle_subdivisions: 24,
+            contact_normal_length: 8.0,
+            collider_color: Color::new(0, 255, 0, 255),
+            sleeping_color: Color::new(0, 120, 255, 255),
+            fixed_color: Color::new(160, 160, 160, 255),
+            sensor_color: Color::new(255, 220, 0, 255),
+            joint_color: Color::new(255, 0, 255, 255),
+            contact_color: Color::new(255, 0, 0, 255),
+            aabb_color: Color::new(255, 255, 255, 100),
+        }
+    }
+}
+impl PhysicsDebugSettings {
+    /// Only draws collider outlines, all in the given color.
+    pub fn shapes_only(color: Color) -> Self {
+        Self {
+            shapes: true,
+            sensors: true,
+            joints: false,
+            contacts: false,
+            aabbs: false,
+            body_states: false,
+            collider_color: color,
+            sensor_color: color,
+            ..Default::default()
+        }
+    }
+}
+
+/// A line to draw, in world space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct DebugLine {
+    pub start: Vector2<f32>,
+    pub end: Vector2<f32>,
+    pub color: Color,
+}
+
+/// Collects the lines making up the debug view of the physics engine, in world space.
+pub(crate) fn collect_debug_lines(
+    physics_engine: &PhysicsEngine,
+    settings: &PhysicsDebugSettings,
+) -> Vec<DebugLine> {
+    let mut lines = Vec::new();
+
+    for (_, collider) in physics_engine.colliders.iter() {
+        if settings.aabbs {
+            let aabb = collider.compute_aabb();
+            push_loop(
+                &mut lines,
+                &[
+                    Point2::new(aabb.mins.x, aabb.mins.y),
+                    Point2::new(aabb.maxs.x, aabb.mins.y),
+                    Point2::new(aabb.maxs.x, aabb.maxs.y),
+                    Point2::new(aabb.mins.x, aabb.maxs.y),
+                ],
+                settings.aabb_color,
+            );
+        }
+
+        let visible = if collider.is_sensor() {
+            settings.sensors
+        } else {
+            settings.shapes
+        };
+
+        if visible {
+            let color = collider_color(physics_engine, collider, settings);
+            push_shape(
+                &mut lines,
+                collider.shape(),
+                collider.position(),
+                settings.circle_subdivisions,
+                color,
+            );
+        }
+    }
+
+    if settings.joints {
+        for (_, joint) in physics_engine.impulse_joints.iter() {
+            let (body1, body2) = match (
+                physics_engine.bodies.get(joint.body1),
+                physics_engine.bodies.get(joint.body2),
+            ) {
+                (Some(body1), Some(body2)) => (body1, body2),
+                _ => continue,
+            };
+
+            let anchor1 = body1.position() * joint.data.local_anchor1();
+            let anchor2 = body2.position() * joint.data.local_anchor2();
+            let center1 = body1.translation();
+            let center2 = body2.translation();
+
+            push_line(&mut lines, *center1, anchor1.coords, settings.joint_color);
+            push_line(
+                &mut lines,
+                anchor1.coords,
+                anchor2.coords,
+                settings.joint_color,
+            );
+            push_line(&mut lines, anchor2.coords, *center2, settings.joint_color);
+            push_cross(&mut lines, anchor1.coords, 3.0, settings.joint_color);
+            push_cross(&mut lines, anchor2.coords, 3.0, settings.joint_color);
+        }
+    }
+
+    if settings.contacts {
+        for pair in physics_engine.narrow_phase.contact_pairs() {
+            if !pair.has_any_active_contact {
+                continue;
+            }
+
+            for manifold in &pair.manifolds {
+                let normal = manifold.data.normal;
+
+                for contact in &manifold.data.solver_contacts {
+                    let point = contact.point.coords;
+                    push_cross(&mut lines, point, 2.0, settings.contact_color);
+                    push_line(
+                        &mut lines,
+                        point,
+                        point + normal * settings.contact_normal_length,
+                        settings.contact_color,
+                    );
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+fn collider_color(
+    physics_engine: &PhysicsEngine,
+    collider: &Collider,
+    settings: &PhysicsDebugSettings,
+) -> Color {
+    if collider.is_sensor() {
+        return settings.sensor_color;
+    }
+
+    if !settings.body_states {
+        return settings.collider_color;
+    }
+
+    match collider
+        .parent()
+        .and_then(|parent| physics_engine.bodies.get(parent))
+    {
+        Some(body) if body.body_type() == RigidBodyType::Fixed => settings.fixed_color,
+        Some(body) if body.is_sleeping() => settings.sleeping_color,
+        Some(_) => settings.collider_color,
+        None => settings.fixed_color,
+    }
+}
+
+/// The edges outlining a shape at the given position, in world space.
+pub(crate) fn shape_edges(
+    shape: &dyn Shape,
+    position: &Isometry2<f32>,
+    subdivisions: u32,
+) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+    let mut lines = Vec::new();
+    push_shape(
+        &mut lines,
+        shape,
+        position,
+        subdivisions,
+        Color::new(0, 0, 0, 0),
+    );
+
+    lines
+        .into_iter()
+        .map(|line| (line.start, line.end))
+        .collect()
+}
+
+fn push_shape(
+    lines: &mut Vec<DebugLine>,
+    shape: &dyn Shape,
+    position: &Isometry2<f32>,
+    subdivisions: u32,
+    color: Color,
+) {
+    let to_world = |points: Vec<Point2<f32>>| {
+        points
+            .into_iter()
+            .map(|p| position * p)
+            .collect::<Vec<Point2<f32>>>()
+    };
+
+    match shape.as_typed_shape() {
+        TypedShape::Ball(ball) => {
+            push_loop(lines, &to_world(ball.to_polyline(subdivisions)), color)
+        }
+        TypedShape::Cuboid(cuboid) => push_loop(lines, &to_world(cuboid.to_polyline()), color),
+        TypedShape::Capsule(capsule) => {
+            push_loop(lines, &to_world(capsule.to_polyline(subdivisions)), color)
+        }
+        TypedShape::ConvexPolygon(polygon) => {
+            push_loop(lines, &to_world(polygon.points().to_vec()), color)
+        }
+        TypedShape::RoundCuboid(cuboid) => {
+            push_loop(lines, &to_world(cuboid.to_polyline(subdivisions)), color)
+        }
+        TypedShape::RoundConvexPolygon(polygon) => {
+            push_loop(lines, &to_world(polygon.to_polyline(subdivisions)), color)
+        }
+        TypedShape::Triangle(triangle) => {
+            push_loop(lines, &to_world(triangle.vertices().to_vec()), color)
+        }
+        TypedShape::Segment(segment) => {
+            push_line(
+                lines,
+                (position * segment.a).coords,
+                (position * segment.b).coords,
+                color,
+            );
+        }
+        TypedShape::Polyline(polyline) => {
+            for segment in polyline.segments() {
+                push_line(
+                    lines,
+                    (position * segment.a).coords,
+                    (position * segment.b).coords,
+                    color,
+                );
+            }
+        }
+        TypedShape::HeightField(heightfield) => {
+            for segment in heightfield.segments() {
+                push_line(
+                    lines,
+                    (position * segment.a).coords,
+                    (position * segment.b).coords,
+                    color,
+                );
+            }
+        }
+        TypedShape::TriMesh(trimesh) => {
+            for triangle in trimesh.triangles() {
+                push_loop(lines, &to_world(triangle.vertices().to_vec()), color);
+            }
+        }
+        TypedShape::Compound(compound) => {
+            for (shape_position, shape) in compound.shapes() {
+                push_shape(
+                    lines,
+                    &**shape,
+                    &(position * shape_position),
+                    subdivisions,
+                    color,
+                );
+            }
+        }
+        _ => {
+            // Fall back to the bounding box for shapes without a known outline.
+            let aabb = shape.compute_aabb(position);
+            push_loop(
+                lines,
+                &[
+                    Point2::new(aabb.mins.x, aabb.mins.y),
+                    Point2::new(aabb.maxs.x, aabb.mins.y),
+                    Point2::new(aabb.maxs.x, aabb.maxs.y),
+                    Point2::new(aabb.mins.x, aabb.maxs.y),
+                ],
+                color,
+            );
+        }
+    }
+}
+
+fn push_line(lines: &mut Vec<DebugLine>, start: Vector2<f32>, end: Vector2<f32>, color: Color) {
+    lines.push(DebugLine { start, end, color });
+}
+
+fn push_loop(lines: &mut Vec<DebugLine>, points: &[Point2<f32>], color: Color) {
+    for i in 0..points.len() {
+        let next = (i + 1) % points.len();
+        push_line(lines, points[i].coords, points[next].coords, color);
+    }
+}
+
+fn push_cross(lines: &mut Vec<DebugLine>, center: Vector2<f32>, size: f32, color: Color) {
+    push_line(
+        lines,
+        center - Vector2::new(size, size),
+        center + Vector2::new(size, size),
+        color,
+    );
+    push_line(
+        lines,
+        center - Vector2::new(size, -size),
+        center + Vector2::new(size, -size),
+        color,
+    );
+}
+
+/// Returns the two counter-clockwise triangles making up a line of the given width.
+pub(crate) fn line_triangles(
+    start: Vector2<f32>,
+    end: Vector2<f32>,
+    width: f32,
+) -> Option<[[Vector2<f32>; 3]; 2]> {
+    let direction = end - start;
+    let length = direction.norm();
+    if length <= f32::EPSILON {
+        return None;
+    }
+
+    let normal = Vector2::new(-direction.y, direction.x) / length * (width / 2.0);
+
+    Some([
+        [start - normal, end - normal, end + normal],
+        [start - normal, end + normal, start + normal],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier2d::{
+        na::Vector2,
+        prelude::{ColliderBuilder, RigidBodyBuilder},
+    };
+
+    use super::{collect_debug_lines, line_triangles, PhysicsDebugSettings};
+    use crate::{PhysicsEngine, Transform};
+
+    #[test]
+    fn line_triangles_are_counter_clockwise() {
+        let triangles =
+            line_triangles(Vector2::new(0.0, 0.0), Vector2::new(0.0, 10.0), 2.0).unwrap();
+
+        for [a, b, c] in triangles {
+            let signed_area = (b - a).perp(&(c - a));
+            assert!(signed_area > 0.0);
+        }
+
+        assert!(line_triangles(Vector2::new(1.0, 1.0), Vector2::new(1.0, 1.0), 2.0).is_none());
+    }
+
+    #[test]
+    fn collects_categories_separately() {
+        let mut physics_engine = PhysicsEngine::new();
+        let mut world = hecs::World::new();
+        let entity = world.spawn((Transform::default(),));
+        let body = physics_engine
+            .build_body(entity, RigidBodyBuilder::fixed(), &mut world)
+            .unwrap();
+        physics_engine.build_collider(body, ColliderBuilder::cuboid(5.0, 5.0));
+        physics_engine.build_collider(body, ColliderBuilder::ball(5.0).sensor(true));
+
+        let settings = PhysicsDebugSettings {
+            shapes: true,
+            sensors: false,
+            circle_subdivisions: 8,
+            ..Default::default()
+        };
+        assert_eq!(collect_debug_lines(&physics_engine, &settings).len(), 4);
+
+        let settings = PhysicsDebugSettings {
+            shapes: false,
+            sensors: true,
+            circle_subdivisions: 8,
+            ..Default::default()
+        };
+        let lines = collect_debug_lines(&physics_engine, &settings);
+        assert!(!lines.is_empty());
+        assert!(lines.iter().all(|line| line.color == settings.sensor_color));
+    }
+}
@@ -0,0 +1,685 @@
+use wgpu::{BindGroup, BindGroupLayout, RenderPipeline};
+
+use crate::{
+    asset_key::AssetId,
+    material::{create_uniform_bind_group, create_uniform_layout, uniform_bytes},
+    rendering_engine::{BindGroupLayoutId, BindGroupLayouts},
+    texture::{Texture, TextureKey},
+    AssetEngine, Color, EmeraldError, BLACK, WHITE,
+};
+
+const PRELUDE: &str = include_str!("./shaders/post_process/prelude.wgsl");
+
+/// A full-screen pass over the final frame, see `RenderingHandler::add_post_process`.
+#[derive(Clone, Debug)]
+pub enum PostProcessPass {
+    ColorGrading(ColorGrading),
+    Vignette(Vignette),
+    Crt(Crt),
+    Bloom(Bloom),
+    ColorBlindness(ColorBlindness),
+    Custom(CustomPostProcess),
+}
+impl PostProcessPass {
+    fn label(&self) -> &str {
+        match self {
+            PostProcessPass::ColorGrading(_) => "emd_color_grading",
+            PostProcessPass::Vignette(_) => "emd_vignette",
+            PostProcessPass::Crt(_) => "emd_crt",
+            PostProcessPass::Bloom(_) => "emd_bloom",
+            PostProcessPass::ColorBlindness(_) => "emd_color_blindness",
+            PostProcessPass::Custom(custom) => &custom.label,
+        }
+    }
+
+    fn source(&self) -> &str {
+        match self {
+            PostProcessPass::ColorGrading(_) => {
+                include_str!("./shaders/post_process/color_grading.wgsl")
+            }
+            PostProcessPass::Vignette(_) => include_str!("./shaders/post_process/vignette.wgsl"),
+            PostProcessPass::Crt(_) => include_str!("./shaders/post_process/crt.wgsl"),
+            PostProcessPass::Bloom(_) => include_str!("./shaders/post_process/bloom.wgsl"),
+            PostProcessPass::ColorBlindness(_) => {
+                include_str!("./shaders/post_process/color_blindness.wgsl")
+            }
+            PostProcessPass::Custom(custom) => &custom.source,
+        }
+    }
+
+    /// The uniform block of the pass, starting with the size of the screen.
+    fn uniforms(&self, screen_size: (u32, u32)) -> Vec<f32> {
+        let (width, height) = (screen_size.0 as f32, screen_size.1 as f32);
+        let mut uniforms = vec![width, height, 1.0 / width, 1.0 / height];
+
+        match self {
+            PostProcessPass::ColorGrading(grading) => {
+                let lut_intensity = if grading.lut.is_some() {
+                    grading.lut_intensity
+                } else {
+                    0.0
+                };
+                uniforms.extend([
+                    grading.exposure,
+                    grading.contrast,
+                    grading.saturation,
+                    lut_intensity,
+                ]);
+                uniforms.extend(to_linear(&grading.tint));
+                uniforms.extend([grading.lut_size as f32, 0.0, 0.0, 0.0]);
+            }
+            PostProcessPass::Vignette(vignette) => {
+                uniforms.extend([vignette.intensity, vignette.radius, vignette.softness, 0.0]);
+                uniforms.extend(to_linear(&vignette.color));
+            }
+            PostProcessPass::Crt(crt) => {
+                let scanline_count = if crt.scanline_count > 0.0 {
+                    crt.scanline_count
+                } else {
+                    height / 2.0
+                };
+                uniforms.extend([
+                    crt.scanline_intensity,
+                    scanline_count,
+                    crt.curvature,
+                    crt.chromatic_aberration,
+                ]);
+            }
+            PostProcessPass::Bloom(bloom) => {
+                uniforms.extend([bloom.threshold, bloom.intensity, bloom.radius, 0.0]);
+            }
+            PostProcessPass::ColorBlindness(color_blindness) => {
+                let [red, green, blue] = color_blindness.mode.matrix();
+                uniforms.extend(red);
+                uniforms.push(color_blindness.strength);
+                uniforms.extend(green);
+                uniforms.push(0.0);
+                uniforms.extend(blue);
+                uniforms.push(0.0);
+            }
+            PostProcessPass::Custom(custom) => uniforms.extend(&custom.uniforms),
+        }
+
+        uniforms
+    }
+
+    /// The extra textures bound by the pass, `None` binds a blank texture.
+    fn textures(&self) -> Vec<Option<&TextureKey>> {
+        match self {
+            PostProcessPass::ColorGrading(grading) => vec![grading.lut.as_ref()],
+            PostProcessPass::Custom(custom) => custom.textures.iter().map(Some).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Adjusts the colors of the frame, optionally through a lookup table.
+#[derive(Clone, Debug)]
+pub struct ColorGrading {
+    /// Multiplier applied to the colors before anything else.
+    pub exposure: f32,
+    pub contrast: f32,
+    /// 0.0 is grayscale, 1.0 leaves the colors unchanged.
+    pub saturation: f32,
+    pub tint: Color,
+    /// A strip of `lut_size` square slices, each `lut_size` pixels wide.
+    /// Red increases to the right within a slice, green increases downwards,
+    /// and blue increases from one slice to the next.
+    pub lut: Option<TextureKey>,
+    pub lut_size: u32,
+    /// How much of the lookup table's colors to use, from 0.0 to 1.0.
+    pub lut_intensity: f32,
+}
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            tint: WHITE,
+            lut: None,
+            lut_size: 16,
+            lut_intensity: 1.0,
+        }
+    }
+}
+
+/// Fades the edges of the frame into a color.
+#[derive(Clone, Debug)]
+pub struct Vignette {
+    pub intensity: f32,
+    /// Distance from the center where the fade begins, relative to the height of the screen.
+    pub radius: f32,
+    /// Distance over which the fade reaches its full intensity, relative to the height of the screen.
+    pub softness: f32,
+    pub color: Color,
+}
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            intensity: 0.5,
+            radius: 0.4,
+            softness: 0.4,
+            color: BLACK,
+        }
+    }
+}
+
+/// Imitates a CRT monitor with scanlines, a curved screen and color fringing.
+#[derive(Clone, Debug)]
+pub struct Crt {
+    /// How dark the gaps between scanlines are, from 0.0 to 1.0.
+    pub scanline_intensity: f32,
+    /// Number of scanlines from the top to the bottom of the screen.
+    /// 0.0 or less uses one scanline every 2 pixels.
+    pub scanline_count: f32,
+    pub curvature: f32,
+    /// Offset of the red and blue channels, in pixels.
+    pub chromatic_aberration: f32,
+}
+impl Default for Crt {
+    fn default() -> Self {
+        Self {
+            scanline_intensity: 0.3,
+            scanline_count: 0.0,
+            curvature: 0.1,
+            chromatic_aberration: 1.0,
+        }
+    }
+}
+
+/// Makes the bright parts of the frame glow.
+#[derive(Clone, Debug)]
+pub struct Bloom {
+    /// Brightness from 0.0 to 1.0 that a color needs to exceed to glow.
+    pub threshold: f32,
+    pub intensity: f32,
+    /// How far the glow spreads, in pixels.
+    pub radius: f32,
+}
+impl Default for Bloom {
+    fn default() -> Self {
+        Self {
+            threshold: 0.7,
+            intensity: 1.0,
+            radius: 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorBlindnessMode {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+impl ColorBlindnessMode {
+    /// The rows of the matrix simulating the color blindness in linear rgb.
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindnessMode::Protanopia => [
+                [0.567, 0.433, 0.0],
+                [0.558, 0.442, 0.0],
+                [0.0, 0.242, 0.758],
+            ],
+            ColorBlindnessMode::Deuteranopia => {
+                [[0.625, 0.375, 0.0], [0.7, 0.3, 0.0], [0.0, 0.3, 0.7]]
+            }
+            ColorBlindnessMode::Tritanopia => {
+                [[0.95, 0.05, 0.0], [0.0, 0.433, 0.567], [0.0, 0.475, 0.525]]
+            }
+            ColorBlindnessMode::Achromatopsia => [[0.299, 0.587, 0.114]; 3],
+        }
+    }
+}
+
+/// Simulates a color blindness, to check that the game is readable without some colors.
+#[derive(Clone, Debug)]
+pub struct ColorBlindness {
+    pub mode: ColorBlindnessMode,
+    /// How much of the simulated colors to use, from 0.0 to 1.0.
+    pub strength: f32,
+}
+impl ColorBlindness {
+    pub fn new(mode: ColorBlindnessMode) -> Self {
+        Self {
+            mode,
+            strength: 1.0,
+        }
+    }
+}
+
+/// A pass with a user defined WGSL fragment shader.
+/// The source is appended to the post-processing prelude, and must define
+/// `fs_main(in: VertexOutput) -> @location(0) vec4<f32>`, sampling the frame through `t_screen` and `s_screen`.
+/// The uniform block at group 1, binding 0 starts with a `vec4<f32>` of the width, height,
+/// 1 / width and 1 / height of the screen, followed by the given uniforms.
+/// The textures are bound after it, like the textures of a material.
+#[derive(Clone, Debug)]
+pub struct CustomPostProcess {
+    pub label: String,
+    pub source: String,
+    pub uniforms: Vec<f32>,
+    pub textures: Vec<TextureKey>,
+}
+impl CustomPostProcess {
+    pub fn new<L: Into<String>, S: Into<String>>(label: L, source: S) -> Self {
+        Self {
+            label: label.into(),
+            source: source.into(),
+            uniforms: Vec::new(),
+            textures: Vec::new(),
+        }
+    }
+}
+
+fn to_linear(color: &Color) -> [f32; 4] {
+    let (r, g, b, a) = color.to_percentage_linear();
+    [r as f32, g as f32, b as f32, a as f32]
+}
+
+/// The parts of the rendering engine needed to run the post-processing passes.
+pub(crate) struct PostProcessContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub format: wgpu::TextureFormat,
+    pub bind_group_layouts: &'a BindGroupLayouts,
+    /// Bound in place of missing textures.
+    pub blank_texture: &'a TextureKey,
+}
+
+struct PassResources {
+    source: String,
+    uniform_size: u64,
+    bound_textures: Vec<AssetId>,
+    layout: BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+impl PassResources {
+    fn new(
+        pass: &PostProcessPass,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &AssetEngine,
+        uniforms: &[u8],
+        texture_ids: Vec<AssetId>,
+    ) -> Result<Self, EmeraldError> {
+        let label = pass.label();
+        let texture_bind_group_layout = ctx
+            .bind_group_layouts
+            .get(&BindGroupLayoutId::TextureQuad)
+            .ok_or_else(|| EmeraldError::new("Unable to get TextureQuad bind group layout"))?;
+        let textures = get_textures(asset_engine, &texture_ids)?;
+
+        // Invalid shaders are reported through the error scope, rather than panicking.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let layout = create_uniform_layout(ctx.device, label, textures.len() as u32);
+        let uniform_buffer = wgpu::util::DeviceExt::create_buffer_init(
+            ctx.device,
+            &wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{}_post_process_uniforms", label)),
+                contents: uniforms,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+        let bind_group =
+            create_uniform_bind_group(ctx.device, label, &layout, &uniform_buffer, &textures);
+        let pipeline = create_post_process_pipeline(
+            ctx.device,
+            ctx.format,
+            label,
+            pass.source(),
+            &[texture_bind_group_layout, &layout],
+        );
+
+        if let Some(error) = pollster::block_on(ctx.device.pop_error_scope()) {
+            return Err(EmeraldError::new(format!(
+                "Unable to create post-processing pass {:?}. {}",
+                label, error
+            )));
+        }
+
+        Ok(Self {
+            source: pass.source().to_string(),
+            uniform_size: uniforms.len() as u64,
+            bound_textures: texture_ids,
+            layout,
+            uniform_buffer,
+            bind_group,
+            pipeline,
+        })
+    }
+}
+
+fn get_textures<'a>(
+    asset_engine: &'a AssetEngine,
+    texture_ids: &[AssetId],
+) -> Result<Vec<&'a Texture>, EmeraldError> {
+    texture_ids
+        .iter()
+        .map(|id| {
+            asset_engine.get_asset::<Texture>(id).ok_or_else(|| {
+                EmeraldError::new(format!("Unable to find Texture for AssetId {:?}", id))
+            })
+        })
+        .collect()
+}
+
+struct PostProcessEntry {
+    pass: PostProcessPass,
+    enabled: bool,
+    resources: PassResources,
+}
+impl PostProcessEntry {
+    /// Rebuilds the resources of the pass if it has changed, then uploads its uniforms.
+    fn prepare(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &AssetEngine,
+        screen_size: (u32, u32),
+    ) -> Result<(), EmeraldError> {
+        let uniforms = uniform_bytes(&self.pass.uniforms(screen_size));
+        let texture_ids = get_texture_ids(&self.pass, ctx.blank_texture);
+
+        if self.resources.source != self.pass.source()
+            || self.resources.uniform_size != uniforms.len() as u64
+            || self.resources.bound_textures.len() != texture_ids.len()
+        {
+            self.resources =
+                PassResources::new(&self.pass, ctx, asset_engine, &uniforms, texture_ids)?;
+        } else if self.resources.bound_textures != texture_ids {
+            let textures = get_textures(asset_engine, &texture_ids)?;
+            self.resources.bind_group = create_uniform_bind_group(
+                ctx.device,
+                self.pass.label(),
+                &self.resources.layout,
+                &self.resources.uniform_buffer,
+                &textures,
+            );
+            self.resources.bound_textures = texture_ids;
+        }
+
+        ctx.queue
+            .write_buffer(&self.resources.uniform_buffer, 0, &uniforms);
+
+        Ok(())
+    }
+}
+
+fn get_texture_ids(pass: &PostProcessPass, blank_texture: &TextureKey) -> Vec<AssetId> {
+    pass.textures()
+        .into_iter()
+        .map(|texture| texture.unwrap_or(blank_texture).asset_key.asset_id)
+        .collect()
+}
+
+/// An ordered list of passes applied to the final frame.
+/// The frame is drawn into an offscreen target, then passed from one pass to the next.
+#[derive(Default)]
+pub(crate) struct PostProcessStack {
+    entries: Vec<PostProcessEntry>,
+    /// The two targets passes read from and write to in turn, matching the size of the screen.
+    targets: Vec<TextureKey>,
+    /// Suffix of the next ping-pong target's label, the old pair stays loaded until its keys are dropped.
+    target_uid: usize,
+}
+impl PostProcessStack {
+    pub fn push(
+        &mut self,
+        pass: PostProcessPass,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &AssetEngine,
+    ) -> Result<usize, EmeraldError> {
+        // The screen size only changes the values of the uniforms, not their size.
+        let uniforms = uniform_bytes(&pass.uniforms((1, 1)));
+        let texture_ids = get_texture_ids(&pass, ctx.blank_texture);
+        let resources = PassResources::new(&pass, ctx, asset_engine, &uniforms, texture_ids)?;
+
+        self.entries.push(PostProcessEntry {
+            pass,
+            enabled: true,
+            resources,
+        });
+
+        Ok(self.entries.len() - 1)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&PostProcessPass> {
+        self.entries.get(index).map(|entry| &entry.pass)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut PostProcessPass> {
+        self.entries.get_mut(index).map(|entry| &mut entry.pass)
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) -> Result<(), EmeraldError> {
+        match self.entries.get_mut(index) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                Ok(())
+            }
+            None => Err(EmeraldError::new(format!(
+                "There is no post-processing pass at index {}",
+                index
+            ))),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<PostProcessPass, EmeraldError> {
+        if index >= self.entries.len() {
+            return Err(EmeraldError::new(format!(
+                "There is no post-processing pass at index {}",
+                index
+            )));
+        }
+
+        Ok(self.entries.remove(index).pass)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.targets.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.entries.iter().any(|entry| entry.enabled)
+    }
+
+    /// Returns a view of the target the frame should be drawn into,
+    /// recreating the targets if the size of the screen has changed.
+    pub fn prepare_targets(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &mut AssetEngine,
+        screen_size: (u32, u32),
+    ) -> Result<wgpu::TextureView, EmeraldError> {
+        if self
+            .targets
+            .first()
+            .filter(|target| target.size() == screen_size)
+            .is_none()
+        {
+            self.targets.clear();
+            for _ in 0..2 {
+                let label = format!("emd_post_process_target_{}", self.target_uid);
+                self.targets
+                    .push(create_target(ctx, asset_engine, &label, screen_size)?);
+                self.target_uid += 1;
+            }
+        }
+
+        target_view(asset_engine, &self.targets[0])
+    }
+
+    /// Runs the enabled passes over the frame drawn into the first target, writing the result to the output.
+    pub fn apply(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &AssetEngine,
+        screen_size: (u32, u32),
+        output: &wgpu::TextureView,
+    ) -> Result<(), EmeraldError> {
+        for entry in self.entries.iter_mut().filter(|entry| entry.enabled) {
+            entry.prepare(ctx, asset_engine, screen_size)?;
+        }
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Post Process Encoder"),
+            });
+
+        let passes: Vec<&PostProcessEntry> =
+            self.entries.iter().filter(|entry| entry.enabled).collect();
+        let mut input = 0;
+        for (i, entry) in passes.iter().enumerate() {
+            let input_bind_group = asset_engine
+                .get_asset::<BindGroup>(&self.targets[input].bind_group_key.asset_id)
+                .ok_or_else(|| EmeraldError::new("Unable to find post-processing target"))?;
+
+            let target_output;
+            let output = if i + 1 == passes.len() {
+                output
+            } else {
+                target_output = target_view(asset_engine, &self.targets[1 - input])?;
+                &target_output
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&format!("Post Process Pass {:?}", entry.pass.label())),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: output,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&entry.resources.pipeline);
+            render_pass.set_bind_group(0, input_bind_group, &[]);
+            render_pass.set_bind_group(1, &entry.resources.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+
+            input = 1 - input;
+        }
+
+        ctx.queue.submit([encoder.finish()]);
+
+        Ok(())
+    }
+}
+
+/// Creates a render target matching the format of the screen.
+pub(crate) fn create_target(
+    ctx: &PostProcessContext<'_>,
+    asset_engine: &mut AssetEngine,
+    label: &str,
+    size: (u32, u32),
+) -> Result<TextureKey, EmeraldError> {
+    let data = vec![0; (size.0 * size.1 * 4) as usize];
+    Texture::new_render_target(
+        label,
+        ctx.bind_group_layouts,
+        asset_engine,
+        ctx.device,
+        ctx.queue,
+        size.0,
+        size.1,
+        &data,
+        ctx.format,
+    )
+}
+
+pub(crate) fn target_view(
+    asset_engine: &AssetEngine,
+    target: &TextureKey,
+) -> Result<wgpu::TextureView, EmeraldError> {
+    asset_engine
+        .get_asset::<Texture>(&target.asset_key.asset_id)
+        .map(|texture| {
+            texture
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        })
+        .ok_or_else(|| EmeraldError::new("Unable to find post-processing target"))
+}
+
+/// Creates a pipeline drawing a single triangle over the whole target, see the post-processing prelude.
+pub(crate) fn create_post_process_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    label: &str,
+    fragment_source: &str,
+    bind_group_layouts: &[&BindGroupLayout],
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(&format!("{} Shader", label)),
+        source: wgpu::ShaderSource::Wgsl(format!("{}\n{}", PRELUDE, fragment_source).into()),
+    });
+
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(&format!("{} Pipeline Layout", label)),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(&format!("{} Pipeline", label)),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bloom, ColorBlindness, ColorBlindnessMode, Crt, PostProcessPass};
+
+    #[test]
+    fn uniforms_start_with_the_screen_size() {
+        let uniforms = PostProcessPass::Bloom(Bloom::default()).uniforms((200, 100));
+        assert_eq!(&uniforms[..4], &[200.0, 100.0, 0.005, 0.01]);
+        assert_eq!(uniforms.len(), 8);
+    }
+
+    #[test]
+    fn crt_scanlines_default_to_every_other_pixel() {
+        let uniforms = PostProcessPass::Crt(Crt::default()).uniforms((200, 100));
+        assert_eq!(uniforms[5], 50.0);
+    }
+
+    #[test]
+    fn color_blindness_strength_is_packed_after_the_first_row() {
+        let mut color_blindness = ColorBlindness::new(ColorBlindnessMode::Achromatopsia);
+        color_blindness.strength = 0.5;
+        let uniforms = PostProcessPass::ColorBlindness(color_blindness).uniforms((1, 1));
+        assert_eq!(uniforms.len(), 16);
+        assert_eq!(uniforms[7], 0.5);
+        assert_eq!(&uniforms[8..11], &[0.299, 0.587, 0.114]);
+    }
+}
@@ -0,0 +1,191 @@
+use std::num::NonZeroU32;
+
+use image::{png::PngEncoder, ColorType};
+
+use crate::EmeraldError;
+
+/// Pixels read back from the gpu, as rows of RGBA bytes from the top left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+impl CapturedImage {
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let i = ((y * self.width + x) * 4) as usize;
+        Some([
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ])
+    }
+
+    /// Encodes the image as a png file.
+    pub fn to_png(&self) -> Result<Vec<u8>, EmeraldError> {
+        let mut bytes = Vec::new();
+        PngEncoder::new(&mut bytes).encode(
+            &self.data,
+            self.width,
+            self.height,
+            ColorType::Rgba8,
+        )?;
+
+        Ok(bytes)
+    }
+}
+
+/// Copies the texture into a buffer and waits for the gpu to map it.
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+) -> Result<CapturedImage, EmeraldError> {
+    let swizzle = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        _ => {
+            return Err(EmeraldError::new(format!(
+                "Unable to read back a texture of format {:?}.",
+                format
+            )))
+        }
+    };
+
+    let (width, height) = (size.width, size.height);
+    let padded_row = padded_bytes_per_row(width);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: (padded_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_row),
+                rows_per_image: NonZeroU32::new(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).ok();
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    match receiver.try_recv() {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            return Err(EmeraldError::new(format!(
+                "Unable to map the readback buffer. {:?}",
+                e
+            )))
+        }
+        Err(_) => {
+            return Err(EmeraldError::new(
+                "The readback buffer was not mapped, the gpu did not finish the copy.",
+            ))
+        }
+    }
+
+    let mut data = unpad_rows(&slice.get_mapped_range(), width, height);
+    buffer.unmap();
+
+    if swizzle {
+        bgra_to_rgba(&mut data);
+    }
+
+    Ok(CapturedImage {
+        width,
+        height,
+        data,
+    })
+}
+
+/// Rows copied out of a texture must be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let unpadded = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    unpadded.next_multiple_of(align)
+}
+
+fn unpad_rows(padded: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let row = (width * 4) as usize;
+    let padded_row = padded_bytes_per_row(width) as usize;
+
+    padded
+        .chunks(padded_row)
+        .take(height as usize)
+        .flat_map(|chunk| chunk[0..row].iter().copied())
+        .collect()
+}
+
+fn bgra_to_rgba(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpads_rows_to_tightly_packed_pixels() {
+        assert_eq!(padded_bytes_per_row(3), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+
+        let mut padded = vec![0; 512];
+        padded[0..12].copy_from_slice(&[1; 12]);
+        padded[256..268].copy_from_slice(&[2; 12]);
+        let data = unpad_rows(&padded, 3, 2);
+
+        assert_eq!(data.len(), 24);
+        assert!(data[0..12].iter().all(|b| *b == 1));
+        assert!(data[12..24].iter().all(|b| *b == 2));
+    }
+
+    #[test]
+    fn swizzles_bgra_and_reads_pixels() {
+        let mut data = vec![10, 20, 30, 255, 1, 2, 3, 4];
+        bgra_to_rgba(&mut data);
+        let image = CapturedImage {
+            width: 2,
+            height: 1,
+            data,
+        };
+
+        assert_eq!(image.pixel(0, 0), Some([30, 20, 10, 255]));
+        assert_eq!(image.pixel(1, 0), Some([3, 2, 1, 4]));
+        assert_eq!(image.pixel(2, 0), None);
+        assert!(image
+            .to_png()
+            .unwrap()
+            .starts_with(&[0x89, b'P', b'N', b'G']));
+    }
+}
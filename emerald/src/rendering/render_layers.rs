@@ -0,0 +1,216 @@
+use hecs::Entity;
+
+use crate::{Translation, World};
+
+use super::components::{
+    get_active_camera_view, get_camera_view, screen_to_world_through_view,
+    world_to_screen_through_view, ViewTransform,
+};
+
+/// The layer drawn through the active camera, that entities without a `RenderLayer` are drawn on.
+pub const DEFAULT_RENDER_LAYER: &str = "default";
+
+/// Places the drawables of an entity on a render layer of its world, see `RenderLayers`.
+/// Entities without one, or whose layer doesn't exist, are drawn on the default layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderLayer {
+    pub name: String,
+}
+impl RenderLayer {
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+/// How a layer's translations are mapped onto the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerProjection {
+    /// Drawn through the world's active camera, or the camera of the viewport being drawn.
+    ActiveCamera,
+    /// Drawn through the `Camera` and `Transform` of the given entity.
+    Camera(Entity),
+    /// Drawn without a camera, translations are in pixels from the center of the screen with y pointing up.
+    /// Used for HUDs and menus that shouldn't move with the camera.
+    Screen,
+}
+
+/// The order drawables within a layer are drawn in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerSortMode {
+    /// By z_index, lowest first.
+    ZIndex,
+    /// By z_index, then from the highest translation to the lowest,
+    /// so things further down the screen are drawn in front. Used for top-down games.
+    YSort,
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderLayerDef {
+    pub name: String,
+    pub projection: LayerProjection,
+    pub sort_mode: LayerSortMode,
+    pub visible: bool,
+}
+impl RenderLayerDef {
+    pub fn new<T: Into<String>>(name: T, projection: LayerProjection) -> Self {
+        Self {
+            name: name.into(),
+            projection,
+            sort_mode: LayerSortMode::ZIndex,
+            visible: true,
+        }
+    }
+
+    /// A layer drawn in screen space.
+    pub fn screen<T: Into<String>>(name: T) -> Self {
+        Self::new(name, LayerProjection::Screen)
+    }
+
+    /// Converts a position on a screen of the given size, in pixels from its top left corner,
+    /// into the translation drawn there on this layer.
+    /// Returns `None` if the camera of the layer is missing.
+    pub fn screen_to_layer(
+        &self,
+        world: &World,
+        screen_size: (u32, u32),
+        screen_translation: &Translation,
+    ) -> Option<Translation> {
+        self.view(world).map(|(camera_translation, view)| {
+            screen_to_world_through_view(
+                &camera_translation,
+                &view,
+                screen_size,
+                screen_translation,
+            )
+        })
+    }
+
+    /// Converts a translation on this layer into the position it's drawn at,
+    /// in pixels from the top left corner of a screen of the given size.
+    /// Returns `None` if the camera of the layer is missing.
+    pub fn layer_to_screen(
+        &self,
+        world: &World,
+        screen_size: (u32, u32),
+        layer_translation: &Translation,
+    ) -> Option<Translation> {
+        self.view(world).map(|(camera_translation, view)| {
+            world_to_screen_through_view(&camera_translation, &view, screen_size, layer_translation)
+        })
+    }
+
+    fn view(&self, world: &World) -> Option<(Translation, ViewTransform)> {
+        match self.projection {
+            LayerProjection::ActiveCamera => Some(get_active_camera_view(world)),
+            LayerProjection::Camera(entity) => get_camera_view(world, entity),
+            LayerProjection::Screen => Some(Default::default()),
+        }
+    }
+}
+
+/// The render layers of a world, drawn in order from first to last.
+/// Starts with only the default layer.
+#[derive(Clone, Debug)]
+pub struct RenderLayers {
+    layers: Vec<RenderLayerDef>,
+}
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self {
+            layers: vec![RenderLayerDef::new(
+                DEFAULT_RENDER_LAYER,
+                LayerProjection::ActiveCamera,
+            )],
+        }
+    }
+}
+impl RenderLayers {
+    /// Adds a layer drawn above the others, replacing any layer with the same name.
+    pub fn push(&mut self, layer: RenderLayerDef) {
+        self.remove(&layer.name);
+        self.layers.push(layer);
+    }
+
+    /// Inserts a layer at the given position in the draw order, replacing any layer with the same name.
+    /// Positions past the end add the layer above the others.
+    pub fn insert(&mut self, index: usize, layer: RenderLayerDef) {
+        self.remove(&layer.name);
+        let index = index.min(self.layers.len());
+        self.layers.insert(index, layer);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<RenderLayerDef> {
+        self.index_of(name).map(|index| self.layers.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RenderLayerDef> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut RenderLayerDef> {
+        self.layers.iter_mut().find(|layer| layer.name == name)
+    }
+
+    /// Position of the layer in the draw order.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RenderLayerDef> {
+        self.layers.iter()
+    }
+
+    /// Returns the index of the layer an entity with the given `RenderLayer` is drawn on,
+    /// or `None` if it would be drawn on the default layer and there isn't one.
+    pub fn resolve(&self, render_layer: Option<&RenderLayer>) -> Option<usize> {
+        render_layer
+            .and_then(|render_layer| self.index_of(&render_layer.name))
+            .or_else(|| self.index_of(DEFAULT_RENDER_LAYER))
+    }
+
+    pub(crate) fn get_by_index(&self, index: usize) -> Option<&RenderLayerDef> {
+        self.layers.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LayerProjection, RenderLayer, RenderLayerDef, RenderLayers, DEFAULT_RENDER_LAYER};
+    use crate::{rendering::components::Camera, Transform, Translation, World};
+
+    #[test]
+    fn entities_fall_back_to_the_default_layer() {
+        let mut layers = RenderLayers::default();
+        layers.push(RenderLayerDef::screen("hud"));
+        layers.insert(0, RenderLayerDef::screen("background"));
+
+        assert_eq!(layers.resolve(Some(&RenderLayer::new("hud"))), Some(2));
+        assert_eq!(layers.resolve(Some(&RenderLayer::new("missing"))), Some(1));
+        assert_eq!(layers.resolve(None), Some(1));
+
+        layers.remove(DEFAULT_RENDER_LAYER);
+        assert_eq!(layers.resolve(None), None);
+    }
+
+    #[test]
+    fn screen_layers_ignore_the_camera() {
+        let mut world = World::new();
+        let camera = world.spawn((
+            Transform::from_translation((500.0, 500.0)),
+            Camera::default(),
+        ));
+        world.make_active_camera(camera).unwrap();
+        let screen_size = (200, 100);
+        let point = Translation::new(150.0, 25.0);
+
+        let hud = RenderLayerDef::screen("hud");
+        let translation = hud.screen_to_layer(&world, screen_size, &point).unwrap();
+        assert_eq!((translation.x, translation.y), (50.0, 25.0));
+
+        let world_layer = RenderLayerDef::new("world", LayerProjection::ActiveCamera);
+        let translation = world_layer
+            .screen_to_layer(&world, screen_size, &point)
+            .unwrap();
+        assert_eq!((translation.x, translation.y), (550.0, 525.0));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::*;
+use crate::{texture_atlas::TextureAtlasSettings, *};
 
 #[derive(Clone, Debug)]
 pub struct RenderSettings {
@@ -13,6 +13,17 @@ pub struct RenderSettings {
     // Whether or not the game engine should automatically cull sprites that are not in camera view
     pub frustrum_culling: bool,
     pub pixel_snap: bool,
+    /// How the game is fit into the window, see `ScalingMode`.
+    pub scaling_mode: ScalingMode,
+    /// How textures are packed into shared pages, see `RenderingHandler::pack_textures`.
+    pub texture_atlas: TextureAtlasSettings,
+    /// Batches of at least this many quads sharing a texture are drawn instanced, from a single quad.
+    /// Smaller batches, and batches drawn with a material, are drawn from vertices.
+    /// `None` draws every batch from vertices.
+    pub instancing_threshold: Option<usize>,
+    /// Requests a software adapter, so frames can be captured without a gpu.
+    /// A software adapter is also used when no other adapter is found.
+    pub force_fallback_adapter: bool,
 }
 impl Default for RenderSettings {
     fn default() -> RenderSettings {
@@ -25,6 +36,177 @@ impl Default for RenderSettings {
             resizable_window: true,
             frustrum_culling: true,
             pixel_snap: true,
+            scaling_mode: ScalingMode::Window,
+            texture_atlas: TextureAtlasSettings::default(),
+            instancing_threshold: Some(256),
+            force_fallback_adapter: false,
         }
     }
 }
+
+/// How the game is fit into the window.
+/// Every mode other than `Window` draws the game at a fixed virtual resolution,
+/// `screen_size()` and mouse positions are then given in virtual pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// The game is drawn at the size of the window.
+    Window,
+    /// Scaled up by the largest whole number that fits the window, with black bars around it.
+    /// Keeps pixel art crisp, at the cost of some unused space.
+    IntegerLetterbox { width: u32, height: u32 },
+    /// Stretched over the whole window, ignoring its aspect ratio.
+    Stretch { width: u32, height: u32 },
+    /// Scaled to fit the window while keeping its aspect ratio, then widened or heightened
+    /// to fill the rest of the window. The screen size grows along one axis instead of leaving bars.
+    Expand { width: u32, height: u32 },
+}
+impl ScalingMode {
+    /// Returns where the virtual screen is drawn in a window of the given size,
+    /// or `None` if the game is drawn at the size of the window.
+    pub fn scaling(&self, window_size: (u32, u32)) -> Option<ScreenScaling> {
+        let (window_width, window_height) = (window_size.0 as f32, window_size.1 as f32);
+        let window_area = Rectangle::new(0.0, 0.0, window_width, window_height);
+
+        let scaling = match *self {
+            ScalingMode::Window => return None,
+            ScalingMode::IntegerLetterbox { width, height } => {
+                let fit = fit_scale((width, height), window_size);
+                // Windows smaller than the virtual resolution fall back to a fractional scale,
+                // which drops rows and columns of pixels since the screen is sampled with nearest filtering.
+                let scale = if fit >= 1.0 { fit.floor() } else { fit };
+                let (area_width, area_height) = (
+                    (width as f32 * scale).round(),
+                    (height as f32 * scale).round(),
+                );
+                ScreenScaling {
+                    screen_size: (width, height),
+                    area: Rectangle::new(
+                        ((window_width - area_width) / 2.0).floor(),
+                        ((window_height - area_height) / 2.0).floor(),
+                        area_width,
+                        area_height,
+                    ),
+                }
+            }
+            ScalingMode::Stretch { width, height } => ScreenScaling {
+                screen_size: (width, height),
+                area: window_area,
+            },
+            ScalingMode::Expand { width, height } => {
+                let scale = fit_scale((width, height), window_size);
+                let screen_size = if scale > 0.0 {
+                    (
+                        ((window_width / scale).round() as u32).max(1),
+                        ((window_height / scale).round() as u32).max(1),
+                    )
+                } else {
+                    (width, height)
+                };
+                ScreenScaling {
+                    screen_size,
+                    area: window_area,
+                }
+            }
+        };
+
+        Some(scaling)
+    }
+}
+
+/// The largest scale at which the size fits inside of the window.
+fn fit_scale(size: (u32, u32), window_size: (u32, u32)) -> f32 {
+    if size.0 == 0 || size.1 == 0 {
+        return 0.0;
+    }
+
+    (window_size.0 as f32 / size.0 as f32).min(window_size.1 as f32 / size.1 as f32)
+}
+
+/// Where the virtual screen is drawn in the window.
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenScaling {
+    /// Size of the screen the game is drawn to, in virtual pixels.
+    pub screen_size: (u32, u32),
+    /// Area of the window covered by the screen, in pixels from its top left corner.
+    pub area: Rectangle,
+}
+impl ScreenScaling {
+    /// Converts a position in the window into a position on the virtual screen,
+    /// both in pixels from their top left corner.
+    pub fn window_to_screen(&self, window_translation: &Translation) -> Translation {
+        if self.area.width <= 0.0 || self.area.height <= 0.0 {
+            return Translation::new(0.0, 0.0);
+        }
+
+        Translation::new(
+            (window_translation.x - self.area.x) * self.screen_size.0 as f32 / self.area.width,
+            (window_translation.y - self.area.y) * self.screen_size.1 as f32 / self.area.height,
+        )
+    }
+
+    /// Converts a position on the virtual screen into a position in the window.
+    pub fn screen_to_window(&self, screen_translation: &Translation) -> Translation {
+        if self.screen_size.0 == 0 || self.screen_size.1 == 0 {
+            return Translation::new(self.area.x, self.area.y);
+        }
+
+        Translation::new(
+            self.area.x + screen_translation.x * self.area.width / self.screen_size.0 as f32,
+            self.area.y + screen_translation.y * self.area.height / self.screen_size.1 as f32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScalingMode, ScreenScaling};
+    use crate::{Rectangle, Translation};
+
+    #[test]
+    fn integer_letterbox_scales_by_whole_numbers() {
+        let scaling = ScalingMode::IntegerLetterbox {
+            width: 320,
+            height: 180,
+        }
+        .scaling((1366, 768))
+        .unwrap();
+
+        assert_eq!(scaling.screen_size, (320, 180));
+        let area = scaling.area;
+        assert_eq!(
+            (area.x, area.y, area.width, area.height),
+            (43.0, 24.0, 1280.0, 720.0)
+        );
+
+        let screen = scaling.window_to_screen(&Translation::new(43.0 + 640.0, 24.0 + 360.0));
+        assert_eq!((screen.x, screen.y), (160.0, 90.0));
+        let window = scaling.screen_to_window(&screen);
+        assert_eq!((window.x, window.y), (683.0, 384.0));
+    }
+
+    #[test]
+    fn zero_sized_screens_convert_to_finite_positions() {
+        let scaling = ScreenScaling {
+            screen_size: (0, 180),
+            area: Rectangle::new(43.0, 24.0, 1280.0, 720.0),
+        };
+
+        let window = scaling.screen_to_window(&Translation::new(160.0, 90.0));
+        assert_eq!((window.x, window.y), (43.0, 24.0));
+        let screen = scaling.window_to_screen(&Translation::new(683.0, 384.0));
+        assert!(screen.x.is_finite() && screen.y.is_finite());
+    }
+
+    #[test]
+    fn expand_grows_the_screen_along_one_axis() {
+        let scaling = ScalingMode::Expand {
+            width: 320,
+            height: 180,
+        }
+        .scaling((800, 600))
+        .unwrap();
+
+        assert_eq!(scaling.screen_size, (320, 240));
+        assert!(ScalingMode::Window.scaling((800, 600)).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     any::TypeId,
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
     ops::Range,
 };
@@ -15,25 +15,105 @@ use winit::dpi::PhysicalSize;
 use crate::{
     asset_key::AssetId,
     autotilemap::AutoTilemap,
+    blend_mode::BlendMode,
     font::{Font, FontKey},
-    render_settings::RenderSettings,
+    render_settings::{RenderSettings, ScreenScaling},
     shaders::{
         self,
         textured_quad::{CameraUniform, Vertex},
     },
-    texture::{get_texture_key, Texture, TextureKey},
+    texture::{get_texture_key, SamplerOptions, Texture, TextureKey, TextureOptions},
     tilemap::Tilemap,
     AssetEngine, Color, EmeraldError, Rectangle, Scale, Transform, Translation, UIButton, World,
     WHITE,
 };
 
-use super::components::{get_bounding_box_of_triangle, Camera, ColorRect, ColorTri, Label, Sprite};
+use super::{
+    components::{
+        get_bounding_box_of_triangle, Camera, ColorRect, ColorRectMaterial, ColorTri, Label,
+        NineSlice, ParticleEmitter, Sprite, VectorShape, ViewTransform,
+    },
+    instancing::{expand_quad_draws, quad_axes, quad_instance, InstancedQuads, QuadInstance},
+    lighting::{
+        collect_lights, collect_occluder_edges, LightGeometry, LightRenderer, NormalMapDraws,
+    },
+    material::{Material, MaterialDescriptor, MaterialKey},
+    post_process::{PostProcessContext, PostProcessPass, PostProcessStack},
+    readback::{read_texture, CapturedImage},
+    render_layers::{LayerProjection, LayerSortMode, RenderLayer, RenderLayers},
+    texture_atlas::{TextureAtlas, TextureAtlasStats},
+    viewport::{ScissorRect, Viewport},
+    virtual_screen::VirtualScreen,
+};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum BindGroupLayoutId {
     TextureQuad,
 }
 
+/// Render state shared by every tri in a `TexturedTriDraw`, a change in state breaks the batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct DrawState {
+    /// The area of the render target the tris are clipped to, or the whole target if `None`.
+    pub scissor: Option<ScissorRect>,
+    /// The material the tris are drawn with, or the default textured shader if `None`.
+    pub material: Option<AssetId>,
+    /// Ignored by tris drawn with a material, which are blended the way the material was created with.
+    pub blend: BlendMode,
+}
+
+/// Where a quad is placed around its position, and how it's flipped and skewed.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct QuadPlacement {
+    pub centered: bool,
+    /// The point of the quad placed at its position and rotated around, overriding `centered`.
+    /// From (0, 0) at the bottom left of the quad, to (1, 1) at its top right.
+    pub pivot: Option<Vector2<f32>>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// See `quad_axes`.
+    pub skew: Vector2<f32>,
+    /// Floors the position of the quad when pixel snapping is enabled.
+    pub snap: bool,
+}
+impl QuadPlacement {
+    pub fn centered(centered: bool) -> Self {
+        Self {
+            centered,
+            pivot: None,
+            flip_x: false,
+            flip_y: false,
+            skew: Vector2::new(0.0, 0.0),
+            snap: true,
+        }
+    }
+
+    /// The pivot, mirrored along with the texture so a flipped quad stays in place.
+    pub fn pivot(&self) -> Option<Vector2<f32>> {
+        self.pivot.map(|pivot| {
+            Vector2::new(
+                if self.flip_x { 1.0 - pivot.x } else { pivot.x },
+                if self.flip_y { 1.0 - pivot.y } else { pivot.y },
+            )
+        })
+    }
+
+    /// The center of a quad with the given size and edges, placed at the position.
+    pub fn center(
+        &self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        axes: (Vector2<f32>, Vector2<f32>),
+    ) -> Vector2<f32> {
+        match self.pivot() {
+            // Rotated and skewed around the pivot, which stays at the position.
+            Some(pivot) => position + axes.0 * (0.5 - pivot.x) + axes.1 * (0.5 - pivot.y),
+            None if self.centered => position,
+            None => position + size / 2.0,
+        }
+    }
+}
+
 /// A set of textured tris that will be drawn.
 #[derive(Debug)]
 pub(crate) struct TexturedTriDraw {
@@ -41,6 +121,9 @@ pub(crate) struct TexturedTriDraw {
     pub texture_bind_group_asset_id: AssetId,
     pub vertices_range: Range<u64>,
     pub indices_range: Range<u64>,
+    /// The quads of a quad batch, until it's expanded into vertices or drawn instanced.
+    pub instances: Option<Range<u32>>,
+    pub state: DrawState,
     count: usize,
     indices_per_draw: usize,
     vertices_per_draw: usize,
@@ -55,18 +138,48 @@ impl TexturedTriDraw {
         indices_start: u64,
         indices_set_size: u64,
         indices_per_draw: usize,
+        state: DrawState,
     ) -> Self {
         Self {
             texture_asset_id,
             texture_bind_group_asset_id,
             vertices_range: vertices_start..vertices_start + vertices_set_size,
             indices_range: indices_start..indices_start + indices_set_size,
+            instances: None,
+            state,
             count: 1,
             vertices_per_draw,
             indices_per_draw,
         }
     }
 
+    /// A batch of quads, starting with the instance at the given index.
+    pub fn quads(
+        texture_asset_id: AssetId,
+        texture_bind_group_asset_id: AssetId,
+        instance_start: u32,
+        state: DrawState,
+    ) -> Self {
+        Self {
+            texture_asset_id,
+            texture_bind_group_asset_id,
+            vertices_range: 0..0,
+            indices_range: 0..0,
+            instances: Some(instance_start..instance_start + 1),
+            state,
+            count: 1,
+            vertices_per_draw: TEXTURED_QUAD_VERTICES_PER_DRAW,
+            indices_per_draw: TEXTURED_QUAD_INDICES_PER_DRAW,
+        }
+    }
+
+    pub fn add_instance(&mut self) {
+        if let Some(instances) = &mut self.instances {
+            instances.end += 1;
+            self.count += 1;
+        }
+    }
+
     /// Add a new vertices_set and indices_set to the call.
     /// Returns the index start for where to add the next indices_set
     pub fn add(&mut self, vertices_set_size: u64, indices_set_size: u64) {
@@ -82,6 +195,10 @@ impl TexturedTriDraw {
     pub fn count(&self) -> usize {
         self.count
     }
+
+    pub fn indices_count(&self) -> u32 {
+        (self.count * self.indices_per_draw) as u32
+    }
 }
 
 pub(crate) type BindGroupLayouts = HashMap<BindGroupLayoutId, BindGroupLayout>;
@@ -103,6 +220,11 @@ pub(crate) struct RenderingEngine {
 
     vertices: Vec<Vertex>,
     indices: Vec<u32>,
+    instances: Vec<QuadInstance>,
+    instanced_quads: InstancedQuads,
+    /// Pipelines blending other than alpha blending, keyed by whether they draw instanced.
+    /// Created the first time a drawable uses the blend mode.
+    blend_pipelines: HashMap<(BlendMode, bool), RenderPipeline>,
 
     pub render_texture_uid: usize,
 
@@ -111,7 +233,23 @@ pub(crate) struct RenderingEngine {
     pub active_render_texture_asset_id: Option<AssetId>,
     pub active_size: winit::dpi::PhysicalSize<u32>,
 
-    layout: Layout,
+    /// The camera view applied to vertices, set while drawing a world.
+    view: ViewTransform,
+    /// The area of the render target draws are clipped to, set while drawing a viewport.
+    scissor: Option<ScissorRect>,
+
+    /// Passes applied to the frame before it is presented.
+    pub post_process: PostProcessStack,
+    /// The target drawn into when the scaling mode uses a virtual resolution.
+    virtual_screen: VirtualScreen,
+    /// Pages that packed textures are drawn from.
+    texture_atlas: TextureAtlas,
+    light_renderer: LightRenderer,
+    /// Normal maps of the sprites drawn, collected only while drawing the lit layers of a world.
+    normal_map_draws: Option<NormalMapDraws>,
+
+    /// Lays out the text of labels, each glyph tagged with the index of its span.
+    layout: Layout<usize>,
 }
 impl RenderingEngine {
     pub async fn new(
@@ -122,7 +260,7 @@ impl RenderingEngine {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::Backends::all());
         let surface = unsafe { instance.create_surface(window) };
-        let adapter = get_adapter(&instance, &surface).await?;
+        let adapter = get_adapter(&instance, &surface, settings.force_fallback_adapter).await?;
         let (device, queue) = get_device_and_queue(&adapter).await?;
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -168,53 +306,17 @@ impl RenderingEngine {
                 label: Some("texture_bind_group_layout"),
             });
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Textured Quad Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("./shaders/textured_quad.wgsl").into()),
-        });
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&texture_bind_group_layout],
-                push_constant_ranges: &[],
-            });
+        let texture_quad_render_pipeline = create_textured_tri_pipeline(
+            &device,
+            config.format,
+            "Textured Quad",
+            include_str!("./shaders/textured_quad.wgsl"),
+            &[&texture_bind_group_layout],
+            wgpu::BlendState::ALPHA_BLENDING,
+        );
 
-        let texture_quad_render_pipeline =
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::desc()],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            });
+        let instanced_quads =
+            InstancedQuads::new(&device, config.format, &texture_bind_group_layout);
 
         bind_group_layouts.insert(BindGroupLayoutId::TextureQuad, texture_bind_group_layout);
 
@@ -258,16 +360,48 @@ impl RenderingEngine {
             index_buffer,
             vertices: Vec::new(),
             indices: Vec::new(),
+            instances: Vec::new(),
+            instanced_quads,
+            blend_pipelines: HashMap::new(),
 
             color_rect_texture,
 
             render_texture_uid: 0,
 
             active_render_texture_asset_id: None,
+            view: ViewTransform::default(),
+            scissor: None,
+            post_process: PostProcessStack::default(),
+            virtual_screen: VirtualScreen::default(),
+            texture_atlas: TextureAtlas::default(),
+            light_renderer: LightRenderer::default(),
+            normal_map_draws: None,
             layout: Layout::new(fontdue::layout::CoordinateSystem::PositiveYUp),
         })
     }
 
+    /// The size of the screen the game draws to, being the virtual resolution if the scaling mode uses one.
+    pub fn screen_size(&self) -> PhysicalSize<u32> {
+        match self.screen_scaling() {
+            Some(scaling) => PhysicalSize::new(scaling.screen_size.0, scaling.screen_size.1),
+            None => self.size,
+        }
+    }
+
+    pub fn screen_scaling(&self) -> Option<ScreenScaling> {
+        self.settings
+            .scaling_mode
+            .scaling((self.size.width, self.size.height))
+    }
+
+    /// Converts a position in the window into a position on the screen the game draws to.
+    pub fn window_to_screen(&self, window_translation: &Translation) -> Translation {
+        match self.screen_scaling() {
+            Some(scaling) => scaling.window_to_screen(window_translation),
+            None => *window_translation,
+        }
+    }
+
     pub fn resize_window(&mut self, new_size: PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
@@ -285,9 +419,246 @@ impl RenderingEngine {
         transform: Transform,
         asset_store: &mut AssetEngine,
     ) -> Result<(), EmeraldError> {
-        let (camera, camera_transform) = get_camera_and_camera_transform(world);
-        let camera_transform = camera_transform - transform;
-        let cmd_adder = DrawCommandAdder::new(self, world);
+        let active_camera = get_camera_and_camera_transform(world);
+        let (width, height) = (
+            self.active_size.width as f32,
+            self.active_size.height as f32,
+        );
+        let visible_area = Rectangle::new(-width / 2.0, -height / 2.0, width, height);
+
+        self.draw_world_layers(
+            world,
+            active_camera,
+            &transform,
+            Vector2::new(0.0, 0.0),
+            visible_area,
+            asset_store,
+        )
+    }
+
+    /// Draws the world through the camera of the viewport, clipped to its area of the render target.
+    pub fn draw_world_in_viewport(
+        &mut self,
+        world: &mut World,
+        viewport: &Viewport,
+        asset_store: &mut AssetEngine,
+    ) -> Result<(), EmeraldError> {
+        let camera = *world.get::<&Camera>(viewport.camera)?;
+        let camera_transform = *world.get::<&Transform>(viewport.camera)?;
+        let target_size = (self.active_size.width, self.active_size.height);
+        let scissor = match viewport.scissor(target_size) {
+            Some(scissor) => scissor,
+            None => return Ok(()),
+        };
+
+        self.scissor = Some(scissor);
+        let result = self.draw_world_layers(
+            world,
+            (camera, camera_transform),
+            &Transform::default(),
+            viewport.center_offset(target_size),
+            scissor.centered_area(target_size),
+            asset_store,
+        );
+        self.scissor = None;
+
+        result
+    }
+
+    /// Draws each visible render layer of the world in order, through the layer's camera.
+    /// Layers drawn through the active camera use the given camera instead,
+    /// and every camera is moved by the inverse of the given transform.
+    /// The center is the offset of the drawn area from the center of the target, in pixels with y pointing up.
+    fn draw_world_layers(
+        &mut self,
+        world: &mut World,
+        active_camera: (Camera, Transform),
+        transform: &Transform,
+        center: Vector2<f32>,
+        visible_area: Rectangle,
+        asset_store: &mut AssetEngine,
+    ) -> Result<(), EmeraldError> {
+        let layers = world.render_layers().clone();
+
+        // Lighting is multiplied over everything drawn up to the last layer drawn through a camera.
+        let last_lit_layer = if world.lighting().enabled {
+            layers
+                .iter()
+                .enumerate()
+                .filter(|(_, layer)| layer.visible && layer.projection != LayerProjection::Screen)
+                .map(|(index, _)| index)
+                .last()
+        } else {
+            None
+        };
+        if last_lit_layer.is_some() {
+            self.normal_map_draws = Some(NormalMapDraws::default());
+        }
+
+        for (index, layer) in layers.iter().enumerate() {
+            if !layer.visible {
+                continue;
+            }
+
+            let (camera, camera_transform) = match layer.projection {
+                LayerProjection::ActiveCamera => active_camera,
+                LayerProjection::Camera(entity) => {
+                    match (
+                        world.get::<&Camera>(entity),
+                        world.get::<&Transform>(entity),
+                    ) {
+                        (Ok(camera), Ok(camera_transform)) => (*camera, *camera_transform),
+                        _ => {
+                            return Err(EmeraldError::new(format!(
+                            "Render layer {:?} camera {:?} needs both a Camera and a Transform.",
+                            layer.name, entity
+                        )))
+                        }
+                    }
+                }
+                LayerProjection::Screen => (Camera::default(), Transform::default()),
+            };
+            let camera_transform = camera_transform - *transform;
+            let mut view = ViewTransform::new(&camera, &camera_transform);
+            view.offset += center;
+
+            self.draw_layer_through_view(
+                world,
+                &layers,
+                index,
+                &camera_transform,
+                view,
+                visible_area,
+                asset_store,
+            )?;
+
+            if Some(index) == last_lit_layer {
+                let (camera, camera_transform) = active_camera;
+                let camera_transform = camera_transform - *transform;
+                let mut view = ViewTransform::new(&camera, &camera_transform);
+                view.offset += center;
+                self.draw_lighting(world, &camera_transform, view, asset_store)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draws the lights of the world into a light accumulation target, through the given view,
+    /// then multiplies everything drawn so far by it.
+    fn draw_lighting(
+        &mut self,
+        world: &World,
+        camera_transform: &Transform,
+        view: ViewTransform,
+        asset_store: &mut AssetEngine,
+    ) -> Result<(), EmeraldError> {
+        let mut normal_map_draws = self.normal_map_draws.take().unwrap_or_default();
+        let size = (self.active_size.width, self.active_size.height);
+        let lighting = world.lighting();
+        let lights = collect_lights(world, &camera_transform.translation);
+        let edges = collect_occluder_edges(
+            world,
+            &camera_transform.translation,
+            lighting.circle_subdivisions,
+        );
+        let geometry = LightGeometry::build(&lights, &edges, &view, size);
+
+        // Taken out of the engine while it draws, so it can borrow the device.
+        let mut light_renderer = std::mem::take(&mut self.light_renderer);
+        let result = light_renderer.render(
+            &self.post_process_context(),
+            asset_store,
+            size,
+            lighting.ambient,
+            &geometry,
+            &mut normal_map_draws,
+        );
+        self.light_renderer = light_renderer;
+        let (light_map, composite) = result?;
+
+        let state = self.draw_state(Some(&composite), BlendMode::Alpha);
+        draw_textured_quad(
+            asset_store,
+            light_map.asset_key.asset_id,
+            light_map.bind_group_key.asset_id,
+            Rectangle::zeroed(),
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            0.0,
+            QuadPlacement::centered(true),
+            WHITE,
+            &Transform::default(),
+            &ViewTransform::default(),
+            self.active_size,
+            &mut self.instances,
+            &mut self.draw_queue,
+            &self.settings,
+            state,
+        )
+    }
+
+    /// Draws a normal map with the same quad as the sprite it belongs to,
+    /// if the normal maps of a lit world are being collected.
+    fn draw_normal_map(
+        &mut self,
+        asset_engine: &mut AssetEngine,
+        normal_map: &TextureKey,
+        target: Rectangle,
+        offset: Vector2<f32>,
+        scale: Vector2<f32>,
+        rotation: f32,
+        placement: QuadPlacement,
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        let state = self.draw_state(None, BlendMode::Alpha);
+        let normal_map_draws = match self.normal_map_draws.as_mut() {
+            Some(normal_map_draws) => normal_map_draws,
+            None => return Ok(()),
+        };
+
+        draw_textured_quad(
+            asset_engine,
+            normal_map.asset_key.asset_id,
+            normal_map.bind_group_key.asset_id,
+            target,
+            offset,
+            scale,
+            rotation,
+            placement,
+            color,
+            transform,
+            &self.view,
+            self.active_size,
+            &mut normal_map_draws.instances,
+            &mut normal_map_draws.draws,
+            &self.settings,
+            state,
+        )
+    }
+
+    /// Draws a render layer of the world relative to the camera transform, through the given view.
+    /// The visible area is the part of the render target being drawn to,
+    /// in pixels from the center of the target with y pointing up.
+    fn draw_layer_through_view(
+        &mut self,
+        world: &mut World,
+        layers: &RenderLayers,
+        layer_index: usize,
+        camera_transform: &Transform,
+        view: ViewTransform,
+        visible_area: Rectangle,
+        asset_store: &mut AssetEngine,
+    ) -> Result<(), EmeraldError> {
+        let cmd_adder = DrawCommandAdder::new(
+            &self.settings,
+            camera_transform,
+            &view,
+            &visible_area,
+            layers,
+            layer_index,
+        );
         let mut draw_queue = Vec::new();
 
         #[cfg(feature = "aseprite")]
@@ -296,15 +667,44 @@ impl RenderingEngine {
         cmd_adder.add_draw_commands::<AutoTilemap>(&mut draw_queue, world, asset_store);
         cmd_adder.add_draw_commands::<Tilemap>(&mut draw_queue, world, asset_store);
         cmd_adder.add_draw_commands::<Sprite>(&mut draw_queue, world, asset_store);
+        cmd_adder.add_draw_commands::<NineSlice>(&mut draw_queue, world, asset_store);
         cmd_adder.add_draw_commands::<UIButton>(&mut draw_queue, world, asset_store);
         cmd_adder.add_draw_commands::<ColorRect>(&mut draw_queue, world, asset_store);
         cmd_adder.add_draw_commands::<Label>(&mut draw_queue, world, asset_store);
-        draw_queue.sort_by(|a, b| a.z_index.partial_cmp(&b.z_index).unwrap());
-        for draw_command in draw_queue {
-            self.draw(asset_store, world, draw_command, &camera, &camera_transform)?;
+        cmd_adder.add_draw_commands::<ParticleEmitter>(&mut draw_queue, world, asset_store);
+        cmd_adder.add_draw_commands::<VectorShape>(&mut draw_queue, world, asset_store);
+
+        let sort_mode = layers
+            .get_by_index(layer_index)
+            .map(|layer| layer.sort_mode)
+            .unwrap_or(LayerSortMode::ZIndex);
+        sort_draw_commands(&mut draw_queue, sort_mode);
+
+        self.view = view;
+        let result = draw_queue.into_iter().try_for_each(|draw_command| {
+            self.draw(asset_store, world, draw_command, camera_transform)
+        });
+        self.view = ViewTransform::default();
+
+        result
+    }
+
+    /// The draw state of the following draws, drawn with the given material and blend mode.
+    fn draw_state(&self, material: Option<&MaterialKey>, blend: BlendMode) -> DrawState {
+        DrawState {
+            scissor: self.scissor,
+            material: material.map(|material| material.asset_key.asset_id),
+            blend,
         }
+    }
 
-        Ok(())
+    /// Sets the camera view applied to the following draws, until it's reset.
+    pub(crate) fn set_view(&mut self, view: ViewTransform) {
+        self.view = view;
+    }
+
+    pub(crate) fn reset_view(&mut self) {
+        self.view = ViewTransform::default();
     }
 
     #[inline]
@@ -313,15 +713,11 @@ impl RenderingEngine {
         asset_engine: &mut AssetEngine,
         world: &mut World,
         draw_command: DrawCommand,
-        camera: &Camera,
         camera_transform: &Transform,
     ) -> Result<(), EmeraldError> {
         let transform = {
-            let entity_transform = world.get::<&mut Transform>(draw_command.entity)?.clone();
-            let mut transform = entity_transform - camera_transform.clone();
-
-            transform.translation += Translation::from(camera.offset);
-            transform
+            let entity_transform = *world.get::<&Transform>(draw_command.entity)?;
+            entity_transform - *camera_transform
         };
 
         match draw_command.drawable_type {
@@ -333,6 +729,10 @@ impl RenderingEngine {
                 let sprite = world.get::<&Sprite>(draw_command.entity)?;
                 self.draw_sprite(asset_engine, &sprite, &transform)?;
             }
+            DrawableType::NineSlice => {
+                let nine_slice = world.get::<&NineSlice>(draw_command.entity)?;
+                self.draw_nine_slice(asset_engine, &nine_slice, &transform)?;
+            }
             DrawableType::Tilemap => {
                 let tilemap = world.get::<&Tilemap>(draw_command.entity)?;
                 self.draw_tilemap(asset_engine, &tilemap, &transform)?;
@@ -342,8 +742,12 @@ impl RenderingEngine {
                 self.draw_tilemap(asset_engine, &autotilemap.tilemap, &transform)?;
             }
             DrawableType::ColorRect => {
-                let color_rect = world.get::<&ColorRect>(draw_command.entity)?;
-                self.draw_color_rect(asset_engine, &color_rect, &transform)?;
+                let color_rect = *world.get::<&ColorRect>(draw_command.entity)?;
+                let material = world
+                    .get::<&ColorRectMaterial>(draw_command.entity)
+                    .ok()
+                    .map(|material| material.0.clone());
+                self.draw_color_rect(asset_engine, &color_rect, material.as_ref(), &transform)?;
             }
             DrawableType::UIButton => {
                 let ui_button = world.get::<&UIButton>(draw_command.entity)?;
@@ -357,6 +761,14 @@ impl RenderingEngine {
                 let label = world.get::<&Label>(draw_command.entity)?;
                 self.draw_label(asset_engine, &label, &transform)?;
             }
+            DrawableType::ParticleEmitter => {
+                let emitter = world.get::<&ParticleEmitter>(draw_command.entity)?;
+                self.draw_particle_emitter(asset_engine, &emitter, &transform)?;
+            }
+            DrawableType::VectorShape => {
+                let vector_shape = world.get::<&VectorShape>(draw_command.entity)?;
+                self.draw_vector_shape(asset_engine, &vector_shape, &transform)?;
+            }
         }
 
         Ok(())
@@ -381,23 +793,30 @@ impl RenderingEngine {
             return Ok(());
         }
 
-        let texture = ui_button.current_texture();
+        if let Some(nine_slice) = ui_button.current_nine_slice() {
+            return self.draw_nine_slice(asset_engine, nine_slice, transform);
+        }
+
+        let (texture_id, bind_group_id, target) =
+            self.atlas_target(ui_button.current_texture(), Rectangle::zeroed());
+        let state = self.draw_state(None, BlendMode::Alpha);
         draw_textured_quad(
             asset_engine,
-            texture.asset_key.asset_id,
-            texture.bind_group_key.asset_id,
-            Rectangle::zeroed(),
+            texture_id,
+            bind_group_id,
+            target,
             Vector2::new(0.0, 0.0),
             Vector2::new(1.0, 1.0),
             0.0,
-            true,
+            QuadPlacement::centered(true),
             WHITE,
             transform,
+            &self.view,
             self.active_size.clone(),
-            &mut self.vertices,
-            &mut self.indices,
+            &mut self.instances,
             &mut self.draw_queue,
             &self.settings,
+            state,
         )
     }
 
@@ -411,23 +830,41 @@ impl RenderingEngine {
             return Ok(());
         }
 
+        if let Some(normal_map) = aseprite.get_normal_map_sprite() {
+            self.draw_normal_map(
+                asset_engine,
+                &normal_map.texture_key,
+                normal_map.target,
+                aseprite.offset,
+                aseprite.scale,
+                aseprite.rotation,
+                aseprite.placement(),
+                aseprite.color,
+                transform,
+            )?;
+        }
+
         let sprite = aseprite.get_sprite();
+        let (texture_id, bind_group_id, target) =
+            self.atlas_target(&sprite.texture_key, sprite.target);
+        let state = self.draw_state(aseprite.material.as_ref(), aseprite.blend_mode);
         draw_textured_quad(
             asset_engine,
-            sprite.texture_key.asset_key.asset_id,
-            sprite.texture_key.bind_group_key.asset_id,
-            sprite.target,
+            texture_id,
+            bind_group_id,
+            target,
             aseprite.offset,
             aseprite.scale,
             aseprite.rotation,
-            aseprite.centered,
+            aseprite.placement(),
             aseprite.color,
             transform,
+            &self.view,
             self.active_size.clone(),
-            &mut self.vertices,
-            &mut self.indices,
+            &mut self.instances,
             &mut self.draw_queue,
             &self.settings,
+            state,
         )
     }
 
@@ -477,11 +914,12 @@ impl RenderingEngine {
                 let offset = Vector2::new(0.0, 0.0);
                 let scale = Vector2::new(1.0, 1.0);
                 let rotation = 0.0;
-                let centered = true;
+                let placement = QuadPlacement::centered(true);
                 let color = crate::colors::WHITE;
                 let transform = Transform::from_translation(translation);
                 let active_size = self.active_size;
 
+                let state = self.draw_state(None, BlendMode::Alpha);
                 draw_textured_quad(
                     asset_engine,
                     tilemap.tilesheet.asset_key.asset_id,
@@ -490,14 +928,15 @@ impl RenderingEngine {
                     offset,
                     scale,
                     rotation,
-                    centered,
+                    placement,
                     color,
                     &transform,
+                    &self.view,
                     active_size,
-                    &mut self.vertices,
-                    &mut self.indices,
+                    &mut self.instances,
                     &mut self.draw_queue,
                     &self.settings,
+                    state,
                 )?;
             }
 
@@ -521,35 +960,209 @@ impl RenderingEngine {
             return Ok(());
         }
 
+        if let Some(normal_map) = &sprite.normal_map {
+            self.draw_normal_map(
+                asset_engine,
+                normal_map,
+                sprite.target,
+                sprite.offset,
+                sprite.scale,
+                sprite.rotation,
+                sprite.placement(),
+                sprite.color,
+                transform,
+            )?;
+        }
+
+        let (texture_id, bind_group_id, target) =
+            self.atlas_target(&sprite.texture_key, sprite.target);
+        let state = self.draw_state(sprite.material.as_ref(), sprite.blend_mode);
         draw_textured_quad(
             asset_engine,
-            sprite.texture_key.asset_key.asset_id,
-            sprite.texture_key.bind_group_key.asset_id,
-            sprite.target,
+            texture_id,
+            bind_group_id,
+            target,
             sprite.offset,
             sprite.scale,
             sprite.rotation,
-            sprite.centered,
+            sprite.placement(),
             sprite.color,
             transform,
+            &self.view,
             self.active_size.clone(),
-            &mut self.vertices,
-            &mut self.indices,
+            &mut self.instances,
             &mut self.draw_queue,
             &self.settings,
+            state,
         )
     }
 
+    /// Draws every particle as its own quad, particles that share a texture and material are batched together.
+    pub fn draw_particle_emitter(
+        &mut self,
+        asset_engine: &mut AssetEngine,
+        emitter: &ParticleEmitter,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        if !emitter.visible {
+            return Ok(());
+        }
+
+        let state = self.draw_state(emitter.material.as_ref(), emitter.blend_mode);
+
+        for particle in emitter.particles() {
+            let frame = match emitter.frame_of(particle) {
+                Some(frame) => frame,
+                None => continue,
+            };
+            let progress = particle.progress();
+            let scale = emitter.scale.sample(progress).unwrap_or(1.0);
+            let color = emitter.color.sample(progress).unwrap_or(WHITE);
+            let (texture_id, bind_group_id, target) =
+                self.atlas_target(&frame.texture_key, frame.target);
+
+            draw_textured_quad(
+                asset_engine,
+                texture_id,
+                bind_group_id,
+                target,
+                emitter.draw_offset(particle),
+                Vector2::new(scale, scale),
+                0.0,
+                QuadPlacement::centered(true),
+                color,
+                // Like other drawables, particles are placed by the entity's translation in either space,
+                // but aren't rotated or scaled along with the entity.
+                transform,
+                &self.view,
+                self.active_size,
+                &mut self.instances,
+                &mut self.draw_queue,
+                &self.settings,
+                state,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws each part of the nine-slice as its own quad,
+    /// rotating the parts around the center of the nine-slice.
+    pub fn draw_nine_slice(
+        &mut self,
+        asset_engine: &mut AssetEngine,
+        nine_slice: &NineSlice,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        if !nine_slice.visible {
+            return Ok(());
+        }
+
+        let texture_size =
+            match asset_engine.get_asset::<Texture>(&nine_slice.texture_key.asset_key.asset_id) {
+                Some(texture) => (texture.size.width as f32, texture.size.height as f32),
+                None => {
+                    return Err(EmeraldError::new(format!(
+                        "Unable to find Texture for AssetId {:?}",
+                        nine_slice.texture_key.asset_key.asset_id
+                    )))
+                }
+            };
+
+        let origin = if nine_slice.centered {
+            -nine_slice.size / 2.0
+        } else {
+            Vector2::new(0.0, 0.0)
+        };
+        let (sin, cos) = nine_slice.rotation.sin_cos();
+        let state = self.draw_state(nine_slice.material.as_ref(), BlendMode::Alpha);
+
+        // Snapped once, so the patches keep their exact offsets from each other and don't leave seams.
+        let mut transform = *transform;
+        transform.translation.x += nine_slice.offset.x;
+        transform.translation.y += nine_slice.offset.y;
+        if self.settings.pixel_snap {
+            transform.translation.x = transform.translation.x.floor();
+            transform.translation.y = transform.translation.y.floor();
+        }
+        let placement = QuadPlacement {
+            snap: false,
+            ..QuadPlacement::centered(true)
+        };
+
+        for patch in nine_slice.patches(texture_size) {
+            let destination = patch.destination;
+            let center = origin
+                + Vector2::new(
+                    destination.x + destination.width / 2.0,
+                    destination.y + destination.height / 2.0,
+                );
+            let center = Vector2::new(
+                cos * center.x - sin * center.y,
+                sin * center.x + cos * center.y,
+            );
+
+            let (texture_id, bind_group_id, target) =
+                self.atlas_target(&nine_slice.texture_key, patch.source);
+            draw_textured_quad(
+                asset_engine,
+                texture_id,
+                bind_group_id,
+                target,
+                center,
+                Vector2::new(
+                    destination.width / patch.source.width,
+                    destination.height / patch.source.height,
+                ),
+                nine_slice.rotation,
+                placement,
+                nine_slice.color,
+                &transform,
+                &self.view,
+                self.active_size,
+                &mut self.instances,
+                &mut self.draw_queue,
+                &self.settings,
+                state,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// The texture and target a texture is drawn with,
+    /// being its atlas page and its area on that page if it has been packed.
+    fn atlas_target(
+        &self,
+        texture_key: &TextureKey,
+        target: Rectangle,
+    ) -> (AssetId, AssetId, Rectangle) {
+        match self.texture_atlas.remap(texture_key, target) {
+            Some((page, target)) => (
+                page.asset_key.asset_id,
+                page.bind_group_key.asset_id,
+                target,
+            ),
+            None => (
+                texture_key.asset_key.asset_id,
+                texture_key.bind_group_key.asset_id,
+                target,
+            ),
+        }
+    }
+
     pub fn draw_color_rect(
         &mut self,
         asset_engine: &mut AssetEngine,
         color_rect: &ColorRect,
+        material: Option<&MaterialKey>,
         transform: &Transform,
     ) -> Result<(), EmeraldError> {
         if !color_rect.visible {
             return Ok(());
         }
 
+        let state = self.draw_state(material, color_rect.blend_mode);
         draw_textured_quad(
             asset_engine,
             self.color_rect_texture.asset_key.asset_id,
@@ -558,14 +1171,15 @@ impl RenderingEngine {
             Vector2::new(0.0, 0.0),
             Vector2::new(color_rect.width as f32, color_rect.height as f32),
             color_rect.rotation,
-            color_rect.centered,
+            QuadPlacement::centered(color_rect.centered),
             color_rect.color,
             transform,
+            &self.view,
             self.active_size.clone(),
-            &mut self.vertices,
-            &mut self.indices,
+            &mut self.instances,
             &mut self.draw_queue,
             &self.settings,
+            state,
         )
     }
 
@@ -575,6 +1189,7 @@ impl RenderingEngine {
         color_tri: &ColorTri,
         transform: &Transform,
     ) -> Result<(), EmeraldError> {
+        let state = self.draw_state(None, BlendMode::Alpha);
         draw_textured_tri(
             asset_engine,
             self.color_rect_texture.asset_key.asset_id,
@@ -587,14 +1202,53 @@ impl RenderingEngine {
             ],
             color_tri.color,
             transform,
+            &self.view,
             self.active_size.clone(),
             &mut self.vertices,
             &mut self.indices,
             &mut self.draw_queue,
             &self.settings,
+            state,
         )
     }
 
+    pub fn draw_vector_shape(
+        &mut self,
+        asset_engine: &mut AssetEngine,
+        vector_shape: &VectorShape,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        if !vector_shape.visible {
+            return Ok(());
+        }
+
+        let state = self.draw_state(None, BlendMode::Alpha);
+        for points in vector_shape.triangles() {
+            draw_textured_tri(
+                asset_engine,
+                self.color_rect_texture.asset_key.asset_id,
+                self.color_rect_texture.bind_group_key.asset_id,
+                points,
+                [
+                    Vector2::new(0.0, 0.0),
+                    Vector2::new(1.0, 1.0),
+                    Vector2::new(0.0, 1.0),
+                ],
+                vector_shape.color,
+                transform,
+                &self.view,
+                self.active_size,
+                &mut self.vertices,
+                &mut self.indices,
+                &mut self.draw_queue,
+                &self.settings,
+                state,
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub fn begin(&mut self, _asset_store: &mut AssetEngine) -> Result<(), EmeraldError> {
         if self.active_render_texture_asset_id.is_some() {
             return Err(EmeraldError::new("Cannot begin render. There is an active render_texture. Please finish rendering to your texture before beginning the final render pass."));
@@ -602,7 +1256,9 @@ impl RenderingEngine {
 
         self.vertices.clear();
         self.indices.clear();
-        self.active_size = self.size;
+        self.instances.clear();
+        self.light_renderer.begin_frame();
+        self.active_size = self.screen_size();
 
         Ok(())
     }
@@ -617,6 +1273,7 @@ impl RenderingEngine {
         }
         self.vertices.clear();
         self.indices.clear();
+        self.instances.clear();
 
         if let Some(texture) = asset_engine.get_asset::<Texture>(&texture_key.asset_key.asset_id) {
             self.active_size = PhysicalSize::new(texture.size.width, texture.size.height);
@@ -627,6 +1284,7 @@ impl RenderingEngine {
             )));
         }
         self.active_render_texture_asset_id = Some(texture_key.asset_key.asset_id);
+        self.light_renderer.begin_frame();
 
         Ok(())
     }
@@ -645,7 +1303,7 @@ impl RenderingEngine {
                         ..Default::default()
                     });
 
-                    self.render_to_view(asset_store, view, &format!("render texture {:?}", id))?;
+                    self.render_to_view(asset_store, &view, &format!("render texture {:?}", id))?;
 
                     return Ok(());
                 }
@@ -663,6 +1321,16 @@ impl RenderingEngine {
         label: &str,
         asset_store: &mut AssetEngine,
         data: &[u8],
+    ) -> Result<TextureKey, EmeraldError> {
+        self.load_texture_with_options(label, asset_store, data, TextureOptions::default())
+    }
+
+    pub fn load_texture_with_options(
+        &mut self,
+        label: &str,
+        asset_store: &mut AssetEngine,
+        data: &[u8],
+        options: TextureOptions,
     ) -> Result<TextureKey, EmeraldError> {
         Texture::from_bytes(
             label,
@@ -671,9 +1339,29 @@ impl RenderingEngine {
             &self.device,
             &self.queue,
             &data,
+            options,
         )
     }
 
+    /// Replaces the sampler of a texture, a packed texture is removed from the atlas to draw with it.
+    pub fn set_texture_sampler(
+        &mut self,
+        texture_key: &TextureKey,
+        sampler: SamplerOptions,
+        asset_store: &mut AssetEngine,
+    ) -> Result<(), EmeraldError> {
+        Texture::set_sampler(
+            texture_key,
+            sampler,
+            &self.bind_group_layouts,
+            asset_store,
+            &self.device,
+        )?;
+        self.texture_atlas.unpack(texture_key);
+
+        Ok(())
+    }
+
     pub fn load_texture_ext(
         &mut self,
         label: &str,
@@ -697,7 +1385,7 @@ impl RenderingEngine {
     fn render_to_view(
         &mut self,
         asset_store: &mut AssetEngine,
-        view: TextureView,
+        view: &TextureView,
         view_name: &str,
     ) -> Result<(), EmeraldError> {
         let mut encoder = self
@@ -711,7 +1399,7 @@ impl RenderingEngine {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some(&format!("Render Pass {:?}", view_name)),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -750,11 +1438,156 @@ impl RenderingEngine {
         let view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
+        self.render_to_output(asset_store, &view)?;
+
+        surface_texture.present();
+        Ok(())
+    }
+
+    /// Draws the queued frame into an offscreen texture the size of the window instead of presenting it,
+    /// and reads its pixels back.
+    pub fn capture_frame(
+        &mut self,
+        asset_store: &mut AssetEngine,
+    ) -> Result<CapturedImage, EmeraldError> {
+        let size = wgpu::Extent3d {
+            width: self.config.width,
+            height: self.config.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Frame Capture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.render_to_output(asset_store, &view)?;
+
+        read_texture(
+            &self.device,
+            &self.queue,
+            &texture,
+            size,
+            self.config.format,
+        )
+    }
+
+    pub fn read_render_texture(
+        &self,
+        texture_key: &TextureKey,
+        asset_store: &AssetEngine,
+    ) -> Result<CapturedImage, EmeraldError> {
+        match asset_store.get_asset::<Texture>(&texture_key.asset_key.asset_id) {
+            Some(texture) => read_texture(
+                &self.device,
+                &self.queue,
+                &texture.texture,
+                texture.size,
+                texture.format,
+            ),
+            None => Err(EmeraldError::new(format!(
+                "Unable to read texture {:?}, it does not exist.",
+                texture_key
+            ))),
+        }
+    }
+
+    fn render_to_output(
+        &mut self,
+        asset_store: &mut AssetEngine,
+        view: &TextureView,
+    ) -> Result<(), EmeraldError> {
+        match self.screen_scaling() {
+            Some(scaling) => {
+                // Taken out of the engine while it's drawn into, so it can borrow the device.
+                let mut virtual_screen = std::mem::take(&mut self.virtual_screen);
+                let result =
+                    self.render_to_virtual_screen(&mut virtual_screen, &scaling, asset_store, view);
+                self.virtual_screen = virtual_screen;
+                result
+            }
+            None => self.render_frame(asset_store, view),
+        }
+    }
+
+    fn render_to_virtual_screen(
+        &mut self,
+        virtual_screen: &mut VirtualScreen,
+        scaling: &ScreenScaling,
+        asset_store: &mut AssetEngine,
+        output: &TextureView,
+    ) -> Result<(), EmeraldError> {
+        let screen_view = virtual_screen.prepare_target(
+            &self.post_process_context(),
+            asset_store,
+            scaling.screen_size,
+        )?;
+        self.render_frame(asset_store, &screen_view)?;
+
+        virtual_screen.present(&self.post_process_context(), asset_store, scaling, output)
+    }
+
+    /// Draws the queued frame into the view, through the post-processing passes if there are any.
+    fn render_frame(
+        &mut self,
+        asset_store: &mut AssetEngine,
+        view: &TextureView,
+    ) -> Result<(), EmeraldError> {
+        if self.post_process.is_active() {
+            // Taken out of the engine while it runs, so the passes can borrow the device.
+            let mut post_process = std::mem::take(&mut self.post_process);
+            let result = self.render_with_post_process(&mut post_process, asset_store, view);
+            self.post_process = post_process;
+            result
+        } else {
+            self.render_to_view(asset_store, view, "Surface Pass")
+        }
+    }
+
+    fn render_with_post_process(
+        &mut self,
+        post_process: &mut PostProcessStack,
+        asset_store: &mut AssetEngine,
+        output: &TextureView,
+    ) -> Result<(), EmeraldError> {
+        let screen_size = (self.active_size.width, self.active_size.height);
+        let scene_view =
+            post_process.prepare_targets(&self.post_process_context(), asset_store, screen_size)?;
+        self.render_to_view(asset_store, &scene_view, "Scene Pass")?;
+
+        post_process.apply(
+            &self.post_process_context(),
+            asset_store,
+            screen_size,
+            output,
+        )
+    }
+
+    fn post_process_context(&self) -> PostProcessContext<'_> {
+        PostProcessContext {
+            device: &self.device,
+            queue: &self.queue,
+            format: self.config.format,
+            bind_group_layouts: &self.bind_group_layouts,
+            blank_texture: &self.color_rect_texture,
+        }
+    }
 
-        self.render_to_view(asset_store, view, "Surface Pass")?;
+    /// Compiles a post-processing pass and adds it to the end of the stack, returning its index.
+    pub fn add_post_process(
+        &mut self,
+        pass: PostProcessPass,
+        asset_engine: &AssetEngine,
+    ) -> Result<usize, EmeraldError> {
+        let mut post_process = std::mem::take(&mut self.post_process);
+        let result = post_process.push(pass, &self.post_process_context(), asset_engine);
+        self.post_process = post_process;
 
-        surface_texture.present();
-        Ok(())
+        result
     }
 
     pub fn create_render_texture(
@@ -784,13 +1617,143 @@ impl RenderingEngine {
         Ok(key)
     }
 
+    /// Copies the textures into the texture atlas, see `TextureAtlas::pack`.
+    pub fn pack_textures(
+        &mut self,
+        textures: &[TextureKey],
+        asset_engine: &mut AssetEngine,
+    ) -> Result<usize, EmeraldError> {
+        self.texture_atlas.pack(
+            &self.settings.texture_atlas,
+            textures,
+            &self.bind_group_layouts,
+            asset_engine,
+            &self.device,
+            &self.queue,
+        )
+    }
+
+    pub fn texture_atlas_stats(&self) -> TextureAtlasStats {
+        self.texture_atlas.stats()
+    }
+
+    pub fn clear_texture_atlas(&mut self) {
+        self.texture_atlas.clear();
+    }
+
+    /// Creates a material from the source of its fragment shader, see `Material::create`.
+    pub fn create_material(
+        &mut self,
+        label: &str,
+        source: &str,
+        descriptor: MaterialDescriptor,
+        asset_engine: &mut AssetEngine,
+    ) -> Result<MaterialKey, EmeraldError> {
+        let texture_bind_group_layout = self
+            .bind_group_layouts
+            .get(&BindGroupLayoutId::TextureQuad)
+            .ok_or_else(|| EmeraldError::new("Unable to get TextureQuad bind group layout"))?;
+
+        Material::create(
+            label,
+            source,
+            descriptor,
+            &self.device,
+            self.config.format,
+            texture_bind_group_layout,
+            asset_engine,
+            wgpu::BlendState::ALPHA_BLENDING,
+        )
+    }
+
+    /// Overwrites the start of the material's uniform block with the given values.
+    pub fn set_material_uniforms(
+        &mut self,
+        material_key: &MaterialKey,
+        uniforms: &[f32],
+        asset_engine: &mut AssetEngine,
+    ) -> Result<(), EmeraldError> {
+        let material = asset_engine
+            .get_asset::<Material>(&material_key.asset_key.asset_id)
+            .ok_or_else(|| {
+                EmeraldError::new(format!("Unable to find material {:?}", material_key))
+            })?;
+
+        let bytes: &[u8] = bytemuck::cast_slice(uniforms);
+        if bytes.len() as u64 > material.uniform_size {
+            return Err(EmeraldError::new(format!(
+                "Material {:?} has {} bytes of uniforms, unable to write {} bytes.",
+                material_key,
+                material.uniform_size,
+                bytes.len()
+            )));
+        }
+        self.queue.write_buffer(&material.uniform_buffer, 0, bytes);
+
+        Ok(())
+    }
+
+    /// Creates the pipelines of the blend modes used by the queued draws, that weren't used before.
+    fn create_blend_pipelines(&mut self) -> Result<(), EmeraldError> {
+        let missing = self
+            .draw_queue
+            .iter()
+            .filter(|draw| draw.state.material.is_none() && draw.state.blend != BlendMode::Alpha)
+            .map(|draw| (draw.state.blend, draw.instances.is_some()))
+            .filter(|key| !self.blend_pipelines.contains_key(key))
+            .collect::<HashSet<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let texture_bind_group_layout = self
+            .bind_group_layouts
+            .get(&BindGroupLayoutId::TextureQuad)
+            .ok_or_else(|| EmeraldError::new("Unable to get TextureQuad bind group layout"))?;
+        for (blend, instanced) in missing {
+            let pipeline = if instanced {
+                InstancedQuads::create_pipeline(
+                    &self.device,
+                    self.config.format,
+                    texture_bind_group_layout,
+                    blend.blend_state(),
+                )
+            } else {
+                create_textured_tri_pipeline(
+                    &self.device,
+                    self.config.format,
+                    &format!("Textured Quad {:?}", blend),
+                    include_str!("./shaders/textured_quad.wgsl"),
+                    &[texture_bind_group_layout],
+                    blend.blend_state(),
+                )
+            };
+            self.blend_pipelines.insert((blend, instanced), pipeline);
+        }
+
+        Ok(())
+    }
+
     #[inline]
     fn consume_draw_queue<'a>(
         &'a mut self,
         render_pass: &mut wgpu::RenderPass<'a>,
         asset_engine: &'a mut AssetEngine,
     ) -> Result<(), EmeraldError> {
+        // Large batches of quads are drawn instanced, the rest are drawn from vertices like tris.
+        expand_quad_draws(
+            &mut self.draw_queue,
+            &self.instances,
+            &mut self.vertices,
+            &mut self.indices,
+            self.settings.instancing_threshold,
+        );
+        self.create_blend_pipelines()?;
         render_pass.set_pipeline(&self.texture_quad_render_pipeline);
+        if self.draw_queue.iter().any(|draw| draw.instances.is_some()) {
+            self.instanced_quads
+                .upload(&self.device, &self.queue, &self.instances);
+        }
 
         // Calculate vertices for every texture to be drawn, paired with their sprite data and vertex indices
         // for every tuple, draw that sprites texture bind group using that vertex index as the slice
@@ -825,14 +1788,72 @@ impl RenderingEngine {
                 .write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
         }
 
+        let mut active_state = DrawState::default();
+        let mut active_instanced = false;
         while let Some(draw_call) = self.draw_queue.pop_back() {
-            let indices_count = draw_call.count() as u32 * draw_call.indices_per_draw as u32;
+            let indices_count = draw_call.indices_count();
+            let instanced = draw_call.instances.is_some();
+
+            if draw_call.state.scissor != active_state.scissor {
+                let scissor = draw_call.state.scissor.unwrap_or(ScissorRect {
+                    x: 0,
+                    y: 0,
+                    width: self.active_size.width,
+                    height: self.active_size.height,
+                });
+                render_pass.set_scissor_rect(scissor.x, scissor.y, scissor.width, scissor.height);
+            }
+
+            if draw_call.state.material != active_state.material
+                || draw_call.state.blend != active_state.blend
+                || instanced != active_instanced
+            {
+                match draw_call.state.material {
+                    Some(material_id) => match asset_engine.get_asset::<Material>(&material_id) {
+                        Some(material) => {
+                            render_pass.set_pipeline(&material.pipeline);
+                            render_pass.set_bind_group(1, &material.bind_group, &[]);
+                        }
+                        None => {
+                            return Err(EmeraldError::new(format!(
+                                "Unable to find material for AssetId {:?}",
+                                material_id
+                            )))
+                        }
+                    },
+                    // Falls back to alpha blending if the pipeline of the blend mode wasn't created.
+                    None => render_pass.set_pipeline(
+                        match self
+                            .blend_pipelines
+                            .get(&(draw_call.state.blend, instanced))
+                        {
+                            Some(pipeline) => pipeline,
+                            None if instanced => &self.instanced_quads.pipeline,
+                            None => &self.texture_quad_render_pipeline,
+                        },
+                    ),
+                }
+            }
+            active_state = draw_call.state;
+            active_instanced = instanced;
 
             if let Some(texture_bind_group) =
                 asset_engine.get_asset::<BindGroup>(&draw_call.texture_bind_group_asset_id)
             {
                 render_pass.set_bind_group(0, texture_bind_group, &[]);
 
+                if let Some(instances) = draw_call.instances {
+                    render_pass.set_vertex_buffer(0, self.instanced_quads.quad_vertices.slice(..));
+                    render_pass
+                        .set_vertex_buffer(1, self.instanced_quads.instance_buffer.slice(..));
+                    render_pass.set_index_buffer(
+                        self.instanced_quads.quad_indices.slice(..),
+                        wgpu::IndexFormat::Uint16,
+                    );
+                    render_pass.draw_indexed(0..InstancedQuads::index_count(), 0, instances);
+                    continue;
+                }
+
                 render_pass
                     .set_vertex_buffer(0, self.vertex_buffer.slice(draw_call.vertices_range));
                 render_pass.set_index_buffer(
@@ -862,37 +1883,13 @@ impl RenderingEngine {
             return Ok(());
         }
 
-        self.layout.reset(&Default::default());
-
-        asset_engine
-            .get_asset::<Font>(&label.font_key.asset_key.asset_id)
-            .map(|font| {
-                self.layout.append(
-                    &[&font.inner],
-                    &TextStyle::new(&label.text, label.font_size as f32, 0),
-                );
-            });
-
-        let mut to_cache = Vec::new();
-        asset_engine
-            .get_asset::<Font>(&label.font_key.asset_key.asset_id)
-            .map(|font| {
-                for glyph in self.layout.glyphs() {
-                    if !font.characters.contains_key(&glyph.key) {
-                        to_cache.push(glyph.key);
-                    }
-                }
-            });
-
-        for glyph_key in to_cache {
-            crate::font::cache_glyph(
-                self,
-                asset_engine,
-                &label.font_key,
-                glyph_key,
-                label.font_size,
-            )?;
-        }
+        let spans = label.spans();
+        // Indexed by the fonts given to the layout, regular then bold then italic.
+        let font_keys = [
+            &label.font_key,
+            label.bold_font.as_ref().unwrap_or(&label.font_key),
+            label.italic_font.as_ref().unwrap_or(&label.font_key),
+        ];
 
         self.layout.reset(&LayoutSettings {
             max_width: label.max_width,
@@ -903,112 +1900,179 @@ impl RenderingEngine {
             ..LayoutSettings::default()
         });
 
-        if let Some(font) = asset_engine.get_asset::<Font>(&label.font_key.asset_key.asset_id) {
-            self.layout.append(
-                &[&font.inner],
-                &TextStyle::new(&label.text, label.font_size as f32, 0),
-            );
-        } else {
-            return Err(EmeraldError::new(format!(
-                "Font {:?} was not found in the asset store.",
-                &label.font_key
-            )));
+        {
+            let mut fonts = Vec::new();
+            for font_key in font_keys {
+                match asset_engine.get_asset::<Font>(&font_key.asset_key.asset_id) {
+                    Some(font) => fonts.push(&font.inner),
+                    None => {
+                        return Err(EmeraldError::new(format!(
+                            "Font {:?} was not found in the asset store.",
+                            font_key
+                        )))
+                    }
+                }
+            }
+
+            // Glyphs are tagged with the index of their span.
+            for (index, span) in spans.iter().enumerate() {
+                let size = span.size.unwrap_or(label.font_size as f32);
+                match &span.icon {
+                    // Icons take the place of as many spaces as they are wide.
+                    Some(icon) => {
+                        let (width, height) = match label.icons.get(icon) {
+                            Some(texture) => texture.size(),
+                            None => continue,
+                        };
+                        let width = width as f32 * size / height.max(1) as f32;
+                        let space = fonts[0].metrics(' ', size).advance_width.max(1.0);
+                        let placeholder = " ".repeat((width / space).ceil().max(1.0) as usize);
+                        self.layout.append(
+                            &fonts,
+                            &TextStyle::with_user_data(&placeholder, size, 0, index),
+                        );
+                    }
+                    None => {
+                        let font_index = if span.bold {
+                            1
+                        } else if span.italic {
+                            2
+                        } else {
+                            0
+                        };
+                        self.layout.append(
+                            &fonts,
+                            &TextStyle::with_user_data(&span.text, size, font_index, index),
+                        );
+                    }
+                }
+            }
+        }
+        let glyphs = self.layout.glyphs().clone();
+
+        for glyph in &glyphs {
+            let font_key = font_keys[glyph.font_index];
+            let cached = asset_engine
+                .get_asset::<Font>(&font_key.asset_key.asset_id)
+                .is_some_and(|font| font.characters.contains_key(&glyph.key));
+            if !cached {
+                crate::font::cache_glyph(self, asset_engine, font_key, glyph.key, label.font_size)?;
+            }
         }
 
         let mut remaining_char_count = if label.visible_characters < 0 {
-            label.text.len() as i64
+            i64::MAX
         } else {
             label.visible_characters
         };
+        let mut character_index = 0;
+        let mut last_icon = None;
 
         let mut to_draw = Vec::new();
-        for glyph in self.layout.glyphs() {
-            let glyph_key = glyph.key;
-            let x = glyph.x;
-            let y = glyph.y;
-
-            if let Some(font) =
-                asset_engine.get_asset_mut::<Font>(&label.font_key.asset_key.asset_id)
-            {
-                if !font.characters.contains_key(&glyph_key) {
-                    return Err(EmeraldError::new(format!(
-                        "Font {:?} does not contain cached glyph {:?}",
-                        font.font_texture_key, glyph_key
-                    )));
-                }
-
-                let font_data = &font.characters[&glyph_key];
-                let left_coord = (font_data.offset_x as f32 + x) * label.scale;
-                let top_coord = y * label.scale;
-
-                let target = Rectangle::new(
-                    font_data.glyph_x as f32,
-                    font_data.glyph_y as f32,
-                    font_data.glyph_w as f32,
-                    font_data.glyph_h as f32,
-                );
+        for glyph in &glyphs {
+            let span = &spans[glyph.user_data];
+
+            let (texture_asset_id, texture_bind_group_asset_id, target, scale, left) =
+                if let Some(icon) = &span.icon {
+                    // Drawn once, at the first of its spaces.
+                    if last_icon == Some(glyph.user_data) {
+                        continue;
+                    }
+                    last_icon = Some(glyph.user_data);
+
+                    let texture = match label.icons.get(icon) {
+                        Some(texture) => texture,
+                        None => continue,
+                    };
+                    let size = span.size.unwrap_or(label.font_size as f32);
+                    let (texture_id, bind_group_id, target) =
+                        self.atlas_target(texture, Rectangle::zeroed());
+                    let scale = size / texture.size().1.max(1) as f32 * label.scale;
+
+                    (texture_id, bind_group_id, target, scale, glyph.x)
+                } else {
+                    let font_key = font_keys[glyph.font_index];
+                    let font = asset_engine
+                        .get_asset::<Font>(&font_key.asset_key.asset_id)
+                        .ok_or_else(|| {
+                            EmeraldError::new(format!("Font not found: {:?}", font_key))
+                        })?;
+                    let font_data = font.characters.get(&glyph.key).ok_or_else(|| {
+                        EmeraldError::new(format!(
+                            "Font {:?} does not contain cached glyph {:?}",
+                            font.font_texture_key, glyph.key
+                        ))
+                    })?;
+
+                    let target = Rectangle::new(
+                        font_data.glyph_x as f32,
+                        font_data.glyph_y as f32,
+                        font_data.glyph_w as f32,
+                        font_data.glyph_h as f32,
+                    );
+                    if target.is_zero_sized() {
+                        continue;
+                    }
 
-                let mut transform = transform.clone();
-                transform.translation.x += label.offset.x + left_coord;
-                transform.translation.y += label.offset.y + top_coord;
+                    (
+                        font.font_texture_key.asset_key.asset_id,
+                        font.font_texture_key.bind_group_key.asset_id,
+                        target,
+                        label.scale,
+                        font_data.offset_x as f32 + glyph.x,
+                    )
+                };
 
-                let scale = Vector2::new(label.scale, label.scale);
-                let offset = label.offset;
-                let rotation = 0.0;
-                if label.centered {
-                    if let Some(width) = &label.max_width {
-                        transform.translation.x -= width / 2.0;
-                    }
-                }
+            if remaining_char_count < 0 {
+                continue;
+            }
 
-                if remaining_char_count < 0 || target.is_zero_sized() {
-                    continue;
+            let (effect_x, effect_y) = span.effect.map_or((0.0, 0.0), |effect| {
+                effect.offset(character_index, label.effect_time)
+            });
+            let mut transform = *transform;
+            transform.translation.x += label.offset.x + (left + effect_x) * label.scale;
+            transform.translation.y += label.offset.y + (glyph.y + effect_y) * label.scale;
+            if label.centered {
+                if let Some(width) = &label.max_width {
+                    transform.translation.x -= width / 2.0;
                 }
+            }
 
-                to_draw.push((
-                    font.font_texture_key.asset_key.asset_id,
-                    font.font_texture_key.bind_group_key.asset_id,
-                    target,
-                    offset,
-                    scale,
-                    rotation,
-                    transform,
-                ));
+            to_draw.push((
+                texture_asset_id,
+                texture_bind_group_asset_id,
+                target,
+                Vector2::new(scale, scale),
+                transform,
+                span.color.unwrap_or(label.color),
+            ));
 
-                remaining_char_count -= 1;
-            } else {
-                return Err(EmeraldError::new(format!(
-                    "Font not found: {:?}",
-                    label.font_key
-                )));
-            }
+            remaining_char_count -= 1;
+            character_index += 1;
         }
-        for (
-            texture_asset_id,
-            texture_bind_group_asset_id,
-            target,
-            offset,
-            scale,
-            rotation,
-            transform,
-        ) in to_draw
+
+        let state = self.draw_state(label.material.as_ref(), label.blend_mode);
+        for (texture_asset_id, texture_bind_group_asset_id, target, scale, transform, color) in
+            to_draw
         {
             draw_textured_quad(
                 asset_engine,
                 texture_asset_id,
                 texture_bind_group_asset_id,
                 target,
-                offset,
+                label.offset,
                 scale,
-                rotation,
-                false,
-                label.color,
+                0.0,
+                QuadPlacement::centered(false),
+                color,
                 &transform,
-                self.active_size.clone(),
-                &mut self.vertices,
-                &mut self.indices,
+                &self.view,
+                self.active_size,
+                &mut self.instances,
                 &mut self.draw_queue,
                 &self.settings,
+                state,
             )?;
         }
 
@@ -1057,8 +2121,6 @@ const INDEX_SIZE: u64 = std::mem::size_of::<u32>() as u64;
 
 const TEXTURED_QUAD_VERTICES_PER_DRAW: usize = 4;
 const TEXTURED_QUAD_INDICES_PER_DRAW: usize = 6;
-const TEXTURED_QUAD_VERTEX_SET_SIZE: u64 = VERTEX_SIZE * TEXTURED_QUAD_VERTICES_PER_DRAW as u64; // 4 vertices, 1 for each corner of the quad
-const TEXTURED_QUAD_INDICES_SET_SIZE: u64 = INDEX_SIZE * TEXTURED_QUAD_INDICES_PER_DRAW as u64; // 6 indices to draw a quad using 4 vertices
 
 fn draw_textured_quad(
     asset_store: &mut AssetEngine,
@@ -1068,14 +2130,15 @@ fn draw_textured_quad(
     offset: Vector2<f32>,
     scale: Vector2<f32>,
     rotation: f32,
-    centered: bool,
+    placement: QuadPlacement,
     color: Color,
     transform: &Transform,
+    view: &ViewTransform,
     active_size: PhysicalSize<u32>,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
+    instances: &mut Vec<QuadInstance>,
     textured_tri_draws: &mut VecDeque<TexturedTriDraw>,
     settings: &RenderSettings,
+    state: DrawState,
 ) -> Result<(), EmeraldError> {
     let texture_size;
     if let Some(texture) = asset_store.get_asset::<Texture>(&texture_asset_id) {
@@ -1100,18 +2163,13 @@ fn draw_textured_quad(
     let mut x = transform.translation.x + offset.x;
     let mut y = transform.translation.y + offset.y;
 
-    if settings.pixel_snap {
+    if settings.pixel_snap && placement.snap {
         x = x.floor();
         y = y.floor();
     }
 
-    let x = x / (active_size.width as f32 / 2.0);
-    let y = y / (active_size.height as f32 / 2.0);
-
-    let normalized_texture_size = (
-        target.width / (active_size.width as f32 / 2.0),
-        target.height / (active_size.height as f32 / 2.0),
-    );
+    let width = target.width * scale.x;
+    let height = target.height * scale.y;
 
     {
         let x = target.x / texture_size.0;
@@ -1121,129 +2179,53 @@ fn draw_textured_quad(
         target = Rectangle::new(x, y, width, height);
     }
 
-    let width = normalized_texture_size.0 * scale.x;
-    let height = normalized_texture_size.1 * scale.y;
-    let mut vertex_rect = Rectangle::new(x, y, width, height);
-
-    if centered {
-        vertex_rect.x -= width / 2.0;
-        vertex_rect.y -= height / 2.0;
+    // Flipping mirrors the texture coordinates, so the winding of the quad is kept.
+    if placement.flip_x {
+        target.x += target.width;
+        target.width = -target.width;
     }
-    let center_x = vertex_rect.x + vertex_rect.width / 2.0;
-    let center_y = vertex_rect.y + vertex_rect.height / 2.0;
-
-    fn rotate_vertex(center_x: f32, center_y: f32, x: f32, y: f32, rotation: f32) -> [f32; 2] {
-        let diff_x = x - center_x;
-        let diff_y = y - center_y;
-        [
-            center_x + (rotation.cos() * diff_x) - (rotation.sin() * diff_y),
-            center_y + (rotation.sin() * diff_x) + (rotation.cos() * diff_y),
-        ]
+    if placement.flip_y {
+        target.y += target.height;
+        target.height = -target.height;
     }
 
-    let color = color.to_percentage_slice();
-    let vertex_set = [
-        // Changed
-        Vertex {
-            position: rotate_vertex(
-                center_x,
-                center_y,
-                vertex_rect.x,
-                vertex_rect.y + vertex_rect.height,
-                rotation,
-            ),
-            tex_coords: [target.x, target.y],
-            color,
-        }, // A
-        Vertex {
-            position: rotate_vertex(center_x, center_y, vertex_rect.x, vertex_rect.y, rotation),
-            tex_coords: [target.x, target.y + target.height],
-            color,
-        }, // B
-        Vertex {
-            position: rotate_vertex(
-                center_x,
-                center_y,
-                vertex_rect.x + vertex_rect.width,
-                vertex_rect.y,
-                rotation,
-            ),
-            tex_coords: [target.x + target.width, target.y + target.height],
-            color,
-        }, // C
-        Vertex {
-            position: rotate_vertex(
-                center_x,
-                center_y,
-                vertex_rect.x + vertex_rect.width,
-                vertex_rect.y + vertex_rect.height,
-                rotation,
-            ),
-            tex_coords: [target.x + target.width, target.y],
-            color,
-        },
-    ];
+    let size = Vector2::new(width, height);
+    let axes = quad_axes(size, rotation, placement.skew);
+    let center = placement.center(Vector2::new(x, y), size, axes);
+
+    // Map the quad through the camera view onto the screen.
+    let instance = quad_instance(center, axes, target, color.to_percentage_slice(), |point| {
+        let position = view.apply(point);
+        Vector2::new(
+            position.x / (active_size.width as f32 / 2.0),
+            position.y / (active_size.height as f32 / 2.0),
+        )
+    });
 
     if settings.frustrum_culling {
-        // Use vertex set bounding box for frustrum culling
-        if !Rectangle::new(-1.0, -1.0, 2.0, 2.0).intersects_with(&vertex_rect) {
+        // Use the quad's bounding box for frustrum culling
+        if !Rectangle::new(-1.0, -1.0, 2.0, 2.0).intersects_with(&instance.bounds()) {
             return Ok(());
         }
     }
 
-    let len = textured_tri_draws.len();
-    let same_texture = len > 0
-        && textured_tri_draws
-            .front()
-            .filter(|draw| {
-                draw.texture_asset_id == texture_asset_id
-                    && draw.indices_per_draw == TEXTURED_QUAD_INDICES_PER_DRAW // check that the previous draw is also for quads
-                    && draw.vertices_per_draw == TEXTURED_QUAD_VERTICES_PER_DRAW
-            })
-            .is_some();
+    let same_batch = textured_tri_draws.front().is_some_and(|draw| {
+        draw.texture_asset_id == texture_asset_id && draw.state == state && draw.instances.is_some()
+    });
 
-    let mut index_start: u32 = 0;
-    if same_texture {
+    if same_batch {
         if let Some(draw) = textured_tri_draws.front_mut() {
-            index_start = draw.index_start();
-            draw.add(
-                TEXTURED_QUAD_VERTEX_SET_SIZE,
-                TEXTURED_QUAD_INDICES_SET_SIZE,
-            );
-        }
-    }
-
-    let indices_set = [
-        index_start,
-        index_start + 1,
-        index_start + 2,
-        index_start,
-        index_start + 2,
-        index_start + 3,
-    ];
-
-    vertices.extend(vertex_set);
-    indices.extend(indices_set);
-
-    if !same_texture {
-        let mut indices_start = 0;
-        let mut vertices_start = 0;
-        if let Some(draw) = textured_tri_draws.front() {
-            indices_start = draw.indices_range.end;
-            vertices_start = draw.vertices_range.end;
+            draw.add_instance();
         }
-
-        textured_tri_draws.push_front(TexturedTriDraw::new(
+    } else {
+        textured_tri_draws.push_front(TexturedTriDraw::quads(
             texture_asset_id,
             texture_bind_group_asset_id,
-            vertices_start,
-            TEXTURED_QUAD_VERTEX_SET_SIZE,
-            TEXTURED_QUAD_VERTICES_PER_DRAW,
-            indices_start,
-            TEXTURED_QUAD_INDICES_SET_SIZE,
-            TEXTURED_QUAD_INDICES_PER_DRAW,
+            instances.len() as u32,
+            state,
         ));
     }
+    instances.push(instance);
 
     Ok(())
 }
@@ -1262,11 +2244,13 @@ fn draw_textured_tri(
     mut target: [Vector2<f32>; 3],
     color: Color,
     transform: &Transform,
+    view: &ViewTransform,
     active_size: PhysicalSize<u32>,
     vertices: &mut Vec<Vertex>,
     indices: &mut Vec<u32>,
     textured_tri_draws: &mut VecDeque<TexturedTriDraw>,
     settings: &RenderSettings,
+    state: DrawState,
 ) -> Result<(), EmeraldError> {
     let texture_size;
     if let Some(texture) = asset_store.get_asset::<Texture>(&texture_asset_id) {
@@ -1295,6 +2279,7 @@ fn draw_textured_tri(
             point.y = point.y.floor();
         }
 
+        *point = view.apply(*point);
         point.x = point.x / (active_size.width as f32 / 2.0);
         point.y = point.y / (active_size.height as f32 / 2.0);
     }
@@ -1314,6 +2299,7 @@ fn draw_textured_tri(
             .front()
             .filter(|draw| {
                 draw.texture_asset_id == texture_asset_id
+                    && draw.state == state
                     && draw.indices_per_draw == TEXTURED_TRI_INDICES_PER_DRAW
                     && draw.vertices_per_draw == TEXTURED_TRI_VERTICES_PER_DRAW
             })
@@ -1346,17 +2332,13 @@ fn draw_textured_tri(
         },
     ];
 
+    // Quads are only expanded into vertices once they're drawn, so tris follow the last vertices added.
+    let vertices_start = vertices.len() as u64 * VERTEX_SIZE;
+    let indices_start = indices.len() as u64 * INDEX_SIZE;
     vertices.extend(vertex_set);
     indices.extend(indices_set);
 
     if !same_texture {
-        let mut indices_start = 0;
-        let mut vertices_start = 0;
-        if let Some(draw) = textured_tri_draws.front() {
-            indices_start = draw.indices_range.end;
-            vertices_start = draw.vertices_range.end;
-        }
-
         textured_tri_draws.push_front(TexturedTriDraw::new(
             texture_asset_id,
             texture_bind_group_asset_id,
@@ -1366,23 +2348,90 @@ fn draw_textured_tri(
             indices_start,
             TEXTURED_TRI_INDICES_SET_SIZE,
             TEXTURED_TRI_INDICES_PER_DRAW,
+            state,
         ));
     }
 
     Ok(())
 }
 
+/// Creates a pipeline drawing textured tris through the given fragment shader.
+/// The fragment shader is appended to the shader prelude, which defines the vertex stage and texture bindings.
+pub(crate) fn create_textured_tri_pipeline(
+    device: &Device,
+    format: wgpu::TextureFormat,
+    label: &str,
+    fragment_source: &str,
+    bind_group_layouts: &[&BindGroupLayout],
+    blend: wgpu::BlendState,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(&format!("{} Shader", label)),
+        source: wgpu::ShaderSource::Wgsl(
+            format!("{}\n{}", shaders::PRELUDE, fragment_source).into(),
+        ),
+    });
+
+    let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some(&format!("{} Pipeline Layout", label)),
+        bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(&format!("{} Pipeline", label)),
+        layout: Some(&render_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+/// Falls back to a software adapter when no hardware adapter is available, such as on a machine without a gpu.
 async fn get_adapter(
     instance: &wgpu::Instance,
     surface: &Surface,
+    force_fallback_adapter: bool,
 ) -> Result<Adapter, EmeraldError> {
-    let adapter_result = instance
-        .request_adapter(&wgpu::RequestAdapterOptions {
+    let request = |force_fallback_adapter| {
+        instance.request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: wgpu::PowerPreference::default(),
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
+            compatible_surface: Some(surface),
+            force_fallback_adapter,
         })
-        .await;
+    };
+
+    let mut adapter_result = request(force_fallback_adapter).await;
+    if adapter_result.is_none() && !force_fallback_adapter {
+        adapter_result = request(true).await;
+    }
 
     match adapter_result {
         None => Err(EmeraldError::new("No graphics adapters found.")),
@@ -1508,6 +2557,11 @@ impl ToDrawable for Aseprite {
     ) -> Option<Rectangle> {
         let mut sprite = self.get_sprite().clone();
         sprite.offset = self.offset.clone();
+        sprite.centered = self.centered;
+        sprite.pivot = self.pivot;
+        sprite.flip_x = self.flip_x;
+        sprite.flip_y = self.flip_y;
+        sprite.skew = self.skew;
 
         sprite.get_visible_bounds(transform, asset_store)
     }
@@ -1534,6 +2588,29 @@ impl ToDrawable for Aseprite {
     }
 }
 
+impl ToDrawable for NineSlice {
+    fn get_visible_bounds(
+        &self,
+        transform: &Transform,
+        _asset_store: &mut AssetEngine,
+    ) -> Option<Rectangle> {
+        // Rotated nine-slices can cover more than their bounds.
+        if self.rotation != 0.0 {
+            return None;
+        }
+
+        Some(self.bounds(&transform.translation))
+    }
+
+    fn z_index(&self) -> f32 {
+        self.z_index
+    }
+
+    fn get_type(&self) -> DrawableType {
+        DrawableType::NineSlice
+    }
+}
+
 impl ToDrawable for Sprite {
     fn get_visible_bounds(
         &self,
@@ -1559,7 +2636,15 @@ impl ToDrawable for Sprite {
         bounds.width *= self.scale.x;
         bounds.height *= self.scale.y;
 
-        if self.centered {
+        // Skewed sprites lean outside of their bounds.
+        if self.skew != Vector2::new(0.0, 0.0) {
+            return None;
+        }
+
+        if let Some(pivot) = self.placement().pivot() {
+            bounds.x -= bounds.width * pivot.x;
+            bounds.y -= bounds.height * pivot.y;
+        } else if self.centered {
             bounds.x -= bounds.width as f32 / 2.0;
             bounds.y -= bounds.height as f32 / 2.0;
         }
@@ -1582,6 +2667,10 @@ impl ToDrawable for UIButton {
         transform: &Transform,
         asset_store: &mut AssetEngine,
     ) -> Option<Rectangle> {
+        if let Some(nine_slice) = self.current_nine_slice() {
+            return nine_slice.get_visible_bounds(transform, asset_store);
+        }
+
         let sprite = Sprite::from_texture(self.current_texture().clone());
         sprite.get_visible_bounds(transform, asset_store)
     }
@@ -1630,6 +2719,42 @@ impl ToDrawable for ColorRect {
     }
 }
 
+impl ToDrawable for ParticleEmitter {
+    fn get_visible_bounds(
+        &self,
+        _transform: &Transform,
+        _asset_store: &mut AssetEngine,
+    ) -> Option<Rectangle> {
+        None
+    }
+
+    fn z_index(&self) -> f32 {
+        self.z_index
+    }
+
+    fn get_type(&self) -> DrawableType {
+        DrawableType::ParticleEmitter
+    }
+}
+
+impl ToDrawable for VectorShape {
+    fn get_visible_bounds(
+        &self,
+        _transform: &Transform,
+        _asset_store: &mut AssetEngine,
+    ) -> Option<Rectangle> {
+        None
+    }
+
+    fn z_index(&self) -> f32 {
+        self.z_index
+    }
+
+    fn get_type(&self) -> DrawableType {
+        DrawableType::VectorShape
+    }
+}
+
 impl ToDrawable for Label {
     fn get_visible_bounds(
         &self,
@@ -1651,45 +2776,78 @@ impl ToDrawable for Label {
 pub(crate) enum DrawableType {
     Aseprite,
     Sprite,
+    NineSlice,
     Tilemap,
     Autotilemap,
     ColorRect,
     UIButton,
     ColorTri,
     Label,
+    ParticleEmitter,
+    VectorShape,
 }
 
 pub(crate) struct DrawCommand {
     pub drawable_type: DrawableType,
     pub entity: Entity,
     pub z_index: f32,
+    /// Vertical translation of the entity, used to sort y-sorted layers.
+    pub y: f32,
+}
+
+fn sort_draw_commands(draw_queue: &mut [DrawCommand], sort_mode: LayerSortMode) {
+    match sort_mode {
+        LayerSortMode::ZIndex => {
+            draw_queue.sort_by(|a, b| a.z_index.partial_cmp(&b.z_index).unwrap())
+        }
+        LayerSortMode::YSort => draw_queue.sort_by(|a, b| {
+            a.z_index
+                .partial_cmp(&b.z_index)
+                .unwrap()
+                .then_with(|| b.y.total_cmp(&a.y))
+        }),
+    }
 }
 
-struct DrawCommandAdder {
+struct DrawCommandAdder<'a> {
     /// Bounds for culling checks, or None if no culling checks should be
     /// performed.
     camera_bounds: Option<Rectangle>,
-    camera: Camera,
-    camera_transform: Transform,
+    layers: &'a RenderLayers,
+    /// Only drawables on this layer are added.
+    layer_index: usize,
 }
 
-impl DrawCommandAdder {
-    fn new(engine: &RenderingEngine, world: &World) -> Self {
-        let (camera, camera_transform) = get_camera_and_camera_transform(world);
-        let camera_bounds = if engine.settings.frustrum_culling {
-            let screen_size = (
-                engine.active_size.width as f32,
-                engine.active_size.height as f32,
-            );
+impl<'a> DrawCommandAdder<'a> {
+    fn new(
+        settings: &RenderSettings,
+        camera_transform: &Transform,
+        view: &ViewTransform,
+        visible_area: &Rectangle,
+        layers: &'a RenderLayers,
+        layer_index: usize,
+    ) -> Self {
+        let camera_bounds = if settings.frustrum_culling {
+            // Bounds of the visible corners in the world, so zoomed and rotated views are culled correctly.
+            let corners = [
+                Vector2::new(visible_area.left(), visible_area.bottom()),
+                Vector2::new(visible_area.right(), visible_area.bottom()),
+                Vector2::new(visible_area.right(), visible_area.top()),
+                Vector2::new(visible_area.left(), visible_area.top()),
+            ]
+            .map(|corner| view.inverse(corner));
+            let (mut min, mut max) = (corners[0], corners[0]);
+            for corner in &corners[1..] {
+                min = min.inf(corner);
+                max = max.sup(corner);
+            }
 
-            let mut camera_view_region = Rectangle::new(
-                camera_transform.translation.x - screen_size.0 / 2.0,
-                camera_transform.translation.y - screen_size.1 / 2.0,
-                screen_size.0,
-                screen_size.1,
+            let camera_view_region = Rectangle::new(
+                camera_transform.translation.x + min.x,
+                camera_transform.translation.y + min.y,
+                max.x - min.x,
+                max.y - min.y,
             );
-            camera_view_region.width *= camera.zoom;
-            camera_view_region.height *= camera.zoom;
 
             Some(camera_view_region)
         } else {
@@ -1697,25 +2855,28 @@ impl DrawCommandAdder {
         };
 
         Self {
-            camera,
-            camera_transform,
             camera_bounds,
+            layers,
+            layer_index,
         }
     }
 
-    fn add_draw_commands<'a, D>(
+    fn add_draw_commands<'w, D>(
         &self,
         draw_queue: &mut Vec<DrawCommand>,
-        world: &'a World,
+        world: &'w World,
         asset_store: &mut AssetEngine,
     ) where
-        D: hecs::Component + ToDrawable + 'a,
+        D: hecs::Component + ToDrawable + 'w,
     {
         draw_queue.extend(
             world
-                .query::<(&D, &Transform)>()
+                .query::<(&D, &Transform, Option<&RenderLayer>)>()
                 .into_iter()
-                .filter(|(_entity, (to_drawable, transform))| {
+                .filter(|(_entity, (_, _, render_layer))| {
+                    self.layers.resolve(*render_layer) == Some(self.layer_index)
+                })
+                .filter(|(_entity, (to_drawable, transform, _))| {
                     if let Some(camera_bounds) = self.camera_bounds {
                         if let Some(drawable_bounds) =
                             to_drawable.get_visible_bounds(transform, asset_store)
@@ -1726,10 +2887,11 @@ impl DrawCommandAdder {
 
                     true
                 })
-                .map(|(entity, (to_drawable, transform))| DrawCommand {
+                .map(|(entity, (to_drawable, transform, _))| DrawCommand {
                     drawable_type: to_drawable.get_type(),
                     entity,
                     z_index: to_drawable.z_index(),
+                    y: transform.translation.y,
                 }),
         );
     }
@@ -1,14 +1,25 @@
-use rapier2d::{
-    na::Vector2,
-    prelude::{ConvexPolygon, RigidBodyHandle},
-};
+use rapier2d::{na::Vector2, prelude::ConvexPolygon};
 
 use crate::{
-    game_engine::GameEngineContext, rendering_engine::RenderingEngine, texture::TextureKey,
-    AssetEngine, Color, EmeraldError, Transform, World,
+    game_engine::GameEngineContext,
+    material::MaterialKey,
+    post_process::PostProcessPass,
+    readback::CapturedImage,
+    render_settings::{ScalingMode, ScreenScaling},
+    rendering_engine::RenderingEngine,
+    texture::{SamplerOptions, TextureKey},
+    texture_atlas::TextureAtlasStats,
+    AssetEngine, Color, EmeraldError, Rectangle, Transform, World,
 };
 
-use super::components::{ColorRect, ColorTri, Label, Sprite};
+use super::{
+    components::{
+        fill_polygon, get_active_camera_view, ColorRect, ColorTri, Label, LineCap, Sprite,
+        VectorShape, VectorShapeKind,
+    },
+    physics_debug::{collect_debug_lines, line_triangles, PhysicsDebugSettings},
+    viewport::Viewport,
+};
 
 pub struct RenderingHandler<'c> {
     asset_engine: &'c mut AssetEngine,
@@ -43,72 +54,75 @@ impl<'c> RenderingHandler<'c> {
             .draw_world_with_transform(world, transform, &mut self.asset_engine)
     }
 
+    /// Draws the world through the camera of the viewport, clipped to its area of the screen,
+    /// or of the active render texture.
+    pub fn draw_world_in_viewport(
+        &mut self,
+        world: &mut World,
+        viewport: &Viewport,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine
+            .draw_world_in_viewport(world, viewport, self.asset_engine)
+    }
+
+    /// Draws the world once through each of the viewports, ex. for split-screen.
+    pub fn draw_viewports(
+        &mut self,
+        world: &mut World,
+        viewports: &[Viewport],
+    ) -> Result<(), EmeraldError> {
+        for viewport in viewports {
+            self.draw_world_in_viewport(world, viewport)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the outlines of every collider in the world in the given color.
     pub fn draw_colliders(
         &mut self,
         world: &mut World,
         color: crate::Color,
     ) -> Result<(), EmeraldError> {
-        if world.physics_engine.is_none() {
-            return Ok(());
-        }
+        self.draw_physics_debug(world, &PhysicsDebugSettings::shapes_only(color))
+    }
 
-        let mut color_rect = ColorRect {
-            color,
-            ..Default::default()
+    /// Draws the debug view of the world's physics, see `PhysicsDebugSettings` for what can be drawn.
+    pub fn draw_physics_debug(
+        &mut self,
+        world: &mut World,
+        settings: &PhysicsDebugSettings,
+    ) -> Result<(), EmeraldError> {
+        let physics_engine = match world.physics_engine.as_ref() {
+            Some(physics_engine) => physics_engine,
+            None => return Ok(()),
         };
 
-        let camera_transform = if let Some(e) = world.get_active_camera() {
-            world.get::<&mut Transform>(e)?.clone()
-        } else {
-            Default::default()
-        };
+        let (camera_translation, view) = get_active_camera_view(world);
+        let camera_offset = Vector2::new(camera_translation.x, camera_translation.y);
+        // Keep lines the same width on screen regardless of zoom.
+        let line_width = settings.line_width / view.zoom;
 
-        for (_id, (body_handle, transform)) in
-            world.inner.query::<(&RigidBodyHandle, &Transform)>().iter()
-        {
-            if let Some(body) = world
-                .physics_engine
-                .as_ref()
-                .unwrap()
-                .bodies
-                .get(*body_handle)
-            {
-                for collider_handle in body.colliders() {
-                    if let Some(collider) = world
-                        .physics_engine
-                        .as_ref()
-                        .unwrap()
-                        .colliders
-                        .get(*collider_handle)
-                    {
-                        let offset = collider.translation() - body.translation();
-                        let collider_transform = transform.clone()
-                            + Transform::from_translation((offset.x, offset.y))
-                            - camera_transform;
-
-                        if let Some(polygon) = collider.shape().as_convex_polygon() {
-                            self.draw_convex_polygon(&color, &polygon, &collider_transform)?;
-                            continue;
-                        }
-
-                        if let Some(cuboid) = collider.shape().as_cuboid() {
-                            color_rect.width = cuboid.half_extents.x as u32 * 2;
-                            color_rect.height = cuboid.half_extents.y as u32 * 2;
-                            self.draw_color_rect(&color_rect, &collider_transform)?;
-                            continue;
-                        }
-
-                        // If we dont have specific logic for the shape let's just draw its AABB
-                        let aabb = collider.compute_aabb();
-                        color_rect.width = aabb.half_extents().x as u32 * 2;
-                        color_rect.height = aabb.half_extents().y as u32 * 2;
-                        self.draw_color_rect(&color_rect, &collider_transform)?;
+        self.rendering_engine.set_view(view);
+        let result = collect_debug_lines(physics_engine, settings)
+            .into_iter()
+            .try_for_each(|line| {
+                if let Some(triangles) = line_triangles(
+                    line.start - camera_offset,
+                    line.end - camera_offset,
+                    line_width,
+                ) {
+                    for points in triangles {
+                        let color_tri = ColorTri::new(line.color, points);
+                        self.draw_color_tri(&color_tri, &Transform::default())?;
                     }
                 }
-            }
-        }
 
-        Ok(())
+                Ok(())
+            });
+        self.rendering_engine.reset_view();
+
+        result
     }
 
     pub fn draw_sprite(
@@ -142,37 +156,223 @@ impl<'c> RenderingHandler<'c> {
         convex_polygon: &ConvexPolygon,
         transform: &Transform,
     ) -> Result<(), EmeraldError> {
-        let vertices = convex_polygon
+        let points = convex_polygon
             .points()
             .iter()
-            .flat_map(|p| [p.x as f64, p.y as f64])
-            .collect::<Vec<f64>>();
-        let triangles = emd_earcutr::earcut(&vertices, &vec![], 2);
-        for tri in triangles.chunks_exact(3) {
-            let i1 = tri[0] * 2;
-            let i2 = tri[1] * 2;
-            let i3 = tri[2] * 2;
-            let color_tri = ColorTri::new(
-                color.clone(),
-                [
-                    Vector2::new(vertices[i1] as f32, vertices[i1 + 1] as f32),
-                    Vector2::new(vertices[i2] as f32, vertices[i2 + 1] as f32),
-                    Vector2::new(vertices[i3] as f32, vertices[i3 + 1] as f32),
-                ],
-            );
-            self.draw_color_tri(&color_tri, transform)?;
+            .map(|point| Vector2::new(point.x, point.y))
+            .collect::<Vec<_>>();
+        for points in fill_polygon(&points) {
+            self.draw_color_tri(&ColorTri::new(*color, points), transform)?;
         }
 
         Ok(())
     }
 
+    /// Fills a polygon, which may be concave as long as its outline doesn't cross itself.
+    pub fn draw_polygon(
+        &mut self,
+        points: &[Vector2<f32>],
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        self.draw_vector_shape(&VectorShape::polygon(points.to_vec(), color), transform)
+    }
+
+    pub fn draw_vector_shape(
+        &mut self,
+        vector_shape: &VectorShape,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine
+            .draw_vector_shape(self.asset_engine, vector_shape, transform)
+    }
+
+    pub fn draw_line(
+        &mut self,
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        thickness: f32,
+        cap: LineCap,
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        let line = VectorShape::line(start, end, thickness, color).with_cap(cap);
+        self.draw_vector_shape(&line, transform)
+    }
+
+    /// Draws lines between consecutive points, and from the last point to the first if closed.
+    pub fn draw_polyline(
+        &mut self,
+        points: &[Vector2<f32>],
+        closed: bool,
+        thickness: f32,
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        let polyline = VectorShape::polyline(points.to_vec(), closed, thickness, color);
+        self.draw_vector_shape(&polyline, transform)
+    }
+
+    /// Draws a filled circle, or an outline of the given thickness if `filled` is false.
+    pub fn draw_circle(
+        &mut self,
+        center: Vector2<f32>,
+        radius: f32,
+        filled: bool,
+        thickness: f32,
+        segments: u32,
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        let mut circle = VectorShape::new(VectorShapeKind::Circle { radius, filled }, color)
+            .with_thickness(thickness);
+        circle.segments = segments;
+        circle.offset = center;
+        self.draw_vector_shape(&circle, transform)
+    }
+
+    /// Draws part of a circle's outline, angles are in radians counter-clockwise from the x axis.
+    pub fn draw_arc(
+        &mut self,
+        center: Vector2<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        thickness: f32,
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        let mut arc = VectorShape::arc(radius, start_angle, end_angle, thickness, color);
+        arc.offset = center;
+        self.draw_vector_shape(&arc, transform)
+    }
+
+    /// Draws the outline of a rectangle, with the stroke centered on its edges.
+    pub fn draw_rect_outline(
+        &mut self,
+        rect: &Rectangle,
+        thickness: f32,
+        color: Color,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        let mut outline = VectorShape::rect_outline(rect.width, rect.height, thickness, color);
+        outline.offset = Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+        self.draw_vector_shape(&outline, transform)
+    }
+
     pub fn draw_color_rect(
         &mut self,
         color_rect: &ColorRect,
         transform: &Transform,
     ) -> Result<(), EmeraldError> {
         self.rendering_engine
-            .draw_color_rect(&mut self.asset_engine, color_rect, transform)
+            .draw_color_rect(&mut self.asset_engine, color_rect, None, transform)
+    }
+
+    /// Draws the color rect with a custom shader, instead of the default textured shader.
+    pub fn draw_color_rect_with_material(
+        &mut self,
+        color_rect: &ColorRect,
+        material_key: &MaterialKey,
+        transform: &Transform,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine.draw_color_rect(
+            self.asset_engine,
+            color_rect,
+            Some(material_key),
+            transform,
+        )
+    }
+
+    /// Overwrites the start of the material's uniform block with the given values,
+    /// ex. to update the time or flash amount used by its shader.
+    pub fn set_material_uniforms(
+        &mut self,
+        material_key: &MaterialKey,
+        uniforms: &[f32],
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine
+            .set_material_uniforms(material_key, uniforms, self.asset_engine)
+    }
+
+    /// Changes how the game is fit into the window, taking effect from the next frame.
+    pub fn set_scaling_mode(&mut self, scaling_mode: ScalingMode) {
+        self.rendering_engine.settings.scaling_mode = scaling_mode;
+    }
+
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.rendering_engine.settings.scaling_mode
+    }
+
+    /// Where the virtual screen is drawn in the window,
+    /// or `None` if the game is drawn at the size of the window.
+    pub fn screen_scaling(&self) -> Option<ScreenScaling> {
+        self.rendering_engine.screen_scaling()
+    }
+
+    /// Adds a full-screen pass to the end of the post-processing stack, returning its index.
+    /// Passes run in order over the frame whenever it is rendered to the screen.
+    pub fn add_post_process(&mut self, pass: PostProcessPass) -> Result<usize, EmeraldError> {
+        self.rendering_engine
+            .add_post_process(pass, self.asset_engine)
+    }
+
+    pub fn post_process(&self, index: usize) -> Option<&PostProcessPass> {
+        self.rendering_engine.post_process.get(index)
+    }
+
+    /// Changes to the pass are applied the next time the frame is rendered.
+    pub fn post_process_mut(&mut self, index: usize) -> Option<&mut PostProcessPass> {
+        self.rendering_engine.post_process.get_mut(index)
+    }
+
+    pub fn set_post_process_enabled(
+        &mut self,
+        index: usize,
+        enabled: bool,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine
+            .post_process
+            .set_enabled(index, enabled)
+    }
+
+    /// Removes a pass, shifting the indices of the passes after it.
+    pub fn remove_post_process(&mut self, index: usize) -> Result<PostProcessPass, EmeraldError> {
+        self.rendering_engine.post_process.remove(index)
+    }
+
+    pub fn clear_post_process(&mut self) {
+        self.rendering_engine.post_process.clear();
+    }
+
+    /// Packs the textures into the pages of the texture atlas,
+    /// so sprites using any of them can be drawn in the same batch.
+    /// Their keys are unchanged, drawing them uses the atlas from then on.
+    /// Textures too large for the atlas and render textures are left as they are.
+    /// Returns the number of textures that were packed.
+    pub fn pack_textures(&mut self, textures: &[TextureKey]) -> Result<usize, EmeraldError> {
+        self.rendering_engine
+            .pack_textures(textures, self.asset_engine)
+    }
+
+    pub fn texture_atlas_stats(&self) -> TextureAtlasStats {
+        self.rendering_engine.texture_atlas_stats()
+    }
+
+    /// Removes every page of the texture atlas, textures are drawn on their own again.
+    pub fn clear_texture_atlas(&mut self) {
+        self.rendering_engine.clear_texture_atlas();
+    }
+
+    /// Changes how the texture is filtered and wrapped when drawn.
+    /// A texture packed into the atlas is drawn from its own texture from then on.
+    pub fn set_texture_sampler(
+        &mut self,
+        texture_key: &TextureKey,
+        sampler: SamplerOptions,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine
+            .set_texture_sampler(texture_key, sampler, self.asset_engine)
     }
 
     /// Begin drawing to the screen
@@ -191,6 +391,21 @@ impl<'c> RenderingHandler<'c> {
         self.rendering_engine.render(&mut self.asset_engine)
     }
 
+    /// Draws the queued frame offscreen instead of to the window, and returns its RGBA pixels.
+    /// Use in place of `render`, the captured frame is not presented.
+    pub fn capture_frame(&mut self) -> Result<CapturedImage, EmeraldError> {
+        self.rendering_engine.capture_frame(self.asset_engine)
+    }
+
+    /// Returns the RGBA pixels of a render texture, or of any other texture.
+    pub fn read_render_texture(
+        &mut self,
+        texture_key: &TextureKey,
+    ) -> Result<CapturedImage, EmeraldError> {
+        self.rendering_engine
+            .read_render_texture(texture_key, self.asset_engine)
+    }
+
     /// Commit all drawings to the active key
     pub fn render_texture(&mut self) -> Result<(), EmeraldError> {
         self.rendering_engine.render_texture(&mut self.asset_engine)
@@ -1 +1,4 @@
 pub(crate) mod textured_quad;
+
+/// Vertex stage and texture bindings shared by every textured tri shader, including materials.
+pub(crate) const PRELUDE: &str = include_str!("./shaders/prelude.wgsl");
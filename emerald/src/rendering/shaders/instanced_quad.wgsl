@@ -0,0 +1,39 @@
+struct InstanceInput {
+    @location(1) center: vec2<f32>,
+    @location(2) axis_x: vec2<f32>,
+    @location(3) axis_y: vec2<f32>,
+    @location(4) uv_rect: vec4<f32>,
+    @location(5) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color_tint: vec4<f32>,
+}
+
+// The corner is a point of the unit quad, from -0.5 to 0.5 on both axes with y pointing up.
+@vertex
+fn vs_main(
+    @location(0) corner: vec2<f32>,
+    in: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let position = in.center + in.axis_x * corner.x + in.axis_y * corner.y;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    // Texture coordinates have y pointing down, from the top left corner of the uv rect.
+    out.tex_coords = in.uv_rect.xy + vec2<f32>(corner.x + 0.5, 0.5 - corner.y) * in.uv_rect.zw;
+    out.color_tint = in.color;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0) @binding(1)
+var s_diffuse: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return color * in.color_tint;
+}
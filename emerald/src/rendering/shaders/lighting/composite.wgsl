@@ -0,0 +1,5 @@
+// Drawn with a multiplying blend state, darkening the frame by the light accumulation target.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+}
@@ -0,0 +1,65 @@
+// Draws lights additively into the light accumulation target.
+// Shadows are drawn first with the same vertices, marking the stencil buffer where the light is blocked.
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) light_offset: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) params: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Offset from the light in pixels, with the height of the light in z.
+    @location(0) light_offset: vec3<f32>,
+    // Linear color, with the intensity in alpha.
+    @location(1) color: vec4<f32>,
+    // Radius, falloff, spot direction, and the cosine of the spot cone's edge.
+    @location(2) params: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var t_normals: texture_2d<f32>;
+
+@vertex
+fn vs_main(
+    in: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
+    out.light_offset = in.light_offset;
+    out.color = in.color;
+    out.params = in.params;
+    return out;
+}
+
+@fragment
+fn fs_shadow(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = in.params.x;
+    let distance = length(in.light_offset.xy);
+    if (distance >= radius) {
+        discard;
+    }
+    var attenuation = pow(1.0 - distance / radius, in.params.y);
+
+    // Spot lights fade over the outer quarter of their cone, point lights have an edge below -1.
+    let cone_edge = in.params.w;
+    if (distance > 0.0) {
+        let direction = vec2<f32>(cos(in.params.z), sin(in.params.z));
+        let cone = dot(-in.light_offset.xy / distance, direction);
+        attenuation = attenuation * clamp((cone - cone_edge) / max(1.0 - cone_edge, 0.0001) * 4.0, 0.0, 1.0);
+    }
+
+    // The alpha of the normal target marks where a normal map was drawn.
+    let normal_sample = textureLoad(t_normals, vec2<i32>(in.clip_position.xy), 0);
+    let normal = normalize(normal_sample.xyz * 2.0 - 1.0);
+    let to_light = normalize(in.light_offset);
+    let shading = mix(1.0, max(dot(normal, to_light), 0.0), normal_sample.a);
+
+    return vec4<f32>(in.color.rgb * in.color.a * attenuation * shading, 1.0);
+}
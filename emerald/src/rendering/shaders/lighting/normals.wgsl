@@ -0,0 +1,12 @@
+// Textures are decoded from sRGB when sampled, so normal maps are encoded again to keep their stored values.
+fn to_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return vec4<f32>(to_srgb(normal.rgb), normal.a * in.color_tint.a);
+}
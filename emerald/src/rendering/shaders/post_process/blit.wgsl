@@ -0,0 +1,4 @@
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_screen, s_screen, in.tex_coords);
+}
@@ -0,0 +1,34 @@
+struct Params {
+    // width, height, 1 / width, 1 / height
+    screen: vec4<f32>,
+    // threshold, intensity, radius
+    settings: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Params;
+
+fn bright_part(uv: vec2<f32>) -> vec3<f32> {
+    let color = textureSampleLevel(t_screen, s_screen, uv, 0.0).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    return color * max(brightness - params.settings.x, 0.0) / max(brightness, 0.0001);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.tex_coords);
+    let texel_step = params.settings.z / 3.0 * params.screen.zw;
+
+    // Gaussian blur of the bright parts of the frame.
+    var glow = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var x = -3; x <= 3; x = x + 1) {
+        for (var y = -3; y <= 3; y = y + 1) {
+            let weight = exp(-f32(x * x + y * y) / 4.5);
+            glow = glow + bright_part(in.tex_coords + vec2<f32>(f32(x), f32(y)) * texel_step) * weight;
+            total = total + weight;
+        }
+    }
+
+    return vec4<f32>(color.rgb + glow / total * params.settings.y, color.a);
+}
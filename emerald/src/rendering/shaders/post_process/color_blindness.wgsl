@@ -0,0 +1,22 @@
+struct Params {
+    // width, height, 1 / width, 1 / height
+    screen: vec4<f32>,
+    // Rows of the simulation matrix, the w of the first row is the strength.
+    red: vec4<f32>,
+    green: vec4<f32>,
+    blue: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Params;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.tex_coords);
+    let simulated = vec3<f32>(
+        dot(color.rgb, params.red.xyz),
+        dot(color.rgb, params.green.xyz),
+        dot(color.rgb, params.blue.xyz),
+    );
+    return vec4<f32>(mix(color.rgb, simulated, params.red.w), color.a);
+}
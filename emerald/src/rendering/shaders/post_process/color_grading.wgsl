@@ -0,0 +1,42 @@
+struct Params {
+    // width, height, 1 / width, 1 / height
+    screen: vec4<f32>,
+    // exposure, contrast, saturation, lut intensity
+    grading: vec4<f32>,
+    tint: vec4<f32>,
+    // lut size
+    lut: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Params;
+@group(1) @binding(1)
+var t_lut: texture_2d<f32>;
+@group(1) @binding(2)
+var s_lut: sampler;
+
+// The lut is a strip of square slices, red increases to the right within a slice,
+// green increases downwards and blue increases from one slice to the next.
+fn sample_lut(color: vec3<f32>) -> vec3<f32> {
+    let size = params.lut.x;
+    let blue = color.b * (size - 1.0);
+    let slice = floor(blue);
+    let next_slice = min(slice + 1.0, size - 1.0);
+    let x = (color.r * (size - 1.0) + 0.5) / (size * size);
+    let y = (color.g * (size - 1.0) + 0.5) / size;
+    let a = textureSampleLevel(t_lut, s_lut, vec2<f32>(x + slice / size, y), 0.0).rgb;
+    let b = textureSampleLevel(t_lut, s_lut, vec2<f32>(x + next_slice / size, y), 0.0).rgb;
+    return mix(a, b, blue - slice);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.tex_coords);
+    var rgb = color.rgb * params.grading.x;
+    rgb = (rgb - 0.5) * params.grading.y + 0.5;
+    let luma = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    rgb = mix(vec3<f32>(luma), rgb, params.grading.z);
+    rgb = clamp(rgb * params.tint.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    rgb = mix(rgb, sample_lut(rgb), params.grading.w);
+    return vec4<f32>(rgb, color.a);
+}
@@ -0,0 +1,28 @@
+struct Params {
+    // width, height, 1 / width, 1 / height
+    screen: vec4<f32>,
+    // scanline intensity, scanline count, curvature, chromatic aberration
+    settings: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Params;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Bulge the screen outwards like the glass of a CRT.
+    let centered = in.tex_coords * 2.0 - 1.0;
+    let curved = centered + centered * (centered.yx * centered.yx) * params.settings.z;
+    let uv = curved * 0.5 + 0.5;
+
+    let aberration = vec2<f32>(params.settings.w * params.screen.z, 0.0);
+    let color = textureSample(t_screen, s_screen, uv);
+    let r = textureSample(t_screen, s_screen, uv + aberration).r;
+    let b = textureSample(t_screen, s_screen, uv - aberration).b;
+
+    let scanline = sin(uv.y * params.settings.y * 3.14159265) * 0.5 + 0.5;
+    let brightness = 1.0 - params.settings.x * (1.0 - scanline);
+
+    let inside = step(0.0, uv.x) * step(uv.x, 1.0) * step(0.0, uv.y) * step(uv.y, 1.0);
+    return vec4<f32>(vec3<f32>(r, color.g, b) * brightness * inside, color.a);
+}
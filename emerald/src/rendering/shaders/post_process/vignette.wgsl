@@ -0,0 +1,22 @@
+struct Params {
+    // width, height, 1 / width, 1 / height
+    screen: vec4<f32>,
+    // intensity, radius, softness
+    settings: vec4<f32>,
+    color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Params;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.tex_coords);
+    var centered = in.tex_coords - 0.5;
+    centered.x = centered.x * params.screen.x / params.screen.y;
+    let dist = length(centered);
+    let amount = smoothstep(params.settings.y, params.settings.y + params.settings.z, dist)
+        * params.settings.x
+        * params.color.a;
+    return vec4<f32>(mix(color.rgb, params.color.rgb, amount), color.a);
+}
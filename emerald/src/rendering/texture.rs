@@ -8,11 +8,125 @@ use crate::{
 };
 pub const EMERALD_DEFAULT_TEXTURE_NAME: &str = "emerald_default_texture";
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Keeps pixels sharp, for pixel art.
+    Nearest,
+    /// Blends neighbouring pixels, for smooth art.
+    Linear,
+}
+impl From<TextureFilter> for wgpu::FilterMode {
+    fn from(filter: TextureFilter) -> Self {
+        match filter {
+            TextureFilter::Nearest => wgpu::FilterMode::Nearest,
+            TextureFilter::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+/// How texture coordinates outside of 0..1 are sampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureWrap {
+    ClampToEdge,
+    /// Tiles the texture, so a sprite target larger than the texture draws it repeatedly.
+    Repeat,
+    MirrorRepeat,
+}
+impl From<TextureWrap> for wgpu::AddressMode {
+    fn from(wrap: TextureWrap) -> Self {
+        match wrap {
+            TextureWrap::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            TextureWrap::Repeat => wgpu::AddressMode::Repeat,
+            TextureWrap::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// How a texture is sampled when drawn, see `RenderingHandler::set_texture_sampler`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SamplerOptions {
+    pub mag_filter: TextureFilter,
+    pub min_filter: TextureFilter,
+    /// How neighbouring mip levels are blended, only used by textures with mipmaps.
+    pub mipmap_filter: TextureFilter,
+    pub wrap_u: TextureWrap,
+    pub wrap_v: TextureWrap,
+}
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        SamplerOptions {
+            mag_filter: TextureFilter::Nearest,
+            min_filter: TextureFilter::Nearest,
+            mipmap_filter: TextureFilter::Nearest,
+            wrap_u: TextureWrap::ClampToEdge,
+            wrap_v: TextureWrap::ClampToEdge,
+        }
+    }
+}
+impl SamplerOptions {
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.mag_filter = filter;
+        self.min_filter = filter;
+        self.mipmap_filter = filter;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: TextureWrap) -> Self {
+        self.wrap_u = wrap;
+        self.wrap_v = wrap;
+        self
+    }
+
+    fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            address_mode_u: self.wrap_u.into(),
+            address_mode_v: self.wrap_v.into(),
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: self.mag_filter.into(),
+            min_filter: self.min_filter.into(),
+            mipmap_filter: self.mipmap_filter.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options for loading a texture, see `AssetLoader::texture_with_options`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub sampler: SamplerOptions,
+    /// Generates a chain of downscaled copies, so the texture doesn't shimmer when drawn small.
+    pub mipmaps: bool,
+}
+impl TextureOptions {
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.sampler = self.sampler.with_filter(filter);
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: TextureWrap) -> Self {
+        self.sampler = self.sampler.with_wrap(wrap);
+        self
+    }
+
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+}
+
+/// The number of levels in a full mip chain, down to a single pixel.
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
 pub(crate) struct Texture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
     pub size: wgpu::Extent3d,
+    pub format: wgpu::TextureFormat,
+    pub usage: wgpu::TextureUsages,
+    pub options: TextureOptions,
 }
 impl Texture {
     pub fn new(
@@ -34,8 +148,11 @@ impl Texture {
             width,
             height,
             data,
-            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
             wgpu::TextureFormat::Rgba8UnormSrgb,
+            TextureOptions::default(),
         )
     }
 
@@ -61,12 +178,14 @@ impl Texture {
             data,
             wgpu::TextureUsages::TEXTURE_BINDING
                 | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
                 | wgpu::TextureUsages::RENDER_ATTACHMENT,
             format,
+            TextureOptions::default(),
         )
     }
 
-    fn new_ext(
+    pub(crate) fn new_ext(
         label: &str,
         bind_group_layouts: &BindGroupLayouts,
         asset_engine: &mut AssetEngine,
@@ -77,16 +196,23 @@ impl Texture {
         data: &[u8],
         usage: wgpu::TextureUsages,
         format: wgpu::TextureFormat,
+        options: TextureOptions,
     ) -> Result<TextureKey, EmeraldError> {
         let size = wgpu::Extent3d {
             width,
             height,
             depth_or_array_layers: 1,
         };
+        // Mip levels are generated from rgba data, other formats only have the full sized level.
+        let mip_level_count = if options.mipmaps && format == wgpu::TextureFormat::Rgba8UnormSrgb {
+            mip_level_count(width, height)
+        } else {
+            1
+        };
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some(label),
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
@@ -94,71 +220,138 @@ impl Texture {
         });
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let sampler = device.create_sampler(&options.sampler.descriptor());
 
         let texture = Self {
             texture,
             view,
             sampler,
             size,
+            format,
+            usage,
+            options,
         };
 
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture.texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            data,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: std::num::NonZeroU32::new(4 * width),
-                rows_per_image: std::num::NonZeroU32::new(height),
-            },
-            texture.size,
-        );
+        texture.write_level(queue, 0, width, height, data);
+        if mip_level_count > 1 {
+            let mut level =
+                image::RgbaImage::from_raw(width, height, data.to_vec()).ok_or_else(|| {
+                    EmeraldError::new(format!(
+                        "Unable to generate mipmaps for {:?}, its data does not match its size.",
+                        label
+                    ))
+                })?;
+            for mip_level in 1..mip_level_count {
+                let (width, height) = ((width >> mip_level).max(1), (height >> mip_level).max(1));
+                level = image::imageops::resize(
+                    &level,
+                    width,
+                    height,
+                    image::imageops::FilterType::Triangle,
+                );
+                texture.write_level(queue, mip_level, width, height, &level);
+            }
+        }
 
+        let texture_bind_group = texture.bind_group(label, bind_group_layouts, device)?;
+        let cached_size = (texture.size.width, texture.size.height);
+
+        let bind_group_key =
+            asset_engine.add_asset_with_label(Box::new(texture_bind_group), label)?;
+        let asset_key = asset_engine.add_asset_with_label(Box::new(texture), label)?;
+        Ok(TextureKey::new(
+            label,
+            cached_size,
+            asset_key,
+            bind_group_key,
+        ))
+    }
+
+    fn bind_group(
+        &self,
+        label: &str,
+        bind_group_layouts: &BindGroupLayouts,
+        device: &wgpu::Device,
+    ) -> Result<BindGroup, EmeraldError> {
         if let Some(texture_bind_group_layout) =
             bind_group_layouts.get(&BindGroupLayoutId::TextureQuad)
         {
-            let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            return Ok(device.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &texture_bind_group_layout,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                        resource: wgpu::BindingResource::TextureView(&self.view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
                     },
                 ],
                 label: Some(&format!("{:?}_group", label)),
-            });
-            let cached_size = (texture.size.width, texture.size.height);
-
-            let bind_group_key =
-                asset_engine.add_asset_with_label(Box::new(texture_bind_group), label)?;
-            let asset_key = asset_engine.add_asset_with_label(Box::new(texture), label)?;
-            return Ok(TextureKey::new(
-                label,
-                cached_size,
-                asset_key,
-                bind_group_key,
-            ));
+            }));
         }
-        return Err(EmeraldError::new(
+        Err(EmeraldError::new(
             "Unable to get TextureQuad bind group layout",
-        ));
+        ))
+    }
+
+    fn write_level(
+        &self,
+        queue: &wgpu::Queue,
+        mip_level: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture: &self.texture,
+                mip_level,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(4 * width),
+                rows_per_image: std::num::NonZeroU32::new(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Replaces the sampler of the texture, along with the bind group it is drawn with.
+    pub(crate) fn set_sampler(
+        texture_key: &TextureKey,
+        sampler: SamplerOptions,
+        bind_group_layouts: &BindGroupLayouts,
+        asset_engine: &mut AssetEngine,
+        device: &wgpu::Device,
+    ) -> Result<(), EmeraldError> {
+        let texture = asset_engine
+            .get_asset_mut::<Texture>(&texture_key.asset_key.asset_id)
+            .ok_or_else(|| {
+                EmeraldError::new(format!(
+                    "Unable to set the sampler of {:?}, it does not exist.",
+                    texture_key.label()
+                ))
+            })?;
+        texture.sampler = device.create_sampler(&sampler.descriptor());
+        texture.options.sampler = sampler;
+        let bind_group = texture.bind_group(texture_key.label(), bind_group_layouts, device)?;
+
+        if let Some(existing) =
+            asset_engine.get_asset_mut::<BindGroup>(&texture_key.bind_group_key.asset_id)
+        {
+            *existing = bind_group;
+        }
+
+        Ok(())
     }
 
     pub fn from_bytes(
@@ -168,11 +361,18 @@ impl Texture {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         bytes: &[u8],
+        options: TextureOptions,
     ) -> Result<TextureKey, EmeraldError> {
         match image::load_from_memory(bytes) {
-            Ok(img) => {
-                Self::from_image(label, bind_group_layouts, asset_store, device, queue, &img)
-            }
+            Ok(img) => Self::from_image_with_options(
+                label,
+                bind_group_layouts,
+                asset_store,
+                device,
+                queue,
+                &img,
+                options,
+            ),
             Err(e) => Err(EmeraldError::new(format!(
                 "Error loading image from memory. Texture Key: {:?} Err: {:?}",
                 label, e
@@ -187,11 +387,31 @@ impl Texture {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         img: &image::DynamicImage,
+    ) -> Result<TextureKey, EmeraldError> {
+        Self::from_image_with_options(
+            label,
+            bind_group_layouts,
+            asset_store,
+            device,
+            queue,
+            img,
+            TextureOptions::default(),
+        )
+    }
+
+    pub fn from_image_with_options(
+        label: &str,
+        bind_group_layouts: &BindGroupLayouts,
+        asset_store: &mut AssetEngine,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        options: TextureOptions,
     ) -> Result<TextureKey, EmeraldError> {
         let rgba = img.to_rgba8();
         let dimensions = img.dimensions();
 
-        Self::new(
+        Self::new_ext(
             label,
             bind_group_layouts,
             asset_store,
@@ -200,6 +420,11 @@ impl Texture {
             dimensions.0,
             dimensions.1,
             &rgba,
+            wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            options,
         )
     }
 }
@@ -254,3 +479,32 @@ impl TextureKey {
         &self.label
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_chains_end_at_a_single_pixel() {
+        assert_eq!(mip_level_count(1, 1), 1);
+        assert_eq!(mip_level_count(2, 1), 2);
+        assert_eq!(mip_level_count(256, 64), 9);
+        assert_eq!(mip_level_count(100, 300), 9);
+    }
+
+    #[test]
+    fn options_map_to_sampler_descriptors() {
+        let options = TextureOptions::default()
+            .with_filter(TextureFilter::Linear)
+            .with_wrap(TextureWrap::Repeat);
+        let descriptor = options.sampler.descriptor();
+
+        assert_eq!(descriptor.mag_filter, wgpu::FilterMode::Linear);
+        assert_eq!(descriptor.address_mode_u, wgpu::AddressMode::Repeat);
+        assert_eq!(descriptor.address_mode_v, wgpu::AddressMode::Repeat);
+        assert_eq!(
+            SamplerOptions::default().descriptor().min_filter,
+            wgpu::FilterMode::Nearest
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    asset_key::AssetId,
+    rendering_engine::BindGroupLayouts,
+    texture::{Texture, TextureKey, TextureOptions},
+    AssetEngine, EmeraldError, Rectangle,
+};
+
+const ATLAS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// How textures are packed into atlas pages, so sprites sharing a page are drawn in one batch.
+#[derive(Clone, Copy, Debug)]
+pub struct TextureAtlasSettings {
+    /// Width and height of each page, limited by what the device supports.
+    pub page_size: u32,
+    /// Empty pixels left between packed textures, so they don't bleed into each other.
+    pub padding: u32,
+    /// Textures wider or taller than this are left out of the atlas.
+    pub max_texture_size: u32,
+    /// Whether textures and aseprite frames are packed as they are loaded.
+    pub pack_on_load: bool,
+}
+impl Default for TextureAtlasSettings {
+    fn default() -> Self {
+        Self {
+            page_size: 2048,
+            padding: 1,
+            max_texture_size: 512,
+            pack_on_load: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AtlasPageStats {
+    pub size: (u32, u32),
+    pub texture_count: usize,
+    /// Pixels covered by packed textures, not counting padding.
+    pub used_area: u64,
+}
+impl AtlasPageStats {
+    /// The fraction of the page covered by packed textures.
+    pub fn occupancy(&self) -> f32 {
+        let area = self.size.0 as u64 * self.size.1 as u64;
+        if area == 0 {
+            return 0.0;
+        }
+
+        self.used_area as f32 / area as f32
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TextureAtlasStats {
+    pub pages: Vec<AtlasPageStats>,
+}
+impl TextureAtlasStats {
+    pub fn texture_count(&self) -> usize {
+        self.pages.iter().map(|page| page.texture_count).sum()
+    }
+
+    /// The fraction of all pages covered by packed textures.
+    pub fn occupancy(&self) -> f32 {
+        let area: u64 = self
+            .pages
+            .iter()
+            .map(|page| page.size.0 as u64 * page.size.1 as u64)
+            .sum();
+        if area == 0 {
+            return 0.0;
+        }
+
+        self.pages.iter().map(|page| page.used_area).sum::<u64>() as f32 / area as f32
+    }
+}
+
+/// Packs rectangles into rows of shelves, each as tall as the first rectangle placed on it.
+#[derive(Clone, Debug)]
+pub(crate) struct ShelfPacker {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+}
+#[derive(Clone, Copy, Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Where the next rectangle on the shelf starts.
+    x: u32,
+}
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Finds room for a rectangle, returning the position of its top left corner.
+    /// Uses the shelf that wastes the least height, opening a new one if none fit.
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > self.width || height > self.height {
+            return None;
+        }
+
+        let page_width = self.width;
+        if let Some(shelf) = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && page_width - shelf.x >= width)
+            .min_by_key(|shelf| shelf.height - height)
+        {
+            let position = (shelf.x, shelf.y);
+            shelf.x += width;
+            return Some(position);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map(|shelf| shelf.y + shelf.height)
+            .unwrap_or(0);
+        if y + height > self.height {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width,
+        });
+
+        Some((0, y))
+    }
+}
+
+struct AtlasPage {
+    texture: TextureKey,
+    packer: ShelfPacker,
+    texture_count: usize,
+    used_area: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AtlasEntry {
+    page: usize,
+    /// In pixels from the top left corner of the page.
+    area: Rectangle,
+}
+
+/// Textures copied into shared pages, so draws of different textures can be batched together.
+/// Packed textures keep their keys, and are drawn from their page instead.
+#[derive(Default)]
+pub(crate) struct TextureAtlas {
+    pages: Vec<AtlasPage>,
+    entries: HashMap<AssetId, AtlasEntry>,
+    /// Keeps the labels of pages unique after the atlas is cleared.
+    page_uid: usize,
+}
+impl TextureAtlas {
+    /// Copies the given textures into the atlas, adding pages as needed.
+    /// Textures that are already packed, too large, render targets, or loaded with options are skipped.
+    /// Returns the number of textures that were packed.
+    pub fn pack(
+        &mut self,
+        settings: &TextureAtlasSettings,
+        textures: &[TextureKey],
+        bind_group_layouts: &BindGroupLayouts,
+        asset_engine: &mut AssetEngine,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<usize, EmeraldError> {
+        let page_size = settings
+            .page_size
+            .min(device.limits().max_texture_dimension_2d);
+        let page_ids: HashSet<AssetId> = self
+            .pages
+            .iter()
+            .map(|page| page.texture.asset_key.asset_id)
+            .collect();
+
+        let mut candidates = Vec::new();
+        for texture_key in textures {
+            let asset_id = texture_key.asset_key.asset_id;
+            if self.entries.contains_key(&asset_id)
+                || page_ids.contains(&asset_id)
+                || candidates.iter().any(|(id, _, _)| *id == asset_id)
+            {
+                continue;
+            }
+
+            let texture = asset_engine
+                .get_asset::<Texture>(&asset_id)
+                .ok_or_else(|| {
+                    EmeraldError::new(format!(
+                        "Unable to pack texture {:?}, it was not found.",
+                        texture_key.label()
+                    ))
+                })?;
+            let (width, height) = (texture.size.width, texture.size.height);
+            // Textures sampled any other way than the pages would change how they look.
+            let packable = texture.format == ATLAS_FORMAT
+                && texture.options == TextureOptions::default()
+                && texture.usage.contains(wgpu::TextureUsages::COPY_SRC)
+                && !texture
+                    .usage
+                    .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                && width.max(height) <= settings.max_texture_size
+                && width.max(height) + settings.padding <= page_size;
+            if packable {
+                candidates.push((asset_id, width, height));
+            }
+        }
+
+        // Tallest first, so shelves are filled by textures of similar heights.
+        candidates.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)));
+
+        let mut copies = Vec::new();
+        for (asset_id, width, height) in candidates {
+            let padded = (width + settings.padding, height + settings.padding);
+            let mut allocation = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+                page.packer
+                    .allocate(padded.0, padded.1)
+                    .map(|position| (index, position))
+            });
+
+            if allocation.is_none() {
+                self.add_page(page_size, bind_group_layouts, asset_engine, device, queue)?;
+                let index = self.pages.len() - 1;
+                allocation = self.pages[index]
+                    .packer
+                    .allocate(padded.0, padded.1)
+                    .map(|position| (index, position));
+            }
+
+            if let Some((page_index, (x, y))) = allocation {
+                let page = &mut self.pages[page_index];
+                page.texture_count += 1;
+                page.used_area += width as u64 * height as u64;
+                self.entries.insert(
+                    asset_id,
+                    AtlasEntry {
+                        page: page_index,
+                        area: Rectangle::new(x as f32, y as f32, width as f32, height as f32),
+                    },
+                );
+                copies.push((asset_id, page_index, x, y));
+            }
+        }
+
+        if copies.is_empty() {
+            return Ok(0);
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Texture Atlas Encoder"),
+        });
+        for (asset_id, page_index, x, y) in &copies {
+            let page_id = self.pages[*page_index].texture.asset_key.asset_id;
+            if let (Some(source), Some(page)) = (
+                asset_engine.get_asset::<Texture>(asset_id),
+                asset_engine.get_asset::<Texture>(&page_id),
+            ) {
+                encoder.copy_texture_to_texture(
+                    wgpu::ImageCopyTexture {
+                        texture: &source.texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d::ZERO,
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    wgpu::ImageCopyTexture {
+                        texture: &page.texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d { x: *x, y: *y, z: 0 },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    source.size,
+                );
+            }
+        }
+        queue.submit([encoder.finish()]);
+
+        Ok(copies.len())
+    }
+
+    fn add_page(
+        &mut self,
+        page_size: u32,
+        bind_group_layouts: &BindGroupLayouts,
+        asset_engine: &mut AssetEngine,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), EmeraldError> {
+        let label = format!("emd_atlas_page_{}", self.page_uid);
+        self.page_uid += 1;
+        let data = vec![0; (page_size * page_size * 4) as usize];
+        let texture = Texture::new_ext(
+            &label,
+            bind_group_layouts,
+            asset_engine,
+            device,
+            queue,
+            page_size,
+            page_size,
+            &data,
+            wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            ATLAS_FORMAT,
+            TextureOptions::default(),
+        )?;
+
+        self.pages.push(AtlasPage {
+            texture,
+            packer: ShelfPacker::new(page_size, page_size),
+            texture_count: 0,
+            used_area: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the page a packed texture is drawn from, along with the target moved onto that page.
+    /// A zero sized target covers the entire texture, as it does when drawing the texture itself.
+    pub fn remap(
+        &self,
+        texture_key: &TextureKey,
+        target: Rectangle,
+    ) -> Option<(&TextureKey, Rectangle)> {
+        let entry = self.entries.get(&texture_key.asset_key.asset_id)?;
+        let page = &self.pages[entry.page];
+        let target = if target.is_zero_sized() {
+            entry.area
+        } else {
+            Rectangle::new(
+                entry.area.x + target.x,
+                entry.area.y + target.y,
+                target.width,
+                target.height,
+            )
+        };
+
+        Some((&page.texture, target))
+    }
+
+    /// Draws the texture from its own texture again, its area of the page is left unused.
+    pub fn unpack(&mut self, texture_key: &TextureKey) {
+        if let Some(entry) = self.entries.remove(&texture_key.asset_key.asset_id) {
+            let page = &mut self.pages[entry.page];
+            page.texture_count -= 1;
+            page.used_area -= (entry.area.width * entry.area.height) as u64;
+        }
+    }
+
+    pub fn stats(&self) -> TextureAtlasStats {
+        TextureAtlasStats {
+            pages: self
+                .pages
+                .iter()
+                .map(|page| AtlasPageStats {
+                    size: page.texture.size(),
+                    texture_count: page.texture_count,
+                    used_area: page.used_area,
+                })
+                .collect(),
+        }
+    }
+
+    /// Drops every page, textures are drawn from their own texture again.
+    pub fn clear(&mut self) {
+        self.pages.clear();
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShelfPacker;
+
+    #[test]
+    fn shelves_are_reused_by_shorter_rectangles() {
+        let mut packer = ShelfPacker::new(64, 64);
+        assert_eq!(packer.allocate(32, 32), Some((0, 0)));
+        assert_eq!(packer.allocate(16, 16), Some((32, 0)));
+        assert_eq!(packer.allocate(32, 8), Some((0, 32)));
+        // The second shelf wastes less height than the first.
+        assert_eq!(packer.allocate(16, 8), Some((32, 32)));
+        assert_eq!(packer.allocate(16, 32), Some((48, 0)));
+    }
+
+    #[test]
+    fn full_pages_refuse_rectangles() {
+        let mut packer = ShelfPacker::new(32, 32);
+        assert_eq!(packer.allocate(64, 8), None);
+        assert_eq!(packer.allocate(32, 24), Some((0, 0)));
+        assert_eq!(packer.allocate(32, 16), None);
+        assert_eq!(packer.allocate(32, 8), Some((0, 24)));
+    }
+}
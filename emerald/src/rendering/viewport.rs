@@ -0,0 +1,241 @@
+use hecs::Entity;
+use rapier2d::na::Vector2;
+
+use crate::{EmeraldError, Rectangle, Translation, World};
+
+use super::components::{
+    get_camera_view, screen_to_world_through_view, world_to_screen_through_view, ViewTransform,
+};
+
+/// An area of the screen, or of a render texture, that a world is drawn into through its own camera.
+/// Used for split-screen, picture-in-picture and minimaps.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    /// The entity holding the `Camera` and `Transform` the world is viewed through.
+    pub camera: Entity,
+    /// Area covered by the viewport, as fractions of the render target's size from its top left corner.
+    /// ex. `Rectangle::new(0.5, 0.0, 0.5, 1.0)` covers the right half of the target.
+    pub area: Rectangle,
+}
+impl Viewport {
+    pub fn new(camera: Entity, area: Rectangle) -> Self {
+        Self { camera, area }
+    }
+
+    /// A viewport covering the whole render target.
+    pub fn fullscreen(camera: Entity) -> Self {
+        Self::new(camera, Rectangle::new(0.0, 0.0, 1.0, 1.0))
+    }
+
+    /// Splits the render target evenly between the given cameras, in reading order.
+    /// Two cameras are placed side by side, three or four are placed in quadrants.
+    pub fn split_screen(cameras: &[Entity]) -> Vec<Viewport> {
+        if cameras.is_empty() {
+            return Vec::new();
+        }
+
+        let columns = (cameras.len() as f32).sqrt().ceil() as usize;
+        let rows = cameras.len().div_ceil(columns);
+        let width = 1.0 / columns as f32;
+        let height = 1.0 / rows as f32;
+
+        cameras
+            .iter()
+            .enumerate()
+            .map(|(i, camera)| {
+                let column = i % columns;
+                let row = i / columns;
+                Viewport::new(
+                    *camera,
+                    Rectangle::new(column as f32 * width, row as f32 * height, width, height),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the area covered by the viewport on a render target of the given size,
+    /// in whole pixels from the top left corner.
+    pub fn pixel_area(&self, target_size: (u32, u32)) -> Rectangle {
+        let (width, height) = (target_size.0 as f32, target_size.1 as f32);
+        let left = (self.area.left() * width).round().clamp(0.0, width);
+        let right = (self.area.right() * width).round().clamp(0.0, width);
+        let top = (self.area.y * height).round().clamp(0.0, height);
+        let bottom = ((self.area.y + self.area.height) * height)
+            .round()
+            .clamp(0.0, height);
+
+        Rectangle::new(left, top, (right - left).max(0.0), (bottom - top).max(0.0))
+    }
+
+    /// Whether the given position, in pixels from the top left corner of the screen, is inside of the viewport.
+    pub fn contains_screen_point(&self, screen_size: (u32, u32), point: &Translation) -> bool {
+        let area = self.pixel_area(screen_size);
+        point.x >= area.x
+            && point.x < area.x + area.width
+            && point.y >= area.y
+            && point.y < area.y + area.height
+    }
+
+    /// Converts a position on a screen of the given size, in pixels from the top left corner,
+    /// into a world translation as seen through this viewport.
+    pub fn screen_to_world(
+        &self,
+        world: &World,
+        screen_size: (u32, u32),
+        screen_translation: &Translation,
+    ) -> Result<Translation, EmeraldError> {
+        let (camera_translation, view) = self.get_view(world, screen_size)?;
+
+        Ok(screen_to_world_through_view(
+            &camera_translation,
+            &view,
+            screen_size,
+            screen_translation,
+        ))
+    }
+
+    /// Converts a world translation into a position on a screen of the given size,
+    /// in pixels from the top left corner, as seen through this viewport.
+    pub fn world_to_screen(
+        &self,
+        world: &World,
+        screen_size: (u32, u32),
+        world_translation: &Translation,
+    ) -> Result<Translation, EmeraldError> {
+        let (camera_translation, view) = self.get_view(world, screen_size)?;
+
+        Ok(world_to_screen_through_view(
+            &camera_translation,
+            &view,
+            screen_size,
+            world_translation,
+        ))
+    }
+
+    fn get_view(
+        &self,
+        world: &World,
+        target_size: (u32, u32),
+    ) -> Result<(Translation, ViewTransform), EmeraldError> {
+        match get_camera_view(world, self.camera) {
+            Some((translation, mut view)) => {
+                view.offset += self.center_offset(target_size);
+                Ok((translation, view))
+            }
+            None => Err(EmeraldError::new(format!(
+                "Viewport camera {:?} needs both a Camera and a Transform.",
+                self.camera
+            ))),
+        }
+    }
+
+    /// Offset of the center of the viewport from the center of the target, in pixels with y pointing up.
+    pub(crate) fn center_offset(&self, target_size: (u32, u32)) -> Vector2<f32> {
+        let area = self.pixel_area(target_size);
+        Vector2::new(
+            area.x + area.width / 2.0 - target_size.0 as f32 / 2.0,
+            target_size.1 as f32 / 2.0 - (area.y + area.height / 2.0),
+        )
+    }
+
+    /// Returns the scissor rect clipping draws to this viewport, or `None` if the viewport is empty.
+    pub(crate) fn scissor(&self, target_size: (u32, u32)) -> Option<ScissorRect> {
+        let area = self.pixel_area(target_size);
+        if area.width < 1.0 || area.height < 1.0 {
+            return None;
+        }
+
+        Some(ScissorRect {
+            x: area.x as u32,
+            y: area.y as u32,
+            width: area.width as u32,
+            height: area.height as u32,
+        })
+    }
+}
+
+/// Returns the last viewport containing the given screen position, being the one drawn on top.
+pub fn get_viewport_at<'a>(
+    viewports: &'a [Viewport],
+    screen_size: (u32, u32),
+    point: &Translation,
+) -> Option<&'a Viewport> {
+    viewports
+        .iter()
+        .rev()
+        .find(|viewport| viewport.contains_screen_point(screen_size, point))
+}
+
+/// An area of the render target draws are clipped to, in pixels from its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ScissorRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+impl ScissorRect {
+    /// The area of the target covered by the scissor rect, in pixels from the center of the target with y pointing up.
+    pub fn centered_area(&self, target_size: (u32, u32)) -> Rectangle {
+        Rectangle::new(
+            self.x as f32 - target_size.0 as f32 / 2.0,
+            target_size.1 as f32 / 2.0 - (self.y + self.height) as f32,
+            self.width as f32,
+            self.height as f32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_viewport_at, Viewport};
+    use crate::{rendering::components::Camera, Rectangle, Transform, Translation, World};
+
+    #[test]
+    fn split_screen_layouts() {
+        let mut world = World::new();
+        let cameras: Vec<_> = (0..4).map(|_| world.spawn(())).collect();
+
+        let two = Viewport::split_screen(&cameras[..2]);
+        assert_eq!(two[1].pixel_area((200, 100)).x, 100.0);
+        assert_eq!(two[1].pixel_area((200, 100)).height, 100.0);
+
+        let three = Viewport::split_screen(&cameras[..3]);
+        let area = three[2].pixel_area((200, 100));
+        assert_eq!(
+            (area.x, area.y, area.width, area.height),
+            (0.0, 50.0, 100.0, 50.0)
+        );
+
+        assert!(Viewport::split_screen(&[]).is_empty());
+    }
+
+    #[test]
+    fn input_maps_through_the_viewport_under_it() {
+        let mut world = World::new();
+        let left = world.spawn((Transform::default(), Camera::default()));
+        let right = world.spawn((
+            Transform::from_translation((1000.0, 0.0)),
+            Camera::default(),
+        ));
+        let viewports = [
+            Viewport::new(left, Rectangle::new(0.0, 0.0, 0.5, 1.0)),
+            Viewport::new(right, Rectangle::new(0.5, 0.0, 0.5, 1.0)),
+        ];
+        let screen_size = (200, 100);
+
+        // The center of the right half of the screen is the center of the right camera.
+        let point = Translation::new(150.0, 50.0);
+        let viewport = get_viewport_at(&viewports, screen_size, &point).unwrap();
+        assert_eq!(viewport.camera, right);
+        let world_translation = viewport
+            .screen_to_world(&world, screen_size, &point)
+            .unwrap();
+        assert_eq!((world_translation.x, world_translation.y), (1000.0, 0.0));
+
+        let screen = viewport
+            .world_to_screen(&world, screen_size, &Translation::new(1010.0, 10.0))
+            .unwrap();
+        assert_eq!((screen.x, screen.y), (160.0, 40.0));
+    }
+}
@@ -0,0 +1,108 @@
+use wgpu::{BindGroup, RenderPipeline};
+
+use crate::{
+    render_settings::ScreenScaling, rendering_engine::BindGroupLayoutId, texture::TextureKey,
+    AssetEngine, EmeraldError,
+};
+
+use super::post_process::{
+    create_post_process_pipeline, create_target, target_view, PostProcessContext,
+};
+
+/// The target a fixed virtual resolution is drawn into, before being scaled into the window.
+#[derive(Default)]
+pub(crate) struct VirtualScreen {
+    target: Option<TextureKey>,
+    /// Suffix of the next virtual screen's label, bumped whenever the virtual resolution changes.
+    target_uid: usize,
+    pipeline: Option<RenderPipeline>,
+}
+impl VirtualScreen {
+    /// Returns a view of the target the frame should be drawn into,
+    /// recreating it if the virtual resolution has changed.
+    pub fn prepare_target(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &mut AssetEngine,
+        screen_size: (u32, u32),
+    ) -> Result<wgpu::TextureView, EmeraldError> {
+        let target = match self.target.take() {
+            Some(target) if target.size() == screen_size => target,
+            _ => {
+                let label = format!("emd_virtual_screen_{}", self.target_uid);
+                self.target_uid += 1;
+                create_target(ctx, asset_engine, &label, screen_size)?
+            }
+        };
+        let view = target_view(asset_engine, &target);
+        self.target = Some(target);
+
+        view
+    }
+
+    /// Draws the virtual screen into its area of the output, clearing the rest to black.
+    pub fn present(
+        &mut self,
+        ctx: &PostProcessContext<'_>,
+        asset_engine: &AssetEngine,
+        scaling: &ScreenScaling,
+        output: &wgpu::TextureView,
+    ) -> Result<(), EmeraldError> {
+        let target = self
+            .target
+            .as_ref()
+            .ok_or_else(|| EmeraldError::new("The virtual screen has not been drawn to"))?;
+        let bind_group = asset_engine
+            .get_asset::<BindGroup>(&target.bind_group_key.asset_id)
+            .ok_or_else(|| EmeraldError::new("Unable to find the virtual screen target"))?;
+
+        if self.pipeline.is_none() {
+            let texture_bind_group_layout = ctx
+                .bind_group_layouts
+                .get(&BindGroupLayoutId::TextureQuad)
+                .ok_or_else(|| EmeraldError::new("Unable to get TextureQuad bind group layout"))?;
+            self.pipeline = Some(create_post_process_pipeline(
+                ctx.device,
+                ctx.format,
+                "emd_virtual_screen",
+                include_str!("./shaders/post_process/blit.wgsl"),
+                &[texture_bind_group_layout],
+            ));
+        }
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Virtual Screen Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Virtual Screen Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: output,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            let area = scaling.area;
+            if area.width >= 1.0 && area.height >= 1.0 {
+                render_pass.set_viewport(area.x, area.y, area.width, area.height, 0.0, 1.0);
+                if let Some(pipeline) = &self.pipeline {
+                    render_pass.set_pipeline(pipeline);
+                }
+                render_pass.set_bind_group(0, bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+
+        ctx.queue.submit([encoder.finish()]);
+
+        Ok(())
+    }
+}
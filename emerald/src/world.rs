@@ -6,8 +6,9 @@ pub mod world_physics_loader;
 use std::collections::HashMap;
 
 use crate::{
-    rendering::components::Camera, resources::Resources, AssetLoadConfig, AssetLoader,
-    EmeraldError, PhysicsEngine, PhysicsHandler, Transform, WorldMergeHandler,
+    lighting::Lighting, render_layers::RenderLayers, rendering::components::Camera,
+    resources::Resources, AssetLoadConfig, AssetLoader, EmeraldError, PhysicsEngine,
+    PhysicsHandler, Transform, Translation, WorldMergeHandler,
 };
 
 use hecs::{
@@ -29,6 +30,8 @@ pub struct World {
     pub(crate) inner: hecs::World,
     resources: Resources,
     merge_handler: Option<WorldMergeHandler>,
+    render_layers: RenderLayers,
+    lighting: Lighting,
 }
 impl Default for World {
     fn default() -> Self {
@@ -37,6 +40,8 @@ impl Default for World {
             inner: hecs::World::default(),
             merge_handler: None,
             resources: Resources::new(),
+            render_layers: RenderLayers::default(),
+            lighting: Lighting::default(),
         }
     }
 }
@@ -147,6 +152,28 @@ impl World {
         &self.resources
     }
 
+    /// The layers the world is drawn in, see `RenderLayer`.
+    #[inline]
+    pub fn render_layers(&self) -> &RenderLayers {
+        &self.render_layers
+    }
+
+    #[inline]
+    pub fn render_layers_mut(&mut self) -> &mut RenderLayers {
+        &mut self.render_layers
+    }
+
+    /// The ambient light of the world and whether it's lit, see `Lighting`.
+    #[inline]
+    pub fn lighting(&self) -> &Lighting {
+        &self.lighting
+    }
+
+    #[inline]
+    pub fn lighting_mut(&mut self) -> &mut Lighting {
+        &mut self.lighting
+    }
+
     /// Disable all cameras then set the camera on the given entity as active.
     /// Fails if the given entity does not exist, or does not have a camera.
     #[inline]
@@ -184,6 +211,26 @@ impl World {
         None
     }
 
+    /// Converts a position on a screen of the given size, in pixels from its top left corner,
+    /// into the world translation drawn there by the active camera.
+    pub fn screen_to_world(
+        &self,
+        screen_size: (u32, u32),
+        screen_translation: &Translation,
+    ) -> Translation {
+        crate::rendering::components::screen_to_world(self, screen_size, screen_translation)
+    }
+
+    /// Converts a world translation into the position it's drawn at by the active camera,
+    /// in pixels from the top left corner of a screen of the given size.
+    pub fn world_to_screen(
+        &self,
+        screen_size: (u32, u32),
+        world_translation: &Translation,
+    ) -> Translation {
+        crate::rendering::components::world_to_screen(self, screen_size, world_translation)
+    }
+
     pub fn spawn(&mut self, components: impl DynamicBundle) -> Entity {
         self.inner.spawn(components)
     }
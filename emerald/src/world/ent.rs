@@ -2,8 +2,8 @@ use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    autotilemap::load_ent_autotilemap, tilemap::load_ent_tilemap, AssetLoader, EmeraldError,
-    Transform, World,
+    autotilemap::load_ent_autotilemap, render_layers::RenderLayer, tilemap::load_ent_tilemap,
+    AssetLoader, EmeraldError, Transform, World,
 };
 
 use self::{
@@ -14,14 +14,18 @@ use self::{
 #[cfg(feature = "aseprite")]
 pub(crate) mod ent_aseprite_loader;
 
+pub(crate) mod ent_area_loader;
 pub(crate) mod ent_color_rect_loader;
 pub(crate) mod ent_label_loader;
+pub(crate) mod ent_nine_slice_loader;
+pub(crate) mod ent_particle_emitter_loader;
 pub(crate) mod ent_rigid_body_loader;
 pub(crate) mod ent_sound_player_loader;
 pub(crate) mod ent_sprite_loader;
 pub(crate) mod ent_transform_loader;
 
 const SPRITE_SCHEMA_KEY: &str = "sprite";
+const AREA_SCHEMA_KEY: &str = "area";
 const RIGID_BODY_SCHEMA_KEY: &str = "rigid_body";
 const ASEPRITE_SCHEMA_KEY: &str = "aseprite";
 pub(crate) const TRANSFORM_SCHEMA_KEY: &str = "transform";
@@ -29,6 +33,9 @@ const LABEL_SCHEMA_KEY: &str = "label";
 const COLOR_RECT_SCHEMA_KEY: &str = "color_rect";
 const AUTOTILEMAP_SCHEMA_KEY: &str = "autotilemap";
 const TILEMAP_SCHEMA_KEY: &str = "tilemap";
+const RENDER_LAYER_SCHEMA_KEY: &str = "render_layer";
+const NINE_SLICE_SCHEMA_KEY: &str = "nine_slice";
+const PARTICLE_EMITTER_SCHEMA_KEY: &str = "particle_emitter";
 
 #[derive(Default)]
 pub struct EntLoadConfig {
@@ -43,6 +50,7 @@ pub(crate) fn load_ent(
 ) -> Result<Entity, EmeraldError> {
     let entity = world.spawn((transform,));
     let mut custom_components = Vec::new();
+    let mut area_value = None;
 
     if let Some(table) = toml.as_table_mut() {
         let table_keys = table
@@ -72,6 +80,11 @@ pub(crate) fn load_ent(
                         load_ent_label(loader, entity, world, &label_value)?;
                     }
                 }
+                RENDER_LAYER_SCHEMA_KEY => {
+                    if let Some(value) = table.remove(RENDER_LAYER_SCHEMA_KEY) {
+                        load_ent_render_layer(entity, world, &value)?;
+                    }
+                }
                 TRANSFORM_SCHEMA_KEY => {
                     if let Some(transform_value) = table.remove(TRANSFORM_SCHEMA_KEY) {
                         load_ent_transform(loader, entity, world, &transform_value)?;
@@ -82,6 +95,18 @@ pub(crate) fn load_ent(
                         load_ent_sprite(loader, entity, world, &sprite_value)?;
                     }
                 }
+                NINE_SLICE_SCHEMA_KEY => {
+                    if let Some(value) = table.remove(NINE_SLICE_SCHEMA_KEY) {
+                        ent_nine_slice_loader::load_ent_nine_slice(loader, entity, world, &value)?;
+                    }
+                }
+                PARTICLE_EMITTER_SCHEMA_KEY => {
+                    if let Some(value) = table.remove(PARTICLE_EMITTER_SCHEMA_KEY) {
+                        ent_particle_emitter_loader::load_ent_particle_emitter(
+                            loader, entity, world, &value,
+                        )?;
+                    }
+                }
                 SOUND_PLAYER_SCHEMA_KEY => {
                     if let Some(value) = table.remove(SOUND_PLAYER_SCHEMA_KEY) {
                         ent_sound_player_loader::load_ent_sound_player(
@@ -99,6 +124,10 @@ pub(crate) fn load_ent(
                         )?;
                     }
                 }
+                AREA_SCHEMA_KEY => {
+                    // Areas are loaded after the rigid body, so their colliders attach to it.
+                    area_value = table.remove(AREA_SCHEMA_KEY);
+                }
                 ASEPRITE_SCHEMA_KEY => {
                     #[cfg(feature = "aseprite")]
                     {
@@ -128,6 +157,10 @@ pub(crate) fn load_ent(
         }
     }
 
+    if let Some(value) = area_value {
+        ent_area_loader::load_ent_area(loader, entity, world, &value)?;
+    }
+
     // Custom components are loaded after all engine components
     if let Some(custom_component_loader) = loader.asset_engine.load_config.custom_component_loader {
         for (key, value) in custom_components {
@@ -138,6 +171,24 @@ pub(crate) fn load_ent(
     Ok(entity)
 }
 
+/// Loads a `RenderLayer` from the name of the layer, ex. `render_layer = "hud"`.
+fn load_ent_render_layer(
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    match toml.as_str() {
+        Some(name) => {
+            world.insert_one(entity, RenderLayer::new(name))?;
+            Ok(())
+        }
+        None => Err(EmeraldError::new(format!(
+            "Expected the name of a render layer, found {:?}",
+            toml
+        ))),
+    }
+}
+
 pub(crate) fn load_ent_from_toml(
     loader: &mut AssetLoader<'_>,
     world: &mut World,
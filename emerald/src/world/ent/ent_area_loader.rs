@@ -0,0 +1,76 @@
+use hecs::Entity;
+use rapier2d::prelude::Group;
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetLoader, EmeraldError, World};
+
+use super::ent_rigid_body_loader::{
+    get_collider_builder, EntColliderSchema, EntCollisionLayersSchema,
+};
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntAreaSchema {
+    /// The layers the area is on.
+    pub layer: Option<EntCollisionLayersSchema>,
+
+    /// The layers the area detects.
+    pub mask: Option<EntCollisionLayersSchema>,
+
+    pub colliders: Vec<EntColliderSchema>,
+}
+
+pub(crate) fn load_ent_area<'a>(
+    _loader: &mut AssetLoader<'a>,
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load area from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntAreaSchema = toml::from_str(&toml.to_string())?;
+    let mut physics = world.physics();
+    let layers = physics.collision_layers();
+    let layer = match &schema.layer {
+        Some(layer) => layer.to_group(layers)?,
+        None => Group::ALL,
+    };
+    let mask = match &schema.mask {
+        Some(mask) => mask.to_group(layers)?,
+        None => Group::ALL,
+    };
+
+    let colliders = schema
+        .colliders
+        .iter()
+        .map(|collider| get_collider_builder(collider, layers))
+        .collect::<Result<Vec<_>, EmeraldError>>()?;
+
+    physics.build_area(entity, crate::Area::new(layer, mask), colliders)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntAreaSchema;
+
+    #[test]
+    fn deser_area() {
+        let toml = r#"
+            mask = ["player", "enemy"]
+            colliders = [
+                { shape = "cuboid", half_width = 16.0, half_height = 8.0 },
+                { shape = "ball", radius = 4.0 },
+            ]
+        "#;
+        let schema: EntAreaSchema = crate::toml::from_str(toml).unwrap();
+
+        assert!(schema.layer.is_none());
+        assert!(schema.mask.is_some());
+        assert_eq!(schema.colliders.len(), 2);
+    }
+}
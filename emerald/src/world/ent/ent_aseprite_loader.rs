@@ -1,7 +1,7 @@
 use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, World};
+use crate::{AssetLoader, EmeraldError, Vector2, World};
 
 use super::Vec2f32Schema;
 
@@ -16,6 +16,10 @@ pub(crate) struct EntAsepriteSchema {
     pub scale: Option<Vec2f32Schema>,
     pub default_animation: Option<AsepriteDefaultAnimationSchema>,
     pub z_index: Option<f32>,
+    pub flip_x: Option<bool>,
+    pub flip_y: Option<bool>,
+    pub pivot: Option<Vec2f32Schema>,
+    pub skew: Option<Vec2f32Schema>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -65,6 +69,12 @@ pub(crate) fn load_ent_aseprite<'a>(
         aseprite.scale.x = scale.x;
         aseprite.scale.y = scale.y;
     }
+    aseprite.flip_x = schema.flip_x.unwrap_or(false);
+    aseprite.flip_y = schema.flip_y.unwrap_or(false);
+    aseprite.pivot = schema.pivot.map(|pivot| Vector2::new(pivot.x, pivot.y));
+    if let Some(skew) = schema.skew {
+        aseprite.skew = Vector2::new(skew.x, skew.y);
+    }
 
     if let Some(default_animation_schema) = schema.default_animation {
         let looping = default_animation_schema.looping.unwrap_or(false);
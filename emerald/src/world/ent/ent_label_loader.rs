@@ -27,6 +27,9 @@ pub(crate) struct EntLabelSchema {
 
     /// options: "center", "left", "right"
     pub horizontal_align: Option<String>,
+
+    /// Parses the text as markup, see `parse_markup`.
+    pub rich_text: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -76,6 +79,9 @@ pub(crate) fn load_ent_label<'a>(
     label.z_index = schema.z_index;
 
     schema.visible.map(|v| label.visible = v);
+    if let Some(rich_text) = schema.rich_text {
+        label.rich_text = rich_text;
+    }
 
     if let Some(offset) = schema.offset {
         label.offset = Vector2::new(offset.x, offset.y);
@@ -0,0 +1,109 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rendering::components::{NineSlice, NineSliceBorder, NineSliceMode},
+    AssetLoader, EmeraldError, Rectangle, Vector2, World,
+};
+
+use super::Vec2f32Schema;
+
+/// A nine-slice is loaded either from a texture and a border,
+/// or from a slice of an Aseprite file with a 9-slice center.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntNineSliceSchema {
+    #[serde(default)]
+    pub texture: Option<String>,
+
+    #[serde(default)]
+    pub border: Option<NineSliceBorder>,
+
+    #[serde(default)]
+    pub aseprite: Option<String>,
+
+    #[serde(default)]
+    pub slice: Option<String>,
+
+    #[serde(default)]
+    pub size: Option<Vec2f32Schema>,
+
+    #[serde(default)]
+    pub target: Option<Rectangle>,
+
+    #[serde(default)]
+    pub edge_mode: Option<NineSliceMode>,
+
+    #[serde(default)]
+    pub center_mode: Option<NineSliceMode>,
+
+    #[serde(default)]
+    pub offset: Option<Vec2f32Schema>,
+
+    #[serde(default)]
+    pub visible: Option<bool>,
+
+    #[serde(default)]
+    pub z_index: Option<f32>,
+}
+
+pub(crate) fn load_ent_nine_slice<'a>(
+    loader: &mut AssetLoader<'a>,
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load nine_slice from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntNineSliceSchema = toml::from_str(&toml.to_string())?;
+    let mut nine_slice = load_nine_slice(loader, &schema)?;
+
+    if let Some(border) = schema.border {
+        nine_slice.border = border;
+    }
+    if let Some(size) = schema.size {
+        nine_slice.size = Vector2::new(size.x, size.y);
+    }
+    if let Some(target) = schema.target {
+        nine_slice.target = target;
+    }
+    if let Some(offset) = schema.offset {
+        nine_slice.offset = Vector2::new(offset.x, offset.y);
+    }
+    nine_slice.edge_mode = schema.edge_mode.unwrap_or(NineSliceMode::Stretch);
+    nine_slice.center_mode = schema.center_mode.unwrap_or(NineSliceMode::Stretch);
+    nine_slice.visible = schema.visible.unwrap_or(true);
+    nine_slice.z_index = schema.z_index.unwrap_or(0.0);
+
+    world.insert_one(entity, nine_slice)?;
+
+    Ok(())
+}
+
+fn load_nine_slice(
+    loader: &mut AssetLoader<'_>,
+    schema: &EntNineSliceSchema,
+) -> Result<NineSlice, EmeraldError> {
+    if let (Some(aseprite), Some(slice)) = (&schema.aseprite, &schema.slice) {
+        #[cfg(feature = "aseprite")]
+        return loader.nine_slice_from_aseprite(aseprite, slice);
+
+        #[cfg(not(feature = "aseprite"))]
+        return Err(EmeraldError::new(format!(
+            "Unable to load slice {:?} of {:?}, the aseprite feature is disabled.",
+            slice, aseprite
+        )));
+    }
+
+    match &schema.texture {
+        Some(texture) => {
+            loader.nine_slice(texture, NineSliceBorder::default(), Vector2::new(0.0, 0.0))
+        }
+        None => Err(EmeraldError::new(
+            "A nine_slice needs either a texture, or an aseprite and a slice.",
+        )),
+    }
+}
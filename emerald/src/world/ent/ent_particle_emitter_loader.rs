@@ -0,0 +1,184 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rendering::components::{
+        ParticleBurst, ParticleCurve, ParticleEmitter, ParticleFrameMode, ParticleSpace,
+    },
+    AssetLoader, Color, EmeraldError, Rectangle, Vector2, World,
+};
+
+use super::Vec2f32Schema;
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct ParticleKeySchema<T> {
+    pub time: f32,
+    pub value: T,
+}
+
+/// Particles are drawn either with a texture, or with the frames of an Aseprite file.
+/// Ranges are given as `[min, max]`.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntParticleEmitterSchema {
+    #[serde(default)]
+    pub texture: Option<String>,
+
+    #[serde(default)]
+    pub aseprite: Option<String>,
+
+    #[serde(default)]
+    pub target: Option<Rectangle>,
+
+    #[serde(default)]
+    pub emitting: Option<bool>,
+
+    #[serde(default)]
+    pub rate: Option<f32>,
+
+    #[serde(default)]
+    pub bursts: Option<Vec<ParticleBurst>>,
+
+    #[serde(default)]
+    pub duration: Option<f32>,
+
+    #[serde(default)]
+    pub looping: Option<bool>,
+
+    #[serde(default)]
+    pub max_particles: Option<usize>,
+
+    #[serde(default)]
+    pub lifetime: Option<(f32, f32)>,
+
+    #[serde(default)]
+    pub speed: Option<(f32, f32)>,
+
+    #[serde(default)]
+    pub angle: Option<(f32, f32)>,
+
+    #[serde(default)]
+    pub gravity: Option<Vec2f32Schema>,
+
+    #[serde(default)]
+    pub color: Option<Vec<ParticleKeySchema<Color>>>,
+
+    #[serde(default)]
+    pub scale: Option<Vec<ParticleKeySchema<f32>>>,
+
+    #[serde(default)]
+    pub frame_mode: Option<ParticleFrameMode>,
+
+    #[serde(default)]
+    pub space: Option<ParticleSpace>,
+
+    #[serde(default)]
+    pub offset: Option<Vec2f32Schema>,
+
+    #[serde(default)]
+    pub visible: Option<bool>,
+
+    #[serde(default)]
+    pub z_index: Option<f32>,
+}
+
+pub(crate) fn load_ent_particle_emitter<'a>(
+    loader: &mut AssetLoader<'a>,
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    let emitter = load_particle_emitter(loader, toml)?;
+    world.insert_one(entity, emitter)?;
+
+    Ok(())
+}
+
+/// Builds an emitter from a table of its settings, shared by ents and particle presets.
+pub(crate) fn load_particle_emitter(
+    loader: &mut AssetLoader<'_>,
+    toml: &toml::Value,
+) -> Result<ParticleEmitter, EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load particle_emitter from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntParticleEmitterSchema = toml::from_str(&toml.to_string())?;
+    let mut emitter = load_emitter_frames(loader, &schema)?;
+
+    if let Some(target) = schema.target {
+        for frame in &mut emitter.frames {
+            frame.target = target;
+        }
+    }
+    if let Some(rate) = schema.rate {
+        emitter.rate = rate;
+    }
+    if let Some(bursts) = schema.bursts {
+        emitter.bursts = bursts;
+    }
+    if let Some(duration) = schema.duration {
+        emitter.duration = duration;
+    }
+    if let Some(max_particles) = schema.max_particles {
+        emitter.max_particles = max_particles;
+    }
+    if let Some(lifetime) = schema.lifetime {
+        emitter.lifetime = lifetime;
+    }
+    if let Some(speed) = schema.speed {
+        emitter.speed = speed;
+    }
+    if let Some(angle) = schema.angle {
+        emitter.angle = angle;
+    }
+    if let Some(gravity) = schema.gravity {
+        emitter.gravity = Vector2::new(gravity.x, gravity.y);
+    }
+    if let Some(keys) = schema.color {
+        emitter.color = ParticleCurve::new(keys.into_iter().map(|k| (k.time, k.value)).collect());
+    }
+    if let Some(keys) = schema.scale {
+        emitter.scale = ParticleCurve::new(keys.into_iter().map(|k| (k.time, k.value)).collect());
+    }
+    if let Some(frame_mode) = schema.frame_mode {
+        emitter.frame_mode = frame_mode;
+    }
+    if let Some(offset) = schema.offset {
+        emitter.offset = Vector2::new(offset.x, offset.y);
+    }
+    emitter.emitting = schema.emitting.unwrap_or(true);
+    emitter.looping = schema.looping.unwrap_or(true);
+    emitter.space = schema.space.unwrap_or(ParticleSpace::Local);
+    emitter.visible = schema.visible.unwrap_or(true);
+    emitter.z_index = schema.z_index.unwrap_or(0.0);
+
+    Ok(emitter)
+}
+
+fn load_emitter_frames(
+    loader: &mut AssetLoader<'_>,
+    schema: &EntParticleEmitterSchema,
+) -> Result<ParticleEmitter, EmeraldError> {
+    if let Some(aseprite) = &schema.aseprite {
+        #[cfg(feature = "aseprite")]
+        {
+            let aseprite = loader.aseprite(aseprite)?;
+            return Ok(ParticleEmitter::from_aseprite(&aseprite));
+        }
+
+        #[cfg(not(feature = "aseprite"))]
+        return Err(EmeraldError::new(format!(
+            "Unable to load particles from {:?}, the aseprite feature is disabled.",
+            aseprite
+        )));
+    }
+
+    match &schema.texture {
+        Some(texture) => Ok(ParticleEmitter::new(loader.texture(texture)?)),
+        None => Err(EmeraldError::new(
+            "A particle_emitter needs either a texture or an aseprite.",
+        )),
+    }
+}
@@ -1,23 +1,88 @@
 use hecs::Entity;
 use rapier2d::{
-    na::Vector2,
-    parry::shape::Cuboid,
-    prelude::{ColliderBuilder, ColliderHandle, RigidBodyBuilder, RigidBodyHandle, RigidBodyType},
+    na::{DVector, Point2, Vector2},
+    prelude::{
+        ColliderBuilder, ColliderHandle, Group, InteractionGroups, RigidBodyBuilder,
+        RigidBodyHandle, RigidBodyType,
+    },
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, World};
+use crate::{AssetLoader, CollisionLayers, EmeraldError, World};
 
 use super::Vec2f32Schema;
 
+/// A set of collision layers, given as a raw bitmask, a single layer name or a list of layer names.
+/// Names are looked up in the world's `CollisionLayers`.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum EntCollisionLayersSchema {
+    Bits(u32),
+    Name(String),
+    Names(Vec<String>),
+}
+impl EntCollisionLayersSchema {
+    pub fn to_group(&self, layers: &CollisionLayers) -> Result<Group, EmeraldError> {
+        match self {
+            EntCollisionLayersSchema::Bits(bits) => Ok(Group::from_bits_truncate(*bits)),
+            EntCollisionLayersSchema::Name(name) => layers.group([name.as_str()]),
+            EntCollisionLayersSchema::Names(names) => {
+                layers.group(names.iter().map(|name| name.as_str()))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntInteractionGroupsSchema {
+    /// The layers this collider is a member of.
+    pub memberships: EntCollisionLayersSchema,
+
+    /// The layers this collider is able to interact with.
+    pub filter: EntCollisionLayersSchema,
+}
+impl EntInteractionGroupsSchema {
+    fn to_interaction_groups(
+        &self,
+        layers: &CollisionLayers,
+    ) -> Result<InteractionGroups, EmeraldError> {
+        Ok(InteractionGroups::new(
+            self.memberships.to_group(layers)?,
+            self.filter.to_group(layers)?,
+        ))
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub(crate) struct EntColliderSchema {
+    /// options: "cuboid", "ball", "capsule", "capsule_x", "capsule_y", "convex_polygon",
+    /// "polyline", "triangle", "segment", "heightfield"
     pub shape: String,
     pub translation: Option<Vec2f32Schema>,
+    /// Rotation of the collider relative to its body, in radians.
+    pub rotation: Option<f32>,
     pub half_width: Option<f32>,
     pub half_height: Option<f32>,
     pub radius: Option<f32>,
+
+    /// Points used by the convex_polygon, polyline, triangle and segment shapes.
+    pub points: Option<Vec<Vec2f32Schema>>,
+
+    /// Optional segment indices for polylines, each pair of indices defines a segment.
+    pub indices: Option<Vec<[u32; 2]>>,
+
+    /// Heights of a heightfield, sampled evenly across its width.
+    pub heights: Option<Vec<f32>>,
+
+    /// Scale of a heightfield.
+    pub scale: Option<Vec2f32Schema>,
+
     pub sensor: Option<bool>,
+    pub friction: Option<f32>,
+    pub restitution: Option<f32>,
+    pub density: Option<f32>,
+    pub collision_groups: Option<EntInteractionGroupsSchema>,
+    pub solver_groups: Option<EntInteractionGroupsSchema>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -26,15 +91,33 @@ pub(crate) struct EntRigidBodySchema {
     pub colliders: Option<Vec<EntColliderSchema>>,
     pub lock_rotations: Option<bool>,
     pub lock_translations: Option<bool>,
+    pub linear_damping: Option<f32>,
+    pub angular_damping: Option<f32>,
+    pub gravity_scale: Option<f32>,
+    pub ccd: Option<bool>,
+    pub linvel: Option<Vec2f32Schema>,
+    pub angvel: Option<f32>,
+    pub can_sleep: Option<bool>,
+    pub dominance_group: Option<i8>,
 }
 
-fn load_ent_collider(
-    rbh: RigidBodyHandle,
-    world: &mut World,
-    collider_schema: EntColliderSchema,
-) -> Result<ColliderHandle, EmeraldError> {
-    // Load collider attributes
-    let mut builder = match collider_schema.shape.as_str() {
+fn get_collider_points(
+    collider_schema: &EntColliderSchema,
+) -> Result<Vec<Point2<f32>>, EmeraldError> {
+    match &collider_schema.points {
+        Some(points) => Ok(points.iter().map(|p| Point2::new(p.x, p.y)).collect()),
+        None => Err(EmeraldError::new(format!(
+            "{:?} colliders require a list of points.",
+            collider_schema.shape
+        ))),
+    }
+}
+
+pub(crate) fn get_collider_builder(
+    collider_schema: &EntColliderSchema,
+    layers: &CollisionLayers,
+) -> Result<ColliderBuilder, EmeraldError> {
+    let builder = match collider_schema.shape.as_str() {
         "cuboid" => {
             if let (Some(half_width), Some(half_height)) =
                 (collider_schema.half_width, collider_schema.half_height)
@@ -53,37 +136,146 @@ fn load_ent_collider(
                 return Err(EmeraldError::new("Ball colliders require a radius"));
             }
         }
+        "capsule" | "capsule_y" => {
+            if let (Some(half_height), Some(radius)) =
+                (collider_schema.half_height, collider_schema.radius)
+            {
+                ColliderBuilder::capsule_y(half_height, radius)
+            } else {
+                return Err(EmeraldError::new(
+                    "Capsule colliders expect both a half_height and radius.",
+                ));
+            }
+        }
+        "capsule_x" => {
+            if let (Some(half_width), Some(radius)) =
+                (collider_schema.half_width, collider_schema.radius)
+            {
+                ColliderBuilder::capsule_x(half_width, radius)
+            } else {
+                return Err(EmeraldError::new(
+                    "Horizontal capsule colliders expect both a half_width and radius.",
+                ));
+            }
+        }
+        "convex_polygon" => {
+            let points = get_collider_points(collider_schema)?;
+            match ColliderBuilder::convex_hull(&points) {
+                Some(builder) => builder,
+                None => {
+                    return Err(EmeraldError::new(
+                        "Unable to compute a convex polygon from the given points.",
+                    ))
+                }
+            }
+        }
+        "polyline" => {
+            let points = get_collider_points(collider_schema)?;
+            if points.len() < 2 {
+                return Err(EmeraldError::new(
+                    "Polyline colliders require at least 2 points.",
+                ));
+            }
+            ColliderBuilder::polyline(points, collider_schema.indices.clone())
+        }
+        "triangle" => {
+            let points = get_collider_points(collider_schema)?;
+            if points.len() != 3 {
+                return Err(EmeraldError::new(
+                    "Triangle colliders require exactly 3 points.",
+                ));
+            }
+            ColliderBuilder::triangle(points[0], points[1], points[2])
+        }
+        "segment" => {
+            let points = get_collider_points(collider_schema)?;
+            if points.len() != 2 {
+                return Err(EmeraldError::new(
+                    "Segment colliders require exactly 2 points.",
+                ));
+            }
+            ColliderBuilder::segment(points[0], points[1])
+        }
+        "heightfield" => {
+            if let (Some(heights), Some(scale)) = (&collider_schema.heights, &collider_schema.scale)
+            {
+                if heights.len() < 2 {
+                    return Err(EmeraldError::new(
+                        "Heightfield colliders require at least 2 heights.",
+                    ));
+                }
+                ColliderBuilder::heightfield(
+                    DVector::from_vec(heights.clone()),
+                    Vector2::new(scale.x, scale.y),
+                )
+            } else {
+                return Err(EmeraldError::new(
+                    "Heightfield colliders expect both heights and a scale.",
+                ));
+            }
+        }
         _ => {
-            return Err(EmeraldError::new(
-                "Collider shape does not match an expected shape.",
-            ))
+            return Err(EmeraldError::new(format!(
+                "Collider shape {:?} does not match an expected shape.",
+                collider_schema.shape
+            )))
         }
     };
 
-    if let Some(translation_value) = collider_schema.translation {
+    apply_collider_attributes(builder, collider_schema, layers)
+}
+
+fn apply_collider_attributes(
+    mut builder: ColliderBuilder,
+    collider_schema: &EntColliderSchema,
+    layers: &CollisionLayers,
+) -> Result<ColliderBuilder, EmeraldError> {
+    if let Some(translation_value) = &collider_schema.translation {
         builder = builder.translation(Vector2::new(translation_value.x, translation_value.y));
     }
 
+    if let Some(rotation) = collider_schema.rotation {
+        builder = builder.rotation(rotation);
+    }
+
     if let Some(sensor) = collider_schema.sensor {
         builder = builder.sensor(sensor);
     }
 
-    Ok(world.physics().build_collider(rbh, builder))
+    if let Some(friction) = collider_schema.friction {
+        builder = builder.friction(friction);
+    }
+
+    if let Some(restitution) = collider_schema.restitution {
+        builder = builder.restitution(restitution);
+    }
+
+    if let Some(density) = collider_schema.density {
+        builder = builder.density(density);
+    }
+
+    if let Some(collision_groups) = &collider_schema.collision_groups {
+        builder = builder.collision_groups(collision_groups.to_interaction_groups(layers)?);
+    }
+
+    if let Some(solver_groups) = &collider_schema.solver_groups {
+        builder = builder.solver_groups(solver_groups.to_interaction_groups(layers)?);
+    }
+
+    Ok(builder)
 }
 
-pub(crate) fn load_ent_rigid_body<'a>(
-    loader: &mut AssetLoader<'a>,
-    entity: Entity,
+fn load_ent_collider(
+    rbh: RigidBodyHandle,
     world: &mut World,
-    toml: &toml::Value,
-) -> Result<RigidBodyHandle, EmeraldError> {
-    if !toml.is_table() {
-        return Err(EmeraldError::new(
-            "Cannot load rigid_body from a non-table toml value.",
-        ));
-    }
-    let schema: EntRigidBodySchema = toml::from_str(&toml.to_string())?;
+    collider_schema: EntColliderSchema,
+) -> Result<ColliderHandle, EmeraldError> {
+    let builder = get_collider_builder(&collider_schema, world.physics().collision_layers())?;
+
+    Ok(world.physics().build_collider(rbh, builder))
+}
 
+fn get_rigid_body_builder(schema: &EntRigidBodySchema) -> Result<RigidBodyBuilder, EmeraldError> {
     let mut body_type = RigidBodyType::Dynamic;
     match schema.body_type.as_str() {
         "dynamic" => {}
@@ -108,6 +300,55 @@ pub(crate) fn load_ent_rigid_body<'a>(
         rigid_body_builder = rigid_body_builder.lock_translations();
     }
 
+    if let Some(linear_damping) = schema.linear_damping {
+        rigid_body_builder = rigid_body_builder.linear_damping(linear_damping);
+    }
+
+    if let Some(angular_damping) = schema.angular_damping {
+        rigid_body_builder = rigid_body_builder.angular_damping(angular_damping);
+    }
+
+    if let Some(gravity_scale) = schema.gravity_scale {
+        rigid_body_builder = rigid_body_builder.gravity_scale(gravity_scale);
+    }
+
+    if let Some(ccd) = schema.ccd {
+        rigid_body_builder = rigid_body_builder.ccd_enabled(ccd);
+    }
+
+    if let Some(linvel) = &schema.linvel {
+        rigid_body_builder = rigid_body_builder.linvel(Vector2::new(linvel.x, linvel.y));
+    }
+
+    if let Some(angvel) = schema.angvel {
+        rigid_body_builder = rigid_body_builder.angvel(angvel);
+    }
+
+    if let Some(can_sleep) = schema.can_sleep {
+        rigid_body_builder = rigid_body_builder.can_sleep(can_sleep);
+    }
+
+    if let Some(dominance_group) = schema.dominance_group {
+        rigid_body_builder = rigid_body_builder.dominance_group(dominance_group);
+    }
+
+    Ok(rigid_body_builder)
+}
+
+pub(crate) fn load_ent_rigid_body<'a>(
+    _loader: &mut AssetLoader<'a>,
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<RigidBodyHandle, EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load rigid_body from a non-table toml value.",
+        ));
+    }
+    let schema: EntRigidBodySchema = toml::from_str(&toml.to_string())?;
+    let rigid_body_builder = get_rigid_body_builder(&schema)?;
+
     let rbh = world.physics().build_body(entity, rigid_body_builder)?;
     if let Some(collider_schemas) = schema.colliders {
         for collider_schema in collider_schemas {
@@ -117,3 +358,147 @@ pub(crate) fn load_ent_rigid_body<'a>(
 
     Ok(rbh)
 }
+
+#[cfg(test)]
+mod tests {
+    use rapier2d::prelude::{Group, RigidBodyType};
+
+    use super::{get_collider_builder, get_rigid_body_builder, EntRigidBodySchema};
+    use crate::CollisionLayers;
+
+    #[test]
+    fn deser_rigid_body_options() {
+        let toml = r#"
+            body_type = "dynamic"
+            linear_damping = 0.5
+            angular_damping = 1.5
+            gravity_scale = 2.0
+            ccd = true
+            linvel = { x = 10.0, y = -4.0 }
+            angvel = 3.0
+            can_sleep = false
+            dominance_group = -2
+        "#;
+        let schema: EntRigidBodySchema = crate::toml::from_str(toml).unwrap();
+        let body = get_rigid_body_builder(&schema).unwrap().build();
+
+        assert_eq!(body.body_type(), RigidBodyType::Dynamic);
+        assert_eq!(body.linear_damping(), 0.5);
+        assert_eq!(body.angular_damping(), 1.5);
+        assert_eq!(body.gravity_scale(), 2.0);
+        assert!(body.is_ccd_enabled());
+        assert_eq!(body.linvel().x, 10.0);
+        assert_eq!(body.linvel().y, -4.0);
+        assert_eq!(body.angvel(), 3.0);
+        assert_eq!(body.dominance_group(), -2);
+    }
+
+    #[test]
+    fn deser_collider_shapes() {
+        let toml = r#"
+            body_type = "fixed"
+
+            [[colliders]]
+            shape = "capsule"
+            half_height = 8.0
+            radius = 4.0
+            friction = 0.2
+            restitution = 0.7
+            density = 3.0
+            rotation = 1.0
+            collision_groups = { memberships = 1, filter = 6 }
+
+            [[colliders]]
+            shape = "convex_polygon"
+            points = [{ x = 0.0, y = 0.0 }, { x = 10.0, y = 0.0 }, { x = 5.0, y = 10.0 }]
+
+            [[colliders]]
+            shape = "polyline"
+            points = [{ x = 0.0, y = 0.0 }, { x = 10.0, y = 0.0 }, { x = 20.0, y = 5.0 }]
+
+            [[colliders]]
+            shape = "triangle"
+            points = [{ x = 0.0, y = 0.0 }, { x = 10.0, y = 0.0 }, { x = 5.0, y = 10.0 }]
+
+            [[colliders]]
+            shape = "segment"
+            points = [{ x = 0.0, y = 0.0 }, { x = 10.0, y = 0.0 }]
+
+            [[colliders]]
+            shape = "heightfield"
+            heights = [0.0, 1.0, 0.5, 2.0]
+            scale = { x = 64.0, y = 8.0 }
+        "#;
+        let schema: EntRigidBodySchema = crate::toml::from_str(toml).unwrap();
+        let colliders = schema
+            .colliders
+            .unwrap()
+            .iter()
+            .map(|c| {
+                get_collider_builder(c, &CollisionLayers::new())
+                    .unwrap()
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(colliders[0].shape().as_capsule().is_some());
+        assert_eq!(colliders[0].friction(), 0.2);
+        assert_eq!(colliders[0].restitution(), 0.7);
+        assert_eq!(colliders[0].density(), 3.0);
+        assert_eq!(colliders[0].rotation().angle(), 1.0);
+        assert_eq!(colliders[0].collision_groups().memberships, Group::GROUP_1);
+        assert_eq!(
+            colliders[0].collision_groups().filter,
+            Group::GROUP_2 | Group::GROUP_3
+        );
+        assert!(colliders[1].shape().as_convex_polygon().is_some());
+        assert!(colliders[2].shape().as_polyline().is_some());
+        assert!(colliders[3].shape().as_triangle().is_some());
+        assert!(colliders[4].shape().as_segment().is_some());
+        assert!(colliders[5].shape().as_heightfield().is_some());
+    }
+
+    #[test]
+    fn triangle_requires_three_points() {
+        let toml = r#"
+            body_type = "fixed"
+
+            [[colliders]]
+            shape = "triangle"
+            points = [{ x = 0.0, y = 0.0 }, { x = 10.0, y = 0.0 }]
+        "#;
+        let schema: EntRigidBodySchema = crate::toml::from_str(toml).unwrap();
+        let collider_schema = &schema.colliders.unwrap()[0];
+
+        assert!(get_collider_builder(collider_schema, &CollisionLayers::new()).is_err());
+    }
+
+    #[test]
+    fn collision_groups_from_layer_names() {
+        let toml = r#"
+            body_type = "dynamic"
+
+            [[colliders]]
+            shape = "ball"
+            radius = 4.0
+            collision_groups = { memberships = "player", filter = ["terrain", "enemy"] }
+        "#;
+        let mut layers = CollisionLayers::new();
+        layers.add("terrain").unwrap();
+        layers.add("player").unwrap();
+        layers.add("enemy").unwrap();
+
+        let schema: EntRigidBodySchema = crate::toml::from_str(toml).unwrap();
+        let collider_schema = &schema.colliders.unwrap()[0];
+        let collider = get_collider_builder(collider_schema, &layers)
+            .unwrap()
+            .build();
+
+        assert_eq!(collider.collision_groups().memberships, Group::GROUP_2);
+        assert_eq!(
+            collider.collision_groups().filter,
+            Group::GROUP_1 | Group::GROUP_3
+        );
+        assert!(get_collider_builder(collider_schema, &CollisionLayers::new()).is_err());
+    }
+}
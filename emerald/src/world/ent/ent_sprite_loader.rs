@@ -1,7 +1,7 @@
 use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, Rectangle, World};
+use crate::{AssetLoader, EmeraldError, Rectangle, Vector2, World};
 
 use super::Vec2f32Schema;
 
@@ -23,6 +23,21 @@ pub(crate) struct EntSpriteSchema {
 
     #[serde(default)]
     pub target: Option<Rectangle>,
+
+    #[serde(default)]
+    pub centered: Option<bool>,
+
+    #[serde(default)]
+    pub flip_x: Option<bool>,
+
+    #[serde(default)]
+    pub flip_y: Option<bool>,
+
+    #[serde(default)]
+    pub pivot: Option<Vec2f32Schema>,
+
+    #[serde(default)]
+    pub skew: Option<Vec2f32Schema>,
 }
 
 pub(crate) fn load_ent_sprite<'a>(
@@ -50,6 +65,13 @@ pub(crate) fn load_ent_sprite<'a>(
         sprite.scale.x = scale.x;
         sprite.scale.y = scale.y;
     }
+    sprite.centered = schema.centered.unwrap_or(true);
+    sprite.flip_x = schema.flip_x.unwrap_or(false);
+    sprite.flip_y = schema.flip_y.unwrap_or(false);
+    sprite.pivot = schema.pivot.map(|pivot| Vector2::new(pivot.x, pivot.y));
+    if let Some(skew) = schema.skew {
+        sprite.skew = Vector2::new(skew.x, skew.y);
+    }
 
     schema.target.map(|t| sprite.target = t);
 
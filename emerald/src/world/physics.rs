@@ -1,11 +1,17 @@
+mod collision_layers;
 mod components;
 mod physics_engine;
 mod physics_handler;
+mod physics_settings;
+mod tilemap_colliders;
 mod types;
 
+pub use collision_layers::*;
 pub use components::*;
 pub use physics_engine::*;
 pub use physics_handler::*;
+pub use physics_settings::*;
+pub use tilemap_colliders::*;
 pub use types::*;
 
 pub use rapier2d::prelude::{ActiveCollisionTypes, Group, InteractionGroups, QueryFilterFlags};
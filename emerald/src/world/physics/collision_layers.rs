@@ -0,0 +1,138 @@
+use rapier2d::prelude::{Group, InteractionGroups, QueryFilter};
+
+use crate::EmeraldError;
+
+/// The maximum number of collision layers, one per bit of a `Group`.
+pub const MAX_COLLISION_LAYERS: usize = 32;
+
+/// A registry of named collision layers, each backed by one bit of an `InteractionGroups` bitmask.
+/// Layers are assigned bits in the order they're added.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CollisionLayers {
+    names: Vec<String>,
+}
+impl CollisionLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer with the given name, returning its group.
+    /// Adding an existing layer returns the group it was already given.
+    pub fn add<T: Into<String>>(&mut self, name: T) -> Result<Group, EmeraldError> {
+        let name = name.into();
+        if let Some(group) = self.get(&name) {
+            return Ok(group);
+        }
+
+        if self.names.len() >= MAX_COLLISION_LAYERS {
+            return Err(EmeraldError::new(format!(
+                "Unable to add collision layer {:?}, there are already {} layers.",
+                name, MAX_COLLISION_LAYERS
+            )));
+        }
+
+        self.names.push(name);
+        Ok(Group::from_bits_truncate(1 << (self.names.len() - 1)))
+    }
+
+    /// Returns the group of the layer with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<Group> {
+        self.names
+            .iter()
+            .position(|layer| layer == name)
+            .map(|index| Group::from_bits_truncate(1 << index))
+    }
+
+    /// Returns the name of the layer backed by the given bit.
+    pub fn name(&self, bit: usize) -> Option<&str> {
+        self.names.get(bit).map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Combines the named layers into a single group. Fails if any of the layers don't exist.
+    pub fn group<'a, I: IntoIterator<Item = &'a str>>(
+        &self,
+        names: I,
+    ) -> Result<Group, EmeraldError> {
+        let mut group = Group::NONE;
+
+        for name in names {
+            match self.get(name) {
+                Some(layer) => group |= layer,
+                None => {
+                    return Err(EmeraldError::new(format!(
+                        "Collision layer {:?} does not exist.",
+                        name
+                    )))
+                }
+            }
+        }
+
+        Ok(group)
+    }
+
+    /// Builds interaction groups that are members of the `memberships` layers,
+    /// and interact with the `filter` layers.
+    pub fn interaction_groups<'a, M, F>(
+        &self,
+        memberships: M,
+        filter: F,
+    ) -> Result<InteractionGroups, EmeraldError>
+    where
+        M: IntoIterator<Item = &'a str>,
+        F: IntoIterator<Item = &'a str>,
+    {
+        Ok(InteractionGroups::new(
+            self.group(memberships)?,
+            self.group(filter)?,
+        ))
+    }
+
+    /// Builds a query filter that only hits colliders on the named layers.
+    pub fn query_filter<'a, 'b, I: IntoIterator<Item = &'a str>>(
+        &self,
+        names: I,
+    ) -> Result<QueryFilter<'b>, EmeraldError> {
+        Ok(QueryFilter::new().groups(InteractionGroups::new(Group::ALL, self.group(names)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier2d::prelude::Group;
+
+    use super::{CollisionLayers, MAX_COLLISION_LAYERS};
+
+    #[test]
+    fn layers_are_assigned_bits_in_order() {
+        let mut layers = CollisionLayers::new();
+        assert_eq!(layers.add("player").unwrap(), Group::GROUP_1);
+        assert_eq!(layers.add("enemy").unwrap(), Group::GROUP_2);
+        assert_eq!(layers.add("player").unwrap(), Group::GROUP_1);
+
+        assert_eq!(
+            layers.group(["player", "enemy"]).unwrap(),
+            Group::GROUP_1 | Group::GROUP_2
+        );
+        assert!(layers.group(["terrain"]).is_err());
+        assert_eq!(layers.name(1), Some("enemy"));
+    }
+
+    #[test]
+    fn layers_are_limited_to_group_bits() {
+        let mut layers = CollisionLayers::new();
+        for i in 0..MAX_COLLISION_LAYERS {
+            layers.add(format!("layer_{}", i)).unwrap();
+        }
+
+        assert_eq!(layers.get("layer_31"), Some(Group::GROUP_32));
+        assert!(layers.add("one_too_many").is_err());
+    }
+}
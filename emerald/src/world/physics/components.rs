@@ -1 +1,117 @@
+use std::collections::HashSet;
 
+use hecs::Entity;
+use rapier2d::prelude::{ColliderHandle, Group};
+
+/// Chooses in which directions an entity's `Transform` is synced with its rigid body.
+/// Entities without this component are synced both ways.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicsSync {
+    /// Push the entity's `Transform` into its body before the physics is stepped.
+    /// Kinematic position-based bodies are moved towards it, other bodies are teleported to it.
+    pub to_physics: bool,
+    /// Copy the body's translation and rotation into the entity's `Transform` after the physics is stepped.
+    pub from_physics: bool,
+}
+impl PhysicsSync {
+    pub fn both() -> Self {
+        Self {
+            to_physics: true,
+            from_physics: true,
+        }
+    }
+
+    /// Only gameplay code moves the body, ex. a kinematic platform following a path.
+    pub fn to_physics_only() -> Self {
+        Self {
+            to_physics: true,
+            from_physics: false,
+        }
+    }
+
+    /// Only the physics moves the entity, edits to its `Transform` are overwritten on the next step.
+    pub fn from_physics_only() -> Self {
+        Self {
+            to_physics: false,
+            from_physics: true,
+        }
+    }
+}
+impl Default for PhysicsSync {
+    fn default() -> Self {
+        Self::both()
+    }
+}
+
+/// A trigger volume tracking which entities overlap its colliders.
+/// Its occupants, along with the entities that entered and exited it, are updated every physics step.
+///
+/// Built through `PhysicsHandler::build_area`, which turns the area's colliders into sensors.
+#[derive(Clone, Debug)]
+pub struct Area {
+    /// The layers this area is on. Other colliders only overlap it if their filter contains one of these layers.
+    pub layer: Group,
+    /// The layers this area detects. Colliders are only tracked if they are a member of one of these layers.
+    pub mask: Group,
+    /// The sensors built for the area, other colliders of its body aren't part of it.
+    pub(crate) sensors: Vec<ColliderHandle>,
+    occupants: HashSet<Entity>,
+    entered: Vec<Entity>,
+    exited: Vec<Entity>,
+}
+impl Default for Area {
+    fn default() -> Self {
+        Self::new(Group::ALL, Group::ALL)
+    }
+}
+impl Area {
+    pub fn new(layer: Group, mask: Group) -> Self {
+        Self {
+            layer,
+            mask,
+            sensors: Vec::new(),
+            occupants: HashSet::new(),
+            entered: Vec::new(),
+            exited: Vec::new(),
+        }
+    }
+
+    /// The entities currently inside of the area.
+    pub fn occupants(&self) -> impl Iterator<Item = &Entity> {
+        self.occupants.iter()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.occupants.contains(&entity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.occupants.is_empty()
+    }
+
+    /// The entities that entered the area during the last physics step.
+    pub fn entered(&self) -> &[Entity] {
+        &self.entered
+    }
+
+    /// The entities that exited the area during the last physics step.
+    pub fn exited(&self) -> &[Entity] {
+        &self.exited
+    }
+
+    /// Replaces the occupants, recording which entities entered and exited since the last update.
+    pub(crate) fn update_occupants(&mut self, occupants: HashSet<Entity>) {
+        self.entered = occupants
+            .iter()
+            .filter(|entity| !self.occupants.contains(entity))
+            .copied()
+            .collect();
+        self.exited = self
+            .occupants
+            .iter()
+            .filter(|entity| !occupants.contains(entity))
+            .copied()
+            .collect();
+        self.occupants = occupants;
+    }
+}
@@ -6,7 +6,7 @@ use rapier2d::prelude::*;
 
 use crate::crossbeam;
 use hecs::{Entity, World};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// A physics engine unique to a game world. This handles the RigidBodies of the game.
 pub struct PhysicsEngine {
@@ -21,6 +21,9 @@ pub struct PhysicsEngine {
     pub(crate) gravity: Vector2<f32>,
     pub(crate) ccd_solver: CCDSolver,
     pub(crate) integration_parameters: IntegrationParameters,
+    pub(crate) settings: PhysicsSettings,
+    pub(crate) collision_layers: CollisionLayers,
+    time_accumulator: f32,
     pub(crate) event_handler: ChannelEventCollector,
     pub(crate) collision_event_recv: crossbeam::channel::Receiver<CollisionEvent>,
     pub(crate) contact_force_event_recv: crossbeam::channel::Receiver<ContactForceEvent>,
@@ -30,7 +33,7 @@ pub struct PhysicsEngine {
     body_colliders: HashMap<RigidBodyHandle, Vec<ColliderHandle>>,
     collider_body: HashMap<ColliderHandle, RigidBodyHandle>,
     entity_collisions: HashMap<Entity, Vec<Entity>>,
-    physics_hooks: Box<dyn PhysicsHooks>,
+    one_way_colliders: HashSet<ColliderHandle>,
     query_pipeline: QueryPipeline,
 }
 
@@ -50,7 +53,6 @@ impl PhysicsEngine {
         let (contact_force_event_send, contact_force_event_recv) = crossbeam::channel::unbounded();
         let event_handler =
             ChannelEventCollector::new(collision_event_send, contact_force_event_send);
-        let physics_hooks = Box::new(());
         let ccd_solver = CCDSolver::new();
         let query_pipeline = QueryPipeline::new();
 
@@ -66,6 +68,9 @@ impl PhysicsEngine {
             island_manager,
             ccd_solver,
             integration_parameters: IntegrationParameters::default(),
+            settings: PhysicsSettings::default(),
+            collision_layers: CollisionLayers::new(),
+            time_accumulator: 0.0,
             collision_event_recv,
             contact_force_event_recv,
             event_handler,
@@ -74,15 +79,51 @@ impl PhysicsEngine {
             body_colliders: HashMap::new(),
             collider_body: HashMap::new(),
             entity_collisions: HashMap::new(),
-            physics_hooks,
+            one_way_colliders: HashSet::new(),
             query_pipeline,
         }
     }
 
+    pub(crate) fn set_settings(&mut self, settings: PhysicsSettings) {
+        settings.apply(&mut self.integration_parameters);
+        self.settings = settings;
+        self.time_accumulator = 0.0;
+    }
+
+    /// Advances the physics by the given frame delta, following the fixed timestep settings.
+    /// Returns the number of internal steps that were run.
+    pub(crate) fn step(&mut self, delta: f32) -> u32 {
+        let fixed_timestep = match self.settings.fixed_timestep {
+            Some(fixed_timestep) if fixed_timestep > 0.0 => fixed_timestep,
+            _ => {
+                self.step_once(delta);
+                return 1;
+            }
+        };
+
+        self.time_accumulator += delta;
+
+        let mut substeps = 0;
+        while self.time_accumulator >= fixed_timestep {
+            if substeps >= self.settings.max_substeps {
+                self.time_accumulator = 0.0;
+                break;
+            }
+
+            self.step_once(fixed_timestep);
+            self.time_accumulator -= fixed_timestep;
+            substeps += 1;
+        }
+
+        substeps
+    }
+
     #[inline]
-    pub(crate) fn step(&mut self, delta: f32) {
-        let dt = self.integration_parameters.dt;
-        self.integration_parameters.dt = delta;
+    fn step_once(&mut self, dt: f32) {
+        self.integration_parameters.dt = dt;
+        let physics_hooks = OneWayPlatformHooks {
+            one_way_colliders: &self.one_way_colliders,
+        };
 
         self.pipeline.step(
             &self.gravity,
@@ -95,11 +136,9 @@ impl PhysicsEngine {
             &mut self.impulse_joints,
             &mut self.multibody_joints,
             &mut self.ccd_solver,
-            &(*self.physics_hooks),
+            &physics_hooks,
             &self.event_handler,
         );
-
-        self.integration_parameters.dt = dt;
     }
 
     #[inline]
@@ -311,6 +350,7 @@ impl PhysicsEngine {
                 transform.translation.x,
                 transform.translation.y,
             ))
+            .rotation(transform.rotation)
             .build();
         self.add_body(entity, body, world)
     }
@@ -349,6 +389,27 @@ impl PhysicsEngine {
         self.add_collider(body_handle, collider)
     }
 
+    /// Marks the collider as a one-way platform, only blocking bodies that land on it from above.
+    #[inline]
+    pub(crate) fn set_collider_one_way(&mut self, collider_handle: ColliderHandle, one_way: bool) {
+        if let Some(collider) = self.colliders.get_mut(collider_handle) {
+            let mut active_hooks = collider.active_hooks();
+            active_hooks.set(ActiveHooks::MODIFY_SOLVER_CONTACTS, one_way);
+            collider.set_active_hooks(active_hooks);
+
+            if one_way {
+                self.one_way_colliders.insert(collider_handle);
+            } else {
+                self.one_way_colliders.remove(&collider_handle);
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_collider_one_way(&self, collider_handle: ColliderHandle) -> bool {
+        self.one_way_colliders.contains(&collider_handle)
+    }
+
     #[inline]
     pub fn build_joint(
         &mut self,
@@ -375,6 +436,13 @@ impl PhysicsEngine {
         if let Some(body_handle) = self.entity_bodies.remove(&entity) {
             self.body_entities.remove(&body_handle);
 
+            if let Some(colliders) = self.body_colliders.remove(&body_handle) {
+                for collider_handle in colliders {
+                    self.collider_body.remove(&collider_handle);
+                    self.one_way_colliders.remove(&collider_handle);
+                }
+            }
+
             if let Some(body) = self.bodies.remove(
                 body_handle,
                 &mut self.island_manager,
@@ -398,6 +466,8 @@ impl PhysicsEngine {
             &mut self.bodies,
             false,
         ) {
+            self.one_way_colliders.remove(&collider_handle);
+
             if let Some(rbh) = self.collider_body.remove(&collider_handle) {
                 if let Some(colliders) = self.body_colliders.get_mut(&rbh) {
                     let mut i = 0;
@@ -424,17 +494,63 @@ impl PhysicsEngine {
         None
     }
 
+    /// Recomputes the occupants of every area from the colliders overlapping its sensors.
+    pub(crate) fn update_areas(&mut self, world: &mut hecs::World) {
+        for (entity, area) in world.query::<&mut Area>().iter() {
+            let mut occupants = HashSet::new();
+
+            for collider_handle in &area.sensors {
+                for (h1, h2, intersecting) in self.narrow_phase.intersections_with(*collider_handle)
+                {
+                    if !intersecting {
+                        continue;
+                    }
+
+                    let other = if h1 == *collider_handle { h2 } else { h1 };
+                    let is_masked = self.colliders.get(other).is_some_and(|collider| {
+                        collider
+                            .collision_groups()
+                            .memberships
+                            .intersects(area.mask)
+                    });
+
+                    if !is_masked {
+                        continue;
+                    }
+
+                    if let Some(other_entity) = self.get_entity_from_collider(other) {
+                        if other_entity != entity {
+                            occupants.insert(other_entity);
+                        }
+                    }
+                }
+            }
+
+            area.update_occupants(occupants);
+        }
+    }
+
     #[inline]
     pub(crate) fn sync_physics_world_to_game_world(&mut self, world: &mut hecs::World) {
-        for (_id, (transform, rbh)) in world.query::<(&mut Transform, &RigidBodyHandle)>().iter() {
-            self.sync_physics_position_to_entity_position(transform, *rbh);
+        for (_id, (transform, rbh, sync)) in world
+            .query::<(&Transform, &RigidBodyHandle, Option<&PhysicsSync>)>()
+            .iter()
+        {
+            if sync.copied().unwrap_or_default().to_physics {
+                self.sync_physics_position_to_entity_position(transform, *rbh);
+            }
         }
     }
 
     #[inline]
     pub(crate) fn sync_game_world_to_physics_world(&mut self, world: &mut hecs::World) {
-        for (_id, (transform, rbh)) in world.query::<(&mut Transform, &RigidBodyHandle)>().iter() {
-            self.sync_entity_position_to_physics_position(transform, *rbh);
+        for (_id, (transform, rbh, sync)) in world
+            .query::<(&mut Transform, &RigidBodyHandle, Option<&PhysicsSync>)>()
+            .iter()
+        {
+            if sync.copied().unwrap_or_default().from_physics {
+                self.sync_entity_position_to_physics_position(transform, *rbh);
+            }
         }
     }
 
@@ -444,9 +560,10 @@ impl PhysicsEngine {
         transform: &mut Transform,
         body_handle: RigidBodyHandle,
     ) {
-        if let Some(body_transform) = self.bodies.get(body_handle) {
-            let translation = body_transform.position().translation;
+        if let Some(body) = self.bodies.get(body_handle) {
+            let translation = body.position().translation;
             transform.translation = Translation::new(translation.x, translation.y);
+            transform.rotation = body.rotation().angle();
         }
     }
 
@@ -457,17 +574,209 @@ impl PhysicsEngine {
         body_handle: RigidBodyHandle,
     ) {
         if let Some(body) = self.bodies.get_mut(body_handle) {
-            if body.is_kinematic() {
-                body.set_next_kinematic_position(Isometry::from(Vector2::new(
-                    transform.translation.x,
-                    transform.translation.y,
-                )))
-            } else {
-                body.set_translation(
-                    Vector2::new(transform.translation.x, transform.translation.y),
-                    true,
-                );
+            let position = Isometry::new(
+                Vector2::new(transform.translation.x, transform.translation.y),
+                transform.rotation,
+            );
+
+            if body.body_type() == RigidBodyType::KinematicPositionBased {
+                body.set_next_kinematic_position(position);
+                return;
             }
+
+            // Only move the body when gameplay code moved the entity, so resting bodies are able to sleep.
+            let current = body.position();
+            let moved = (current.translation.vector - position.translation.vector).norm()
+                > SYNC_EPSILON
+                || current.rotation.angle_to(&position.rotation).abs() > SYNC_EPSILON;
+
+            if moved {
+                body.set_position(position, true);
+            }
+        }
+    }
+}
+
+/// Differences in position or rotation below this are treated as the entity not having moved.
+const SYNC_EPSILON: f32 = 1e-4;
+
+/// The maximum angle, in radians, between a contact normal and the platform's up direction
+/// for the contact to be kept by a one-way platform.
+const ONE_WAY_PLATFORM_ALLOWED_ANGLE: f32 = 0.1;
+
+/// Discards contacts with one-way platforms, unless the contact pushes the other collider upwards.
+struct OneWayPlatformHooks<'a> {
+    one_way_colliders: &'a HashSet<ColliderHandle>,
+}
+impl<'a> PhysicsHooks for OneWayPlatformHooks<'a> {
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        let allowed_local_n1 = if self.one_way_colliders.contains(&context.collider1) {
+            Vector2::y()
+        } else if self.one_way_colliders.contains(&context.collider2) {
+            -Vector2::y()
+        } else {
+            return;
+        };
+
+        context.update_as_oneway_platform(&allowed_local_n1, ONE_WAY_PLATFORM_ALLOWED_ANGLE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier2d::prelude::{ColliderBuilder, RigidBodyBuilder};
+
+    use crate::{Area, PhysicsSettings, PhysicsSync, Transform, World};
+
+    #[test]
+    fn body_rotation_is_synced_to_transform() {
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(),));
+        world
+            .physics()
+            .build_body(entity, RigidBodyBuilder::dynamic().angvel(1.0))
+            .unwrap();
+
+        world.physics().step(1.0 / 60.0);
+
+        let rotation = world.get::<&Transform>(entity).unwrap().rotation;
+        assert!(rotation > 0.0);
+    }
+
+    #[test]
+    fn kinematic_body_follows_transform() {
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(),));
+        let rbh = world
+            .physics()
+            .build_body(entity, RigidBodyBuilder::kinematic_position_based())
+            .unwrap();
+
+        {
+            let mut transform = world.get::<&mut Transform>(entity).unwrap();
+            transform.translation.x = 10.0;
+            transform.rotation = 0.5;
+        }
+        world.physics().step(1.0 / 60.0);
+
+        let body = world.physics().rigid_body(rbh).unwrap().clone();
+        assert!((body.translation().x - 10.0).abs() < 1e-4);
+        assert!((body.rotation().angle() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sync_flags_choose_direction() {
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(), PhysicsSync::to_physics_only()));
+        world
+            .physics()
+            .build_body(
+                entity,
+                RigidBodyBuilder::dynamic().linvel([0.0, 60.0].into()),
+            )
+            .unwrap();
+
+        world.physics().step(1.0 / 60.0);
+
+        let transform = *world.get::<&Transform>(entity).unwrap();
+        assert_eq!(transform.translation.y, 0.0);
+    }
+
+    #[test]
+    fn fixed_timestep_carries_leftover_time() {
+        let mut world = World::new();
+        world.physics().set_settings(PhysicsSettings {
+            fixed_timestep: Some(0.25),
+            max_substeps: 4,
+            ..Default::default()
+        });
+
+        let physics_engine = world.physics_engine.as_mut().unwrap();
+        assert_eq!(physics_engine.step(0.625), 2);
+        assert_eq!(physics_engine.step(0.125), 1);
+        assert_eq!(physics_engine.step(10.0), 4);
+        assert_eq!(physics_engine.step(0.0), 0);
+    }
+
+    #[test]
+    fn area_tracks_entered_and_exited_entities() {
+        let mut world = World::new();
+        let area_entity = world.spawn((Transform::default(),));
+        world
+            .physics()
+            .build_area(
+                area_entity,
+                Area::default(),
+                vec![ColliderBuilder::cuboid(10.0, 10.0)],
+            )
+            .unwrap();
+
+        let entity = world.spawn((Transform::default(),));
+        let rbh = world
+            .physics()
+            .build_body(entity, RigidBodyBuilder::dynamic())
+            .unwrap();
+        world
+            .physics()
+            .build_collider(rbh, ColliderBuilder::ball(2.0));
+
+        world.physics().step(1.0 / 60.0);
+        {
+            let area = world.get::<&Area>(area_entity).unwrap();
+            assert_eq!(area.entered(), &[entity]);
+            assert!(area.contains(entity));
+        }
+
+        world.physics().step(1.0 / 60.0);
+        assert!(world
+            .get::<&Area>(area_entity)
+            .unwrap()
+            .entered()
+            .is_empty());
+
+        world.get::<&mut Transform>(entity).unwrap().translation.x = 100.0;
+        world.physics().step(1.0 / 60.0);
+        {
+            let area = world.get::<&Area>(area_entity).unwrap();
+            assert_eq!(area.exited(), &[entity]);
+            assert!(area.is_empty());
         }
     }
+
+    #[test]
+    fn area_ignores_solid_colliders_of_its_body() {
+        let mut world = World::new();
+        let area_entity = world.spawn((Transform::default(),));
+        let rbh = world
+            .physics()
+            .build_body(area_entity, RigidBodyBuilder::kinematic_position_based())
+            .unwrap();
+        world
+            .physics()
+            .build_collider(rbh, ColliderBuilder::cuboid(50.0, 50.0));
+        world
+            .physics()
+            .build_area(
+                area_entity,
+                Area::default(),
+                vec![ColliderBuilder::cuboid(5.0, 5.0)],
+            )
+            .unwrap();
+
+        // Overlaps the solid collider of the body, but not the area's sensor.
+        let entity = world.spawn((Transform::from_translation((30.0, 0.0)),));
+        let other_rbh = world
+            .physics()
+            .build_body(entity, RigidBodyBuilder::dynamic())
+            .unwrap();
+        world
+            .physics()
+            .build_collider(other_rbh, ColliderBuilder::ball(2.0).sensor(true));
+
+        world.physics().step(1.0 / 60.0);
+
+        let area = world.get::<&Area>(area_entity).unwrap();
+        assert!(area.is_empty());
+        assert!(area.entered().is_empty());
+    }
 }
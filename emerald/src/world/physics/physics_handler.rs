@@ -77,6 +77,48 @@ impl<'a> PhysicsHandler<'a> {
         self.physics_engine.colliders.get_mut(collider_handle)
     }
 
+    /// Builds an area on the entity from the given colliders, which are turned into sensors on the area's layer.
+    /// The colliders are attached to the entity's body, or to a new kinematic body following its transform if it has none.
+    pub fn build_area(
+        &mut self,
+        entity: Entity,
+        mut area: Area,
+        colliders: Vec<ColliderBuilder>,
+    ) -> Result<Vec<ColliderHandle>, EmeraldError> {
+        let existing_body = self
+            .world
+            .get::<&RigidBodyHandle>(entity)
+            .map(|rbh| *rbh)
+            .ok();
+        let rbh = match existing_body {
+            Some(rbh) => rbh,
+            None => self.physics_engine.build_body(
+                entity,
+                RigidBodyBuilder::kinematic_position_based(),
+                self.world,
+            )?,
+        };
+
+        let groups = InteractionGroups::new(area.layer, Group::ALL);
+        let handles: Vec<ColliderHandle> = colliders
+            .into_iter()
+            .map(|builder| {
+                let builder = builder
+                    .sensor(true)
+                    .collision_groups(groups)
+                    .active_collision_types(ActiveCollisionTypes::all());
+                self.physics_engine.build_collider(rbh, builder)
+            })
+            .collect();
+        area.sensors = handles.clone();
+
+        self.world
+            .insert_one(entity, area)
+            .map_err(|_| EmeraldError::new("Unable to insert area into entity."))?;
+
+        Ok(handles)
+    }
+
     /// Remove physics body attached to this entity.
     pub fn remove_body(&mut self, entity: Entity) -> Option<RigidBody> {
         if let Some(body) = self.physics_engine.remove_body(entity) {
@@ -88,6 +130,17 @@ impl<'a> PhysicsHandler<'a> {
         None
     }
 
+    /// Makes the collider behave as a one-way platform.
+    /// Bodies may pass through it from below and the sides, but land on it from above.
+    pub fn set_collider_one_way(&mut self, collider_handle: ColliderHandle, one_way: bool) {
+        self.physics_engine
+            .set_collider_one_way(collider_handle, one_way)
+    }
+
+    pub fn is_collider_one_way(&self, collider_handle: ColliderHandle) -> bool {
+        self.physics_engine.is_collider_one_way(collider_handle)
+    }
+
     pub fn remove_collider(&mut self, collider_handle: ColliderHandle) -> Option<Collider> {
         self.physics_engine.remove_collider(collider_handle)
     }
@@ -118,12 +171,13 @@ impl<'a> PhysicsHandler<'a> {
         self.physics_engine.cast_shape(shape, shape_cast_query)
     }
 
-    /// Steps the physics at 1/60 timestep
+    /// Advances the physics by the given frame delta.
+    /// With a fixed timestep configured in the `PhysicsSettings`, this runs as many fixed substeps as fit in the delta.
     pub fn step(&mut self, delta: f32) {
         self.step_n(1, delta);
     }
 
-    /// Steps the physics n-times at 1/60 timestep
+    /// Advances the physics n-times by the given frame delta.
     pub fn step_n(&mut self, n: u32, delta: f32) {
         self.physics_engine
             .sync_physics_world_to_game_world(&mut self.world);
@@ -134,6 +188,7 @@ impl<'a> PhysicsHandler<'a> {
 
         self.physics_engine.consume_contacts();
         self.physics_engine.update_query_pipeline();
+        self.physics_engine.update_areas(self.world);
 
         self.physics_engine
             .sync_game_world_to_physics_world(&mut self.world);
@@ -142,4 +197,40 @@ impl<'a> PhysicsHandler<'a> {
     pub fn set_gravity(&mut self, gravity: Vector2<f32>) {
         self.physics_engine.gravity = gravity;
     }
+
+    pub fn collision_layers(&self) -> &CollisionLayers {
+        &self.physics_engine.collision_layers
+    }
+
+    pub fn collision_layers_mut(&mut self) -> &mut CollisionLayers {
+        &mut self.physics_engine.collision_layers
+    }
+
+    /// Adds a named collision layer, returning its group.
+    pub fn add_collision_layer<T: Into<String>>(&mut self, name: T) -> Result<Group, EmeraldError> {
+        self.physics_engine.collision_layers.add(name)
+    }
+
+    /// Builds a query filter that only hits colliders on the named collision layers.
+    pub fn query_filter<'b, 'c, I: IntoIterator<Item = &'b str>>(
+        &self,
+        layers: I,
+    ) -> Result<QueryFilter<'c>, EmeraldError> {
+        self.physics_engine.collision_layers.query_filter(layers)
+    }
+
+    pub fn settings(&self) -> &PhysicsSettings {
+        &self.physics_engine.settings
+    }
+
+    /// Applies the given timestep and solver settings, resetting any leftover fixed timestep time.
+    pub fn set_settings(&mut self, settings: PhysicsSettings) {
+        self.physics_engine.set_settings(settings);
+    }
+
+    /// The rapier integration parameters used when stepping.
+    /// These are derived from the `PhysicsSettings`, `dt` is set by every step.
+    pub fn integration_parameters(&self) -> &IntegrationParameters {
+        &self.physics_engine.integration_parameters
+    }
 }
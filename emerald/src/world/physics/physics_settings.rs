@@ -0,0 +1,57 @@
+use rapier2d::prelude::IntegrationParameters;
+use serde::{Deserialize, Serialize};
+
+/// Timestep and solver settings of a world's physics.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PhysicsSettings {
+    /// When set, `PhysicsHandler::step` advances the physics in increments of this timestep,
+    /// carrying any leftover frame time over to the next step.
+    /// When `None`, every step advances the physics by the given frame delta.
+    pub fixed_timestep: Option<f32>,
+    /// The maximum number of fixed timesteps run by a single step.
+    /// Leftover time past this limit is dropped, so a long frame doesn't snowball into the next ones.
+    pub max_substeps: u32,
+    /// Number of velocity iterations run by the constraint solver.
+    pub velocity_iterations: usize,
+    /// Number of friction iterations run by the constraint solver.
+    pub friction_iterations: usize,
+    /// Number of position stabilization iterations run by the constraint solver.
+    pub stabilization_iterations: usize,
+    /// Maximum number of substeps run by continuous collision detection.
+    pub ccd_substeps: usize,
+    /// Error reduction parameter, the proportion of penetration corrected every step, between 0 and 1.
+    pub erp: f32,
+    /// Amount of penetration the solver will allow, in pixels.
+    pub allowed_linear_error: f32,
+    /// Distance at which contacts are predicted before colliders touch, in pixels.
+    pub prediction_distance: f32,
+}
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        let parameters = IntegrationParameters::default();
+
+        Self {
+            fixed_timestep: None,
+            max_substeps: 8,
+            velocity_iterations: parameters.max_velocity_iterations,
+            friction_iterations: parameters.max_velocity_friction_iterations,
+            stabilization_iterations: parameters.max_stabilization_iterations,
+            ccd_substeps: parameters.max_ccd_substeps,
+            erp: parameters.erp,
+            allowed_linear_error: parameters.allowed_linear_error,
+            prediction_distance: parameters.prediction_distance,
+        }
+    }
+}
+impl PhysicsSettings {
+    pub(crate) fn apply(&self, parameters: &mut IntegrationParameters) {
+        parameters.max_velocity_iterations = self.velocity_iterations;
+        parameters.max_velocity_friction_iterations = self.friction_iterations;
+        parameters.max_stabilization_iterations = self.stabilization_iterations;
+        parameters.max_ccd_substeps = self.ccd_substeps;
+        parameters.erp = self.erp;
+        parameters.allowed_linear_error = self.allowed_linear_error;
+        parameters.prediction_distance = self.prediction_distance;
+    }
+}
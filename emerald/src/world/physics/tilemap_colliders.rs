@@ -0,0 +1,494 @@
+use std::collections::BTreeMap;
+
+use hecs::Entity;
+use rapier2d::{
+    na::{Point2, Vector2},
+    prelude::{
+        ColliderBuilder, ColliderHandle, InteractionGroups, RigidBodyBuilder, RigidBodyHandle,
+    },
+};
+
+use crate::{
+    autotilemap::AutoTilemap,
+    tilemap::{TileCollision, Tilemap},
+    EmeraldError, World,
+};
+
+/// How the solid tiles of a tilemap are turned into colliders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TilemapColliderMode {
+    /// Greedily merges solid tiles into as few rectangles as possible.
+    Rectangles,
+    /// Traces the outlines of solid regions into polylines, so bodies don't snag on the seams between tiles.
+    /// Outlines are hollow, bodies that end up inside of a solid region will not be pushed out.
+    Outlines,
+}
+
+/// Builds static colliders from the contents of a `Tilemap` or `AutoTilemap`,
+/// using the collision metadata of its tileset.
+#[derive(Clone, Debug)]
+pub struct TilemapColliderBuilder {
+    pub mode: TilemapColliderMode,
+    pub collision_groups: InteractionGroups,
+    pub friction: Option<f32>,
+    pub restitution: Option<f32>,
+}
+impl Default for TilemapColliderBuilder {
+    fn default() -> Self {
+        Self {
+            mode: TilemapColliderMode::Rectangles,
+            collision_groups: InteractionGroups::all(),
+            friction: None,
+            restitution: None,
+        }
+    }
+}
+impl TilemapColliderBuilder {
+    pub fn new(mode: TilemapColliderMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn collision_groups(mut self, collision_groups: InteractionGroups) -> Self {
+        self.collision_groups = collision_groups;
+        self
+    }
+
+    pub fn friction(mut self, friction: f32) -> Self {
+        self.friction = Some(friction);
+        self
+    }
+
+    pub fn restitution(mut self, restitution: f32) -> Self {
+        self.restitution = Some(restitution);
+        self
+    }
+
+    /// Builds the colliders for the tilemap of the given entity, and attaches them to its body.
+    /// A fixed body is built for the entity if it does not have one yet.
+    /// Colliders previously built for this entity's tilemap are replaced.
+    pub fn build(
+        &self,
+        world: &mut World,
+        entity: Entity,
+    ) -> Result<Vec<ColliderHandle>, EmeraldError> {
+        let (tile_colliders, generation) = with_tilemap(world, entity, |tilemap| {
+            Ok((self.compute_tile_colliders(tilemap)?, tilemap.generation))
+        })?;
+
+        if let Ok(previous) = world.remove_one::<TilemapColliders>(entity) {
+            for handle in previous.handles {
+                world.physics().remove_collider(handle);
+            }
+        }
+
+        let existing_body = world.get::<&RigidBodyHandle>(entity).map(|rbh| *rbh).ok();
+        let rbh = match existing_body {
+            Some(rbh) => rbh,
+            None => world
+                .physics()
+                .build_body(entity, RigidBodyBuilder::fixed())?,
+        };
+
+        let mut handles = Vec::with_capacity(tile_colliders.len());
+        for (mut collider_builder, one_way) in tile_colliders {
+            collider_builder = collider_builder.collision_groups(self.collision_groups);
+
+            if let Some(friction) = self.friction {
+                collider_builder = collider_builder.friction(friction);
+            }
+
+            if let Some(restitution) = self.restitution {
+                collider_builder = collider_builder.restitution(restitution);
+            }
+
+            let handle = world.physics().build_collider(rbh, collider_builder);
+            if one_way {
+                world.physics().set_collider_one_way(handle, true);
+            }
+            handles.push(handle);
+        }
+
+        world.insert_one(
+            entity,
+            TilemapColliders {
+                builder: self.clone(),
+                handles: handles.clone(),
+                generation,
+            },
+        )?;
+
+        Ok(handles)
+    }
+
+    /// Computes the colliders of the tilemap, relative to the tilemap's transform.
+    /// Returns each collider paired with whether or not it is a one-way platform.
+    fn compute_tile_colliders(
+        &self,
+        tilemap: &Tilemap,
+    ) -> Result<Vec<(ColliderBuilder, bool)>, EmeraldError> {
+        let tile_size = Vector2::new(tilemap.tile_size.x as f32, tilemap.tile_size.y as f32);
+        let collision_at = |x: usize, y: usize| {
+            tilemap.tiles[y * tilemap.width + x].and_then(|id| tilemap.tile_collisions.get(&id))
+        };
+        let is_solid = |x: usize, y: usize| collision_at(x, y) == Some(&TileCollision::Solid);
+        let is_one_way = |x: usize, y: usize| collision_at(x, y) == Some(&TileCollision::OneWay);
+
+        let mut colliders = Vec::new();
+
+        match self.mode {
+            TilemapColliderMode::Rectangles => {
+                for rect in merge_tile_rects(tilemap.width, tilemap.height, true, is_solid) {
+                    colliders.push((rect_collider(&rect, tile_size), false));
+                }
+            }
+            TilemapColliderMode::Outlines => {
+                for outline in trace_tile_outlines(tilemap.width, tilemap.height, is_solid) {
+                    let points = outline
+                        .iter()
+                        .map(|(x, y)| {
+                            Point2::new(
+                                (*x as f32 - 0.5) * tile_size.x,
+                                (*y as f32 - 0.5) * tile_size.y,
+                            )
+                        })
+                        .collect::<Vec<Point2<f32>>>();
+                    let indices = (0..points.len() as u32)
+                        .map(|i| [i, (i + 1) % points.len() as u32])
+                        .collect();
+                    colliders.push((ColliderBuilder::polyline(points, Some(indices)), false));
+                }
+            }
+        }
+
+        // One-way platforms are only merged horizontally, so they can be landed on at every row.
+        for rect in merge_tile_rects(tilemap.width, tilemap.height, false, is_one_way) {
+            colliders.push((rect_collider(&rect, tile_size), true));
+        }
+
+        for y in 0..tilemap.height {
+            for x in 0..tilemap.width {
+                if let Some(TileCollision::Custom(points)) = collision_at(x, y) {
+                    let points = points
+                        .iter()
+                        .map(|p| Point2::new(p.x, p.y))
+                        .collect::<Vec<Point2<f32>>>();
+
+                    match ColliderBuilder::convex_hull(&points) {
+                        Some(builder) => colliders.push((
+                            builder.translation(Vector2::new(
+                                x as f32 * tile_size.x,
+                                y as f32 * tile_size.y,
+                            )),
+                            false,
+                        )),
+                        None => {
+                            return Err(EmeraldError::new(format!(
+                                "Unable to build a convex collider for the tile at {:?}",
+                                (x, y)
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(colliders)
+    }
+}
+
+/// The colliders built for a tilemap by a `TilemapColliderBuilder`.
+/// Used by the `tilemap_collider_system` to rebuild the colliders when the tilemap changes.
+pub struct TilemapColliders {
+    builder: TilemapColliderBuilder,
+    handles: Vec<ColliderHandle>,
+    generation: usize,
+}
+impl TilemapColliders {
+    pub fn handles(&self) -> &[ColliderHandle] {
+        &self.handles
+    }
+
+    pub fn builder(&self) -> &TilemapColliderBuilder {
+        &self.builder
+    }
+}
+
+/// Rebuilds the colliders of every tilemap that has changed since its colliders were built,
+/// ex. through `Tilemap::set_tile` or `AutoTilemap::bake`.
+pub fn tilemap_collider_system(world: &mut World) -> Result<(), EmeraldError> {
+    let mut to_rebuild = Vec::new();
+
+    for (entity, (tilemap_colliders, tilemap)) in
+        world.query::<(&TilemapColliders, &Tilemap)>().iter()
+    {
+        if tilemap_colliders.generation != tilemap.generation {
+            to_rebuild.push((entity, tilemap_colliders.builder.clone()));
+        }
+    }
+
+    for (entity, (tilemap_colliders, autotilemap)) in
+        world.query::<(&TilemapColliders, &AutoTilemap)>().iter()
+    {
+        if tilemap_colliders.generation != autotilemap.tilemap.generation {
+            to_rebuild.push((entity, tilemap_colliders.builder.clone()));
+        }
+    }
+
+    for (entity, builder) in to_rebuild {
+        builder.build(world, entity)?;
+    }
+
+    Ok(())
+}
+
+fn with_tilemap<T>(
+    world: &World,
+    entity: Entity,
+    f: impl FnOnce(&Tilemap) -> Result<T, EmeraldError>,
+) -> Result<T, EmeraldError> {
+    if let Ok(tilemap) = world.get::<&Tilemap>(entity) {
+        return f(&tilemap);
+    }
+
+    if let Ok(autotilemap) = world.get::<&AutoTilemap>(entity) {
+        return f(&autotilemap.tilemap);
+    }
+
+    Err(EmeraldError::new(format!(
+        "Entity {:?} does not have a Tilemap or AutoTilemap to build colliders from.",
+        entity
+    )))
+}
+
+fn rect_collider(rect: &TileRect, tile_size: Vector2<f32>) -> ColliderBuilder {
+    let half_width = rect.width as f32 * tile_size.x / 2.0;
+    let half_height = rect.height as f32 * tile_size.y / 2.0;
+
+    // Tiles are drawn centered on their position in the grid.
+    let x = (rect.x as f32 - 0.5) * tile_size.x + half_width;
+    let y = (rect.y as f32 - 0.5) * tile_size.y + half_height;
+
+    ColliderBuilder::cuboid(half_width, half_height).translation(Vector2::new(x, y))
+}
+
+/// A rectangle of tiles, given in tile coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TileRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Greedily merges the filled tiles into rectangles, first along rows then down columns.
+pub(crate) fn merge_tile_rects(
+    width: usize,
+    height: usize,
+    merge_rows: bool,
+    is_filled: impl Fn(usize, usize) -> bool,
+) -> Vec<TileRect> {
+    let mut visited = vec![false; width * height];
+    let mut rects = Vec::new();
+    let is_available =
+        |visited: &Vec<bool>, x: usize, y: usize| !visited[y * width + x] && is_filled(x, y);
+
+    for y in 0..height {
+        for x in 0..width {
+            if !is_available(&visited, x, y) {
+                continue;
+            }
+
+            let mut rect_width = 1;
+            while x + rect_width < width && is_available(&visited, x + rect_width, y) {
+                rect_width += 1;
+            }
+
+            let mut rect_height = 1;
+            if merge_rows {
+                while y + rect_height < height
+                    && (x..x + rect_width).all(|rx| is_available(&visited, rx, y + rect_height))
+                {
+                    rect_height += 1;
+                }
+            }
+
+            for ry in y..y + rect_height {
+                for rx in x..x + rect_width {
+                    visited[ry * width + rx] = true;
+                }
+            }
+
+            rects.push(TileRect {
+                x,
+                y,
+                width: rect_width,
+                height: rect_height,
+            });
+        }
+    }
+
+    rects
+}
+
+/// Traces the outlines of the filled regions, returning closed loops of tile corner coordinates.
+/// Loops wind counter-clockwise around filled regions, and clockwise around holes.
+pub(crate) fn trace_tile_outlines(
+    width: usize,
+    height: usize,
+    is_filled: impl Fn(usize, usize) -> bool,
+) -> Vec<Vec<(i64, i64)>> {
+    let filled = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < width
+            && (y as usize) < height
+            && is_filled(x as usize, y as usize)
+    };
+
+    // Directed edges between tile corners, with the filled tile on the left of each edge.
+    let mut edges: BTreeMap<(i64, i64), Vec<(i64, i64)>> = BTreeMap::new();
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if !filled(x, y) {
+                continue;
+            }
+
+            if !filled(x, y - 1) {
+                edges.entry((x, y)).or_default().push((x + 1, y));
+            }
+            if !filled(x + 1, y) {
+                edges.entry((x + 1, y)).or_default().push((x + 1, y + 1));
+            }
+            if !filled(x, y + 1) {
+                edges.entry((x + 1, y + 1)).or_default().push((x, y + 1));
+            }
+            if !filled(x - 1, y) {
+                edges.entry((x, y + 1)).or_default().push((x, y));
+            }
+        }
+    }
+
+    let mut outlines = Vec::new();
+    while let Some(&start) = edges.keys().next() {
+        let mut outline = vec![start];
+        let mut current = start;
+
+        while let Some(next) = take_edge(&mut edges, current) {
+            if next == start {
+                break;
+            }
+            outline.push(next);
+            current = next;
+        }
+
+        outlines.push(remove_collinear_points(outline));
+    }
+
+    outlines
+}
+
+fn take_edge(
+    edges: &mut BTreeMap<(i64, i64), Vec<(i64, i64)>>,
+    from: (i64, i64),
+) -> Option<(i64, i64)> {
+    let ends = edges.get_mut(&from)?;
+    let end = ends.pop();
+
+    if ends.is_empty() {
+        edges.remove(&from);
+    }
+
+    end
+}
+
+fn remove_collinear_points(outline: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let len = outline.len();
+    (0..len)
+        .filter(|i| {
+            let prev = outline[(i + len - 1) % len];
+            let point = outline[*i];
+            let next = outline[(i + 1) % len];
+            let cross =
+                (point.0 - prev.0) * (next.1 - point.1) - (point.1 - prev.1) * (next.0 - point.0);
+
+            cross != 0
+        })
+        .map(|i| outline[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_tile_rects, trace_tile_outlines, TileRect};
+
+    fn grid(rows: &[&str]) -> (usize, usize, impl Fn(usize, usize) -> bool) {
+        // Rows are given top to bottom, while tilemaps grow upwards.
+        let height = rows.len();
+        let width = rows[0].len();
+        let cells = rows
+            .iter()
+            .rev()
+            .map(|row| row.chars().map(|c| c == '#').collect::<Vec<bool>>())
+            .collect::<Vec<Vec<bool>>>();
+
+        (width, height, move |x: usize, y: usize| cells[y][x])
+    }
+
+    #[test]
+    fn merges_solid_block_into_one_rect() {
+        let (width, height, is_filled) = grid(&["###", "###"]);
+        let rects = merge_tile_rects(width, height, true, is_filled);
+
+        assert_eq!(
+            rects,
+            vec![TileRect {
+                x: 0,
+                y: 0,
+                width: 3,
+                height: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn merges_l_shape_into_two_rects() {
+        let (width, height, is_filled) = grid(&["#..", "#..", "###"]);
+        let rects = merge_tile_rects(width, height, true, is_filled);
+
+        assert_eq!(rects.len(), 2);
+        assert_eq!(rects.iter().map(|r| r.width * r.height).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn unmerged_rows_stay_one_tile_tall() {
+        let (width, height, is_filled) = grid(&["##", "##"]);
+        let rects = merge_tile_rects(width, height, false, is_filled);
+
+        assert_eq!(rects.len(), 2);
+        assert!(rects.iter().all(|r| r.height == 1 && r.width == 2));
+    }
+
+    #[test]
+    fn traces_block_outline_as_four_corners() {
+        let (width, height, is_filled) = grid(&["###", "###"]);
+        let outlines = trace_tile_outlines(width, height, is_filled);
+
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 4);
+        for corner in [(0, 0), (3, 0), (3, 2), (0, 2)] {
+            assert!(outlines[0].contains(&corner));
+        }
+    }
+
+    #[test]
+    fn traces_holes_as_separate_outlines() {
+        let (width, height, is_filled) = grid(&["###", "#.#", "###"]);
+        let outlines = trace_tile_outlines(width, height, is_filled);
+
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.iter().all(|outline| outline.len() == 4));
+    }
+}
@@ -1,11 +1,15 @@
 use rapier2d::na::Vector2;
 use serde::{Deserialize, Serialize};
 
-use crate::{ent::Vec2f32Schema, AssetLoader, EmeraldError, World};
+use crate::{ent::Vec2f32Schema, AssetLoader, EmeraldError, PhysicsSettings, World};
 
 #[derive(Deserialize, Serialize)]
 struct WorldPhysicsSchema {
     pub gravity: Option<Vec2f32Schema>,
+    /// Names of the collision layers, assigned bits in order.
+    pub layers: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub settings: PhysicsSettings,
 }
 
 pub(crate) fn load_world_physics<'a>(
@@ -21,11 +25,47 @@ pub(crate) fn load_world_physics<'a>(
 
     let schema: WorldPhysicsSchema = toml::from_str(&toml.to_string())?;
 
-    if let Some(gravity) = schema.gravity {
+    if let Some(gravity) = &schema.gravity {
         world
             .physics()
             .set_gravity(Vector2::new(gravity.x, gravity.y));
     }
 
+    if let Some(layers) = &schema.layers {
+        for layer in layers {
+            world.physics().add_collision_layer(layer.as_str())?;
+        }
+    }
+
+    if &schema.settings != world.physics().settings() {
+        world.physics().set_settings(schema.settings);
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::WorldPhysicsSchema;
+    use crate::PhysicsSettings;
+
+    #[test]
+    fn deser_physics_settings() {
+        let toml = r#"
+            gravity = { x = 0.0, y = -9.8 }
+            fixed_timestep = 0.008
+            velocity_iterations = 8
+            erp = 0.5
+        "#;
+        let schema: WorldPhysicsSchema = crate::toml::from_str(toml).unwrap();
+        let settings = schema.settings;
+
+        assert_eq!(settings.fixed_timestep, Some(0.008));
+        assert_eq!(settings.velocity_iterations, 8);
+        assert_eq!(settings.erp, 0.5);
+        assert_eq!(
+            settings.prediction_distance,
+            PhysicsSettings::default().prediction_distance
+        );
+    }
+}